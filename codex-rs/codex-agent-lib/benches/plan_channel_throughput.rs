@@ -0,0 +1,61 @@
+//! Throughput bench for the plan/todo buffering strategy behind
+//! [`codex_agent_lib::PlanChannelCapacity`], so a caller with high-frequency
+//! plan updates can pick a capacity that keeps up with its consumer.
+//!
+//! `PlanChannel` itself is crate-private, so this drives the same tokio
+//! `mpsc` primitives it wraps (bounded vs. unbounded) under an equivalent
+//! workload rather than the type directly.
+
+use codex_agent_lib::PlanChannelCapacity;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use criterion::criterion_group;
+use criterion::criterion_main;
+
+const MESSAGE_COUNTS: &[usize] = &[100, 1_000, 10_000];
+
+fn send_n_messages(rt: &tokio::runtime::Runtime, capacity: PlanChannelCapacity, n: usize) {
+    rt.block_on(async move {
+        match capacity {
+            PlanChannelCapacity::Bounded(capacity) => {
+                let (tx, mut rx) = tokio::sync::mpsc::channel::<u64>(capacity);
+                let producer = tokio::spawn(async move {
+                    for i in 0..n as u64 {
+                        let _ = tx.send(i).await;
+                    }
+                });
+                while (rx.recv().await).is_some() {}
+                let _ = producer.await;
+            }
+            PlanChannelCapacity::Unbounded => {
+                let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<u64>();
+                let producer = tokio::spawn(async move {
+                    for i in 0..n as u64 {
+                        let _ = tx.send(i);
+                    }
+                });
+                while (rx.recv().await).is_some() {}
+                let _ = producer.await;
+            }
+        }
+    });
+}
+
+fn bench_plan_channel(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let mut group = c.benchmark_group("plan_channel_throughput");
+
+    for &n in MESSAGE_COUNTS {
+        group.bench_with_input(BenchmarkId::new("bounded_64", n), &n, |b, &n| {
+            b.iter(|| send_n_messages(&rt, PlanChannelCapacity::Bounded(64), n));
+        });
+        group.bench_with_input(BenchmarkId::new("unbounded", n), &n, |b, &n| {
+            b.iter(|| send_n_messages(&rt, PlanChannelCapacity::Unbounded, n));
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_plan_channel);
+criterion_main!(benches);
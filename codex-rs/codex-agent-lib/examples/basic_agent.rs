@@ -72,12 +72,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 OutputData::ToolOutput { tool_name, output } => {
                     println!("  📝 [{}]: {}", tool_name, output);
                 }
+                OutputData::ToolOutputDelta { tool_name, chunk } => {
+                    print!("  📝 [{}]: {}", tool_name, chunk);
+                }
                 OutputData::ToolComplete { tool_name, result } => {
                     println!("  ✅ Tool {} completed: {}", tool_name, result);
                 }
                 OutputData::TodoUpdate { todos } => {
                     println!("  📋 Todo list updated: {} items", todos.len());
                 }
+                OutputData::RichOutput { mime, data } => {
+                    println!("  🖼️  [{}]: {} bytes", mime, data.len());
+                }
+                OutputData::Image { mime, data, alt } => {
+                    println!(
+                        "  🖼️  [{}]: {} bytes{}",
+                        mime,
+                        data.len(),
+                        alt.map(|a| format!(" ({a})")).unwrap_or_default()
+                    );
+                }
+                OutputData::Traceback { ename, evalue, .. } => {
+                    eprintln!("  ❌ {}: {}", ename, evalue);
+                }
                 OutputData::Reasoning(reasoning) => {
                     println!("  💭 Reasoning: {}", reasoning);
                 }
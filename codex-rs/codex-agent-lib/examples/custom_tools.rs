@@ -34,14 +34,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 },
                 "required": ["expression"]
             }),
-            handler: |args| {
+            handler: std::sync::Arc::new(|args| {
                 Box::pin(async move {
                     // Simple calculator implementation
                     let expr = args["expression"].as_str().unwrap_or("");
                     // In a real implementation, you'd evaluate the expression
                     Ok(format!("Result of '{}' = 42", expr))
                 })
-            },
+            }),
+            requires_approval: false,
         },
     ];
 
@@ -5,12 +5,18 @@
 //! 2. Accepts user input for Python code generation
 //! 3. Executes the generated code and displays results
 //! 4. Shows real-time todo list and output updates
+//!
+//! The agent definition (model, system prompt, tools, sandbox policy) is
+//! loaded from `python_agent.yaml` via [`AgentConfig::from_file`] rather
+//! than embedded in this file, so the persona can be tuned without a
+//! recompile. Requires the `templates` feature.
 
 use codex_agent_lib::Agent;
 use codex_agent_lib::AgentConfig;
+use codex_agent_lib::InputMessage;
 use codex_agent_lib::OutputData;
+use codex_agent_lib::OutputError;
 use codex_agent_lib::PlanMessage;
-use codex_agent_lib::SandboxPolicy;
 use codex_agent_lib::TodoItem;
 use crossterm::event::DisableMouseCapture;
 use crossterm::event::EnableMouseCapture;
@@ -41,6 +47,7 @@ use ratatui::widgets::Paragraph;
 use ratatui::widgets::Wrap;
 use std::error::Error;
 use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
@@ -60,6 +67,26 @@ struct App {
     tool_output: String,
     /// Whether the agent is processing
     is_processing: bool,
+    /// Number of wrapped message lines hidden above the top of the chat
+    /// viewport; `0` shows the oldest message first
+    scroll_offset: usize,
+    /// Whether the viewport stays pinned to the latest message as new
+    /// output arrives; disabled by any manual scroll, re-enabled once the
+    /// user scrolls back down to the bottom
+    follow_tail: bool,
+    /// Height (in lines) of the chat viewport as of the last draw, used to
+    /// size `PageUp`/`PageDown` jumps
+    messages_visible_height: usize,
+    /// Previously submitted user messages, oldest first; persisted to
+    /// [`history_file_path`] on exit and reloaded on startup
+    input_history: Vec<String>,
+    /// Position in `input_history` while browsing with Up/Down; `None` means
+    /// the input box holds a fresh, unbrowsed draft
+    history_index: Option<usize>,
+    /// The draft that was in progress when history browsing started, so
+    /// Down past the most recent entry restores it instead of leaving the
+    /// input blank
+    history_draft: String,
 }
 
 /// Message in the chat
@@ -67,6 +94,9 @@ struct App {
 struct Message {
     role: MessageRole,
     content: String,
+    /// Still receiving `PrimaryDelta` chunks for this message; draws a
+    /// trailing caret until a `Completed`/`Primary` event finalizes it
+    streaming: bool,
 }
 
 #[derive(Clone, PartialEq)]
@@ -74,6 +104,33 @@ enum MessageRole {
     User,
     Assistant,
     System,
+    /// An agent/tool failure or a local send failure, rendered as a
+    /// visually distinct block instead of folded into `System`
+    Error,
+}
+
+/// Path to the persisted input-history file under the user's config dir
+fn history_file_path() -> PathBuf {
+    let base = dirs::config_dir().unwrap_or_else(std::env::temp_dir);
+    base.join("codex-agent-lib")
+        .join("interactive_python_agent_history.txt")
+}
+
+/// Load previously submitted inputs, oldest first; empty if the history
+/// file doesn't exist yet (e.g. first run)
+fn load_history() -> Vec<String> {
+    std::fs::read_to_string(history_file_path())
+        .map(|contents| contents.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Persist `history` to the history file, one entry per line
+fn save_history(history: &[String]) {
+    let path = history_file_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let _ = std::fs::write(path, history.join("\n"));
 }
 
 impl App {
@@ -83,16 +140,127 @@ impl App {
             messages: vec![Message {
                 role: MessageRole::System,
                 content: "Welcome! I'll help you write and execute Python code. Let me set up the environment first...".to_string(),
+                streaming: false,
             }],
             todos: Vec::new(),
             status: "Initializing...".to_string(),
             tool_output: String::new(),
             is_processing: false,
+            scroll_offset: 0,
+            follow_tail: true,
+            messages_visible_height: 10,
+            input_history: load_history(),
+            history_index: None,
+            history_draft: String::new(),
         }
     }
 
     fn add_message(&mut self, role: MessageRole, content: String) {
-        self.messages.push(Message { role, content });
+        self.messages.push(Message {
+            role,
+            content,
+            streaming: false,
+        });
+    }
+
+    /// Append `chunk` to the in-progress assistant message, starting a new
+    /// one on the first delta of a turn (i.e. when the last message isn't
+    /// itself a still-`streaming` assistant message)
+    fn add_message_delta(&mut self, role: MessageRole, chunk: String) {
+        match self.messages.last_mut() {
+            Some(last) if last.role == role && last.streaming => {
+                last.content.push_str(&chunk);
+            }
+            _ => {
+                self.messages.push(Message {
+                    role,
+                    content: chunk,
+                    streaming: true,
+                });
+            }
+        }
+    }
+
+    /// Mark the in-progress assistant message as finished, so its trailing
+    /// streaming caret stops rendering
+    fn finalize_streaming_message(&mut self) {
+        if let Some(last) = self.messages.last_mut() {
+            last.streaming = false;
+        }
+    }
+
+    /// Scroll the chat viewport up (towards older messages) by `lines`
+    fn scroll_up(&mut self, lines: usize) {
+        self.scroll_offset = self.scroll_offset.saturating_sub(lines);
+        self.follow_tail = false;
+    }
+
+    /// Scroll the chat viewport down (towards newer messages) by `lines`
+    fn scroll_down(&mut self, lines: usize) {
+        self.scroll_offset = self.scroll_offset.saturating_add(lines);
+        self.follow_tail = false;
+    }
+
+    /// Jump to the very first message
+    fn scroll_to_top(&mut self) {
+        self.scroll_offset = 0;
+        self.follow_tail = false;
+    }
+
+    /// Jump to (and resume following) the latest message
+    fn scroll_to_bottom(&mut self) {
+        self.follow_tail = true;
+    }
+
+    /// Walk backwards into older submitted inputs, saving the current draft
+    /// the first time so Down can restore it later
+    fn history_prev(&mut self) {
+        if self.input_history.is_empty() {
+            return;
+        }
+        let next_index = match self.history_index {
+            None => {
+                self.history_draft = self.input.clone();
+                self.input_history.len() - 1
+            }
+            Some(0) => 0,
+            Some(i) => i - 1,
+        };
+        self.history_index = Some(next_index);
+        self.input = self.input_history[next_index].clone();
+    }
+
+    /// Walk forward towards more recent submitted inputs; past the most
+    /// recent entry, restores the draft that was in progress before
+    /// browsing started
+    fn history_next(&mut self) {
+        match self.history_index {
+            None => {}
+            Some(i) if i + 1 < self.input_history.len() => {
+                self.history_index = Some(i + 1);
+                self.input = self.input_history[i + 1].clone();
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.input = std::mem::take(&mut self.history_draft);
+            }
+        }
+    }
+
+    /// Editing a recalled entry turns it into a fresh draft: further Up/Down
+    /// presses start browsing anew from the most recent entry
+    fn reset_history_browse(&mut self) {
+        self.history_index = None;
+    }
+
+    /// Record a submitted message in the in-memory history, skipping a
+    /// no-op repeat of the last entry
+    fn record_history(&mut self, message: &str) {
+        if self.input_history.last().map(String::as_str) != Some(message) {
+            self.input_history.push(message.to_string());
+        }
+        self.history_index = None;
+        self.history_draft.clear();
     }
 
     fn update_todos(&mut self, todos: Vec<TodoItem>) {
@@ -121,7 +289,7 @@ impl App {
 }
 
 /// Draw the UI
-fn draw_ui(frame: &mut Frame, app: &App) {
+fn draw_ui(frame: &mut Frame, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
@@ -160,17 +328,28 @@ fn draw_ui(frame: &mut Frame, app: &App) {
                 MessageRole::User => Style::default().fg(Color::Cyan),
                 MessageRole::Assistant => Style::default().fg(Color::White),
                 MessageRole::System => Style::default().fg(Color::Yellow),
+                MessageRole::Error => Style::default()
+                    .fg(Color::Red)
+                    .add_modifier(ratatui::style::Modifier::BOLD),
             };
 
             let prefix = match msg.role {
                 MessageRole::User => "You: ",
                 MessageRole::Assistant => "Assistant: ",
                 MessageRole::System => "System: ",
+                MessageRole::Error => "Error: ",
             };
 
-            // Wrap long messages
+            // Wrap long messages, appending a trailing caret to an
+            // in-progress message so it reads as "still typing" rather than
+            // a response that stopped short
             let width = main_chunks[0].width.saturating_sub(4) as usize;
-            let wrapped = textwrap::wrap(&msg.content, width);
+            let display_content = if msg.streaming {
+                format!("{}\u{2588}", msg.content)
+            } else {
+                msg.content.clone()
+            };
+            let wrapped = textwrap::wrap(&display_content, width);
 
             wrapped
                 .into_iter()
@@ -187,14 +366,25 @@ fn draw_ui(frame: &mut Frame, app: &App) {
         })
         .collect();
 
-    // Show only the most recent messages that fit in the viewport
+    // Slice the wrapped-line buffer using the scroll offset: `follow_tail`
+    // pins the viewport to the latest lines even as more are appended;
+    // otherwise the offset holds steady (anchored from the top) so
+    // incoming tokens don't yank a manually scrolled-up viewport.
     let visible_height = main_chunks[0].height.saturating_sub(2) as usize; // Subtract borders
-    let messages_to_show: Vec<ListItem> = if all_messages.len() > visible_height {
-        let skip_count = all_messages.len() - visible_height;
-        all_messages.into_iter().skip(skip_count).collect()
-    } else {
-        all_messages
-    };
+    let total_lines = all_messages.len();
+    let max_offset = total_lines.saturating_sub(visible_height);
+
+    app.messages_visible_height = visible_height.max(1);
+    if app.follow_tail || app.scroll_offset >= max_offset {
+        app.scroll_offset = max_offset;
+        app.follow_tail = true;
+    }
+
+    let messages_to_show: Vec<ListItem> = all_messages
+        .into_iter()
+        .skip(app.scroll_offset)
+        .take(visible_height)
+        .collect();
 
     let messages_list =
         List::new(messages_to_show).block(Block::default().borders(Borders::ALL).title("Chat"));
@@ -269,12 +459,17 @@ fn draw_ui(frame: &mut Frame, app: &App) {
     frame.render_widget(tool_output, right_chunks[1]);
 
     // Input field
+    let ctrl_c_hint = if app.is_processing {
+        "Ctrl+C to cancel"
+    } else {
+        "Ctrl+C to quit"
+    };
     let input = Paragraph::new(app.input.clone())
         .style(Style::default().fg(Color::White))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Input (Enter to send, Ctrl+C to quit)"),
+                .title(format!("Input (Enter to send, {ctrl_c_hint})")),
         );
     frame.render_widget(input, chunks[2]);
 }
@@ -306,224 +501,11 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     // Create app state
     let app = Arc::new(Mutex::new(App::new()));
 
-    // Configure the agent with system prompt for Python development
-    let config = AgentConfig::builder()
-        .model("gpt-5-mini".to_string())
-        .api_key(std::env::var("OPENAI_API_KEY").ok())
-        .system_prompt(Some(
-            r#"You are a Python development assistant running in an interactive terminal-based interface. You help users write and execute Python code using `uv` for environment management.
-
-## CRITICAL REQUIREMENT
-**YOU MUST ALWAYS START BY CALLING update_plan TO CREATE A TASK LIST BEFORE DOING ANY OTHER WORK!**
-
-## Your Capabilities
-
-- Execute shell commands to set up Python environments and run scripts
-- Create and edit Python files using apply_patch
-- Track your progress with update_plan for multi-step tasks
-- Provide clear, concise updates about your actions
-
-## Available Tools
-
-- **shell**: Execute commands - IMPORTANT: Always use bash syntax, NOT nu or other shells
-- **apply_patch**: Create and edit files with precise patches
-- **update_plan**: Track task progress with step-by-step plans
-
-## CRITICAL: Shell Command Requirements
-
-**ALWAYS prefix commands with bash -c when creating files or using shell features:**
-- Use: `shell(["bash", "-c", "echo 'content' > file.py"])`
-- NOT: `shell(["echo", "content", ">", "file.py"])` (this won't work!)
-- The shell tool needs explicit bash invocation for redirects and pipes
-
-## How You Work
-
-### Planning (MANDATORY)
-**ALWAYS start by creating a plan with update_plan before doing any work!**
-- Create your plan immediately when you receive a request
-- Break tasks into 3-7 meaningful steps (5-7 words each)
-- Mark steps as: pending, in_progress, or completed
-- Always have exactly one in_progress step
-- Update the plan as you complete each step
-
-Example plan for Python setup:
-```json
-{
-  "plan": [
-    {"step": "Check uv installation", "status": "in_progress"},
-    {"step": "Initialize Python project", "status": "pending"},
-    {"step": "Create virtual environment", "status": "pending"},
-    {"step": "Create hello.py script", "status": "pending"},
-    {"step": "Run the script", "status": "pending"}
-  ]
-}
-```
-
-### Preambles
-Before tool calls, send brief updates (8-12 words) about what you're doing:
-- "Setting up Python environment with uv..."
-- "Installing required packages for data analysis..."
-- "Creating script to calculate prime numbers..."
-
-### Testing Your Work
-Always verify your Python scripts work correctly:
-- Run the script after creating it
-- Check for errors and fix them
-- Show the output to the user
-
-## Essential uv Commands and Usage
-
-### Initial Setup (do this ONCE at the start)
-1. Check if uv is installed: Run `uv --version`
-2. Initialize a Python project: Run `uv init` in the current directory
-   - This creates a pyproject.toml file and src/ directory structure
-3. Create/activate virtual environment: Run `uv venv`
-   - This creates a .venv directory with an isolated Python environment
-   - uv automatically uses this environment for all subsequent commands
-
-### Installing Packages
-- Install a package: `uv pip install package_name`
-- Install multiple packages: `uv pip install pandas numpy matplotlib`
-- Install from requirements.txt: `uv pip install -r requirements.txt`
-- Show installed packages: `uv pip list`
-
-### Running Python Scripts with uv
-IMPORTANT: Always use `uv run` to execute Python scripts to ensure the correct environment is used:
-- Run a script: `uv run python script_name.py`
-- Run with arguments: `uv run python script.py arg1 arg2`
-- Interactive Python: `uv run python`
-- Run a module: `uv run python -m module_name`
-
-### File Organization and Operations
-- Place all Python scripts in the current directory or src/ subdirectory
-- Name files descriptively: `data_analysis.py`, `web_scraper.py`, etc.
-- For simple scripts, current directory is fine
-- For larger projects, use src/ directory structure
-
-### Creating and Managing Files
-
-**Using apply_patch (preferred for complex files):**
-```bash
-apply_patch << 'EOF'
-*** Begin Patch
-*** Create File: hello.py
-def calculate_primes(n):
-    primes = []
-    for num in range(2, n + 1):
-        is_prime = True
-        for i in range(2, int(num ** 0.5) + 1):
-            if num % i == 0:
-                is_prime = False
-                break
-        if is_prime:
-            primes.append(num)
-    return primes
-
-print(calculate_primes(20))
-*** End Patch
-EOF
-```
-
-**Using bash for simple files (IMPORTANT: Use bash -c):**
-```json
-{"command": ["bash", "-c", "echo 'print(\"Hello from Python!\")' > hello.py"]}
-```
-
-**Using bash with heredoc for multi-line files:**
-```json
-{"command": ["bash", "-c", "cat > script.py << 'EOF'\nimport math\nprint(f\"Pi: {math.pi}\")\nEOF"]}
-```
-
-**Alternative: Let apply_patch handle file creation to avoid shell issues**
-
-**Other file operations:**
-- Read a file: `cat filename.py`
-- List files: `ls -la`
-- Create directory: `mkdir dirname`
-- Check if file exists: `test -f filename.py && echo "exists" || echo "not found"`
-
-### Your Workflow
-
-1. **FIRST STEP - ALWAYS**: Call update_plan to create your task list
-2. **Initial setup** (once per session) - USE BASH EXPLICITLY:
-   ```json
-   {"command": ["bash", "-c", "uv --version"]}  # Check if uv is installed
-   {"command": ["bash", "-c", "uv init"]}       # Initialize Python project
-   {"command": ["bash", "-c", "uv venv"]}       # Create virtual environment
-   ```
-
-3. **For each user request**:
-   - Send a brief preamble about what you're doing
-   - Analyze package requirements
-   - Install packages: `uv pip install <packages>`
-   - Create Python script using apply_patch or shell
-   - Execute: `uv run python script.py`
-   - Show output and verify correctness
-   - Update plan to mark steps completed
-
-### Example Workflow
-For a data analysis request:
-```bash
-# Step 1: Install packages
-uv pip install pandas matplotlib numpy
-
-# Step 2: Create analysis script
-apply_patch << 'EOF'
-*** Begin Patch
-*** Create File: analysis.py
-import pandas as pd
-import numpy as np
-import matplotlib.pyplot as plt
-
-# Generate sample data
-data = pd.DataFrame({
-    'x': np.linspace(0, 10, 100),
-    'y': np.sin(np.linspace(0, 10, 100))
-})
-
-print(f"Data shape: {data.shape}")
-print(f"Summary:\n{data.describe()}")
-*** End Patch
-EOF
-
-# Step 3: Run and verify
-uv run python analysis.py
-```
-
-## Key Principles
-
-### Environment Management
-- **Always use** `uv run python` not bare `python` - ensures correct environment
-- Virtual environment (.venv) is managed automatically by uv
-- No manual activation/deactivation needed
-- uv is faster than pip with better dependency resolution
-
-### Error Handling
-- **Package errors**: Verify spelling, suggest alternatives
-- **Script errors**: Show full output, fix iteratively (max 3 attempts)
-- **uv not found**: Guide user to install: `curl -LsSf https://astral.sh/uv/install.sh | sh`
-
-### Quality Guidelines
-- Keep code simple and readable
-- Test your scripts before marking tasks complete
-- Fix issues at root cause, not with surface patches
-- Provide concise progress updates (8-12 words)
-- Group related commands in single preambles
-
-## Final Notes
-
-- Be precise, safe, and helpful
-- Complete tasks fully before yielding to user
-- Show command outputs clearly
-- Suggest logical next steps when appropriate"#
-                .to_string(),
-        ))
-        .max_turns(100)
-        // Use DangerFullAccess to allow full file system access for uv operations
-        .sandbox_policy(SandboxPolicy::DangerFullAccess)
-        // Note: File operations are done through the shell/bash tool
-        // The Bash tool with DangerFullAccess allows all file operations
-        .build();
+    // Load the agent definition (model, system prompt, tools, sandbox
+    // policy) from python_agent.yaml instead of embedding it here, so the
+    // persona can be tuned without recompiling this example
+    let manifest_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+    let config = AgentConfig::from_file(&manifest_dir.join("examples/python_agent.yaml"))?;
 
     // Create the agent
     let agent = Agent::new(config)?;
@@ -561,29 +543,21 @@ uv run python analysis.py
                     app.set_status("Agent started".to_string());
                 }
                 OutputData::Primary(msg) => {
-                    // Check if this is a duplicate of the last message
-                    let should_add = if let Some(last_msg) = app.messages.last() {
-                        last_msg.role != MessageRole::Assistant || !last_msg.content.contains(&msg)
-                    } else {
-                        true
-                    };
-
-                    if should_add {
-                        app.add_message(MessageRole::Assistant, msg);
-                        app.clear_tool_output();
+                    // A backend that streamed deltas for this turn already
+                    // has the in-progress message on top; replace its
+                    // content with the authoritative full text and stop the
+                    // streaming caret rather than appending a duplicate
+                    match app.messages.last_mut() {
+                        Some(last) if last.role == MessageRole::Assistant && last.streaming => {
+                            last.content = msg;
+                            last.streaming = false;
+                        }
+                        _ => app.add_message(MessageRole::Assistant, msg),
                     }
+                    app.clear_tool_output();
                 }
                 OutputData::PrimaryDelta(delta) => {
-                    // Only append to existing assistant message, don't create new ones
-                    if let Some(last_msg) = app.messages.last_mut() {
-                        if last_msg.role == MessageRole::Assistant {
-                            last_msg.content.push_str(&delta);
-                        }
-                    }
-                    // If there's no assistant message yet, create one
-                    else {
-                        app.add_message(MessageRole::Assistant, delta);
-                    }
+                    app.add_message_delta(MessageRole::Assistant, delta);
                 }
                 OutputData::ToolStart {
                     tool_name,
@@ -644,17 +618,42 @@ uv run python analysis.py
                         }
                     }
                 }
-                OutputData::ToolComplete { tool_name, .. } => {
-                    app.append_tool_output(format!("âœ“ {} completed\n\n", tool_name));
+                OutputData::ToolOutputDelta { chunk, .. } => {
+                    // Unlike `ToolOutput` above, a delta is appended live as
+                    // the command produces it instead of being truncated to
+                    // the first 10 lines, so long-running commands don't
+                    // look frozen; `append_tool_output`'s size cap still
+                    // applies.
+                    let cleaned = strip_ansi_escapes::strip(&chunk);
+                    app.append_tool_output(String::from_utf8_lossy(&cleaned).into_owned());
+                }
+                OutputData::ToolComplete { tool_name, result } => {
+                    if let Some(reason) = result.strip_prefix("Error: ") {
+                        app.append_tool_output(format!("âœ— {} failed\n\n", tool_name));
+                        app.add_message(
+                            MessageRole::Error,
+                            format!("tool `{tool_name}` failed: {reason}"),
+                        );
+                        app.set_status(format!("{tool_name} failed"));
+                    } else {
+                        app.append_tool_output(format!("âœ“ {} completed\n\n", tool_name));
+                    }
                 }
                 OutputData::Completed => {
+                    app.finalize_streaming_message();
                     app.set_status("Ready".to_string());
                     app.is_processing = false;
                 }
+                OutputData::Error(OutputError::Interrupted) => {
+                    app.finalize_streaming_message();
+                    app.set_status("Cancelled".to_string());
+                    app.is_processing = false;
+                }
                 OutputData::Error(err) => {
+                    app.finalize_streaming_message();
                     eprintln!("Agent error: {:?}", err); // Debug output
-                    app.add_message(MessageRole::System, format!("Error: {:?}", err));
-                    app.set_status("Error occurred".to_string());
+                    app.add_message(MessageRole::Error, format!("{err:?}"));
+                    app.set_status(format!("Error: {err}"));
                     app.is_processing = false;
                 }
                 _ => {}
@@ -669,12 +668,13 @@ uv run python analysis.py
     // Main UI loop
     let app_ui = app.clone();
     let input_tx_clone = input_tx.clone();
+    let mut attachment_cache = attachments::AttachmentCache::new();
 
     loop {
         // Draw UI
         terminal.draw(|f| {
-            let app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
-            draw_ui(f, &app);
+            let mut app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
+            draw_ui(f, &mut app);
         })?;
 
         // Handle input - reduce polling frequency for better performance
@@ -685,38 +685,102 @@ uv run python analysis.py
                         KeyCode::Char('c')
                             if key.modifiers.contains(event::KeyModifiers::CONTROL) =>
                         {
-                            break;
+                            let is_processing =
+                                app_ui.lock().unwrap_or_else(|e| e.into_inner()).is_processing;
+                            if is_processing {
+                                // First Ctrl-C while a turn is in flight cancels
+                                // just that turn; the TUI stays up so the user
+                                // can keep working. A second Ctrl-C while idle
+                                // (handled by the `else` branch below) quits.
+                                let controller = controller.clone();
+                                tokio::spawn(async move { controller.interrupt().await });
+                            } else {
+                                break;
+                            }
                         }
                         KeyCode::Enter => {
                             let mut app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
                             if !app.input.is_empty() && !app.is_processing {
-                                let msg = app.input.clone();
+                                let raw_input = app.input.clone();
                                 app.input.clear();
-                                app.add_message(MessageRole::User, msg.clone());
+                                app.record_history(&raw_input);
+                                app.add_message(MessageRole::User, raw_input.clone());
                                 app.is_processing = true;
                                 app.set_status("Processing...".to_string());
                                 drop(app); // Release lock before sending
 
+                                // Resolve `@path/to/img.png` tokens and inline
+                                // `data:` URLs into a multimodal InputMessage
+                                let (message, images) =
+                                    attachment_cache.parse_input(&raw_input);
+                                let input_message = InputMessage { message, images };
+
                                 // Send message through channel
                                 let input_tx = input_tx_clone.clone();
+                                let app_send = app_ui.clone();
                                 tokio::spawn(async move {
-                                    let _ = input_tx.send(msg.into()).await;
+                                    if input_tx.send(input_message).await.is_err() {
+                                        let mut app = app_send.lock().unwrap_or_else(|e| e.into_inner());
+                                        app.add_message(
+                                            MessageRole::Error,
+                                            "failed to send message: agent task is no longer running"
+                                                .to_string(),
+                                        );
+                                        app.set_status("Send failed".to_string());
+                                        app.is_processing = false;
+                                    }
                                 });
                             }
                         }
                         KeyCode::Char(c) => {
                             let mut app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
+                            app.reset_history_browse();
                             app.input.push(c);
                         }
                         KeyCode::Backspace => {
                             let mut app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
+                            app.reset_history_browse();
                             app.input.pop();
                         }
+                        // Up/Down recall input history while the viewport is
+                        // pinned to the tail (the normal compose state); once
+                        // the user has scrolled back to review prior output
+                        // (`!follow_tail`), the same keys keep paging through
+                        // it instead, so the two uses don't fight over the
+                        // same keys.
                         KeyCode::Up => {
-                            // Could implement scrolling later
+                            let mut app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
+                            if app.follow_tail {
+                                app.history_prev();
+                            } else {
+                                app.scroll_up(1);
+                            }
                         }
                         KeyCode::Down => {
-                            // Could implement scrolling later
+                            let mut app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
+                            if app.follow_tail {
+                                app.history_next();
+                            } else {
+                                app.scroll_down(1);
+                            }
+                        }
+                        KeyCode::PageUp => {
+                            let mut app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
+                            let page = app.messages_visible_height;
+                            app.scroll_up(page);
+                        }
+                        KeyCode::PageDown => {
+                            let mut app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
+                            let page = app.messages_visible_height;
+                            app.scroll_down(page);
+                        }
+                        KeyCode::Home => {
+                            let mut app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
+                            app.scroll_to_top();
+                        }
+                        KeyCode::End => {
+                            let mut app = app_ui.lock().unwrap_or_else(|e| e.into_inner());
+                            app.scroll_to_bottom();
                         }
                         _ => {}
                     }
@@ -728,6 +792,9 @@ uv run python analysis.py
     // Stop the agent
     controller.stop().await;
 
+    // Persist input history for the next run
+    save_history(&app_ui.lock().unwrap_or_else(|e| e.into_inner()).input_history);
+
     // Restore terminal
     disable_raw_mode()?;
     execute!(
@@ -740,8 +807,113 @@ uv run python analysis.py
     Ok(())
 }
 
+// Parse `@path` and `data:` image references out of typed input
+mod attachments {
+    use base64::Engine;
+    use codex_agent_lib::ImageInput;
+    use sha2::Digest;
+    use std::collections::HashMap;
+    use std::io;
+    use std::path::Path;
+    use std::path::PathBuf;
+
+    /// Caches encoded attachments by the sha256 hash of their raw bytes, so
+    /// re-attaching the same image within a session reuses the already
+    /// base64-encoded `data:` URL instead of re-encoding it (the file is
+    /// still read each time to compute the hash)
+    #[derive(Default)]
+    pub struct AttachmentCache {
+        encoded: HashMap<[u8; 32], String>,
+    }
+
+    impl AttachmentCache {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Pull `@path/to/img.png` tokens and inline `data:` URLs out of
+        /// `text`, returning the remaining message text and the resolved
+        /// [`ImageInput`]s to attach alongside it
+        pub fn parse_input(&mut self, text: &str) -> (String, Vec<ImageInput>) {
+            let mut images = Vec::new();
+            let mut words = Vec::new();
+
+            for token in text.split_whitespace() {
+                if let Some(path) = token.strip_prefix('@') {
+                    match self.encode_path(Path::new(path)) {
+                        Ok(data_url) => {
+                            images.push(ImageInput::Base64(data_url));
+                            continue;
+                        }
+                        Err(err) => {
+                            tracing::warn!("failed to attach {path}: {err}");
+                        }
+                    }
+                } else if token.starts_with("data:") {
+                    images.push(ImageInput::Base64(token.to_string()));
+                    continue;
+                }
+                words.push(token);
+            }
+
+            (words.join(" "), images)
+        }
+
+        /// Read `path`, guess its MIME type, and return a base64 `data:` URL
+        fn encode_path(&mut self, path: &Path) -> io::Result<String> {
+            let bytes = std::fs::read(path)?;
+            let hash: [u8; 32] = sha2::Sha256::digest(&bytes).into();
+
+            if let Some(cached) = self.encoded.get(&hash) {
+                return Ok(cached.clone());
+            }
+
+            let mime = mime_guess::from_path(path).first_or_octet_stream();
+            let payload = base64::engine::general_purpose::STANDARD.encode(&bytes);
+            let data_url = format!("data:{mime};base64,{payload}");
+
+            self.encoded.insert(hash, data_url.clone());
+            Ok(data_url)
+        }
+    }
+
+    /// Resolve an inline `data:` URL back to a temp file, for a backend
+    /// that wants a file path rather than an embedded payload
+    #[allow(dead_code)] // reserved for backends that require a file path, not an embedded payload
+    pub fn data_url_to_temp_file(data_url: &str) -> io::Result<PathBuf> {
+        let (header, payload) = data_url
+            .split_once(',')
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a data: URL"))?;
+
+        let mime = header
+            .strip_prefix("data:")
+            .and_then(|m| m.split(';').next())
+            .unwrap_or("application/octet-stream");
+        let extension = mime_guess::get_mime_extensions_str(mime)
+            .and_then(|exts| exts.first())
+            .copied()
+            .unwrap_or("bin");
+
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(payload)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let hash = sha2::Sha256::digest(&bytes);
+        let path = std::env::temp_dir().join(format!("{hash:x}.{extension}"));
+        std::fs::write(&path, &bytes)?;
+        Ok(path)
+    }
+}
+
 // Add textwrap for message wrapping
 mod textwrap {
+    use unicode_segmentation::UnicodeSegmentation;
+    use unicode_width::UnicodeWidthStr;
+
+    /// Wrap `text` to `width` display columns, measuring grapheme clusters
+    /// (via `unicode-segmentation`) by their terminal column count (via
+    /// `unicode-width`) rather than raw bytes, so CJK text and wide emoji
+    /// wrap at the right column instead of overflowing the pane
     pub fn wrap(text: &str, width: usize) -> Vec<String> {
         if text.is_empty() {
             return vec![String::new()];
@@ -749,16 +921,35 @@ mod textwrap {
 
         let mut result = Vec::new();
         let mut current_line = String::new();
+        let mut current_width = 0;
 
         for word in text.split_whitespace() {
+            let word_width = word.width();
+
+            if word_width > width {
+                // The word alone overflows the line; hard-break it at
+                // grapheme boundaries so no line ever exceeds `width`.
+                if !current_line.is_empty() {
+                    result.push(std::mem::take(&mut current_line));
+                    current_width = 0;
+                }
+                for chunk in break_overlong_word(word, width) {
+                    result.push(chunk);
+                }
+                continue;
+            }
+
             if current_line.is_empty() {
                 current_line = word.to_string();
-            } else if current_line.len() + word.len() + 1 < width {
+                current_width = word_width;
+            } else if current_width + 1 + word_width <= width {
                 current_line.push(' ');
                 current_line.push_str(word);
+                current_width += 1 + word_width;
             } else {
-                result.push(current_line);
+                result.push(std::mem::take(&mut current_line));
                 current_line = word.to_string();
+                current_width = word_width;
             }
         }
 
@@ -768,4 +959,28 @@ mod textwrap {
 
         result
     }
+
+    /// Split a single word whose display width exceeds `width` into
+    /// grapheme-aligned chunks, each no wider than `width`
+    fn break_overlong_word(word: &str, width: usize) -> Vec<String> {
+        let mut chunks = Vec::new();
+        let mut chunk = String::new();
+        let mut chunk_width = 0;
+
+        for grapheme in word.graphemes(true) {
+            let grapheme_width = grapheme.width();
+            if chunk_width + grapheme_width > width && !chunk.is_empty() {
+                chunks.push(std::mem::take(&mut chunk));
+                chunk_width = 0;
+            }
+            chunk.push_str(grapheme);
+            chunk_width += grapheme_width;
+        }
+
+        if !chunk.is_empty() {
+            chunks.push(chunk);
+        }
+
+        chunks
+    }
 }
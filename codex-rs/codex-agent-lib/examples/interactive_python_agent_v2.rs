@@ -21,9 +21,16 @@ async fn main() -> Result<()> {
     // Create Python developer agent from template
     let agent = Agent::from_template(templates::python_developer())?;
     
+    // Persist input history (Up/Down recall) across runs
+    let history_path = dirs::config_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("codex-agent-lib")
+        .join("interactive_python_agent_history.txt");
+
     // Run interactive TUI with initial setup prompt
     AgentTui::new()
         .with_title("Python Development Assistant")
+        .with_history_file(history_path)
         .run(
             agent,
             Some("Please set up a Python environment using uv. First check if uv is installed, then initialize a project with uv init, create a virtual environment with uv venv. Then create a simple hello.py script that calculates and prints the first 20 prime numbers, and run it using 'uv run python hello.py' to verify everything works.".to_string())
@@ -0,0 +1,25 @@
+//! REPL agent example: a fast-building alternative to `tui_agent`
+
+#[cfg(feature = "repl")]
+use codex_agent_lib::prelude::*;
+
+#[cfg(feature = "repl")]
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter("codex_agent_lib=info")
+        .init();
+
+    let agent = Agent::from_template(templates::python_developer())?;
+
+    Repl::new()
+        .with_prompt("codex> ")
+        .run(agent)
+        .await
+}
+
+#[cfg(not(feature = "repl"))]
+fn main() {
+    eprintln!("This example requires the 'repl' feature. Run with:");
+    eprintln!("cargo run --example repl_agent --features repl,templates");
+}
@@ -2,26 +2,36 @@
 
 #[cfg(feature = "tui")]
 use codex_agent_lib::prelude::*;
+#[cfg(feature = "tui")]
+use codex_agent_lib::tui::RollingFileSink;
+#[cfg(feature = "tui")]
+use tracing_subscriber::layer::SubscriberExt;
+#[cfg(feature = "tui")]
+use tracing_subscriber::util::SubscriberInitExt;
 
 #[cfg(feature = "tui")]
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize logging to file
-    if let Ok(log_file) = std::fs::File::create("tui_agent.log") {
-        tracing_subscriber::fmt()
-            .with_env_filter("codex_agent_lib=debug")
-            .with_writer(log_file)
-            .with_ansi(false)
-            .init();
-    }
-    
     // Create agent from template
     let agent = Agent::from_template(templates::python_developer())?;
-    
+
     // Run TUI application
     let mut tui = AgentTui::new()
         .with_title("Python Development Assistant");
-    
+
+    // Feed logs into the TUI's in-app log pane (toggle with F2) and a
+    // size-rotated file, instead of a plain unbounded log file
+    if let Ok(file_sink) = RollingFileSink::new(".", "tui_agent", 1_000_000, 5) {
+        let _ = tracing_subscriber::registry()
+            .with(tui.log_layer())
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(file_sink)
+                    .with_ansi(false),
+            )
+            .try_init();
+    }
+
     tui.run(
         agent,
         Some("Please set up a Python environment with uv and create a hello world script".to_string())
@@ -1,12 +1,15 @@
 //! Core agent implementation
 
 use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 use tokio::sync::RwLock;
+use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_util::sync::CancellationToken;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -21,6 +24,7 @@ use codex_core::protocol::Op;
 use codex_login::CodexAuth;
 
 use crate::config::AgentConfig;
+use crate::config::ModelProviderKind;
 use crate::error::AgentError;
 use crate::error::OutputError;
 use crate::error::Result;
@@ -29,12 +33,16 @@ use crate::message::OutputData;
 use crate::message::OutputMessage;
 use crate::message::PlanMessage;
 use crate::message::PlanMetadata;
+use crate::message::TextChange;
+use crate::message::TurnProgress;
+use crate::tool::ToolChunk;
 use std::ops::ControlFlow;
 use tokio_stream::Stream;
 use tokio_stream::StreamExt;
 
 /// Current state of the agent
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AgentState {
     /// Agent is initialized but not running
     Initialized,
@@ -52,6 +60,12 @@ pub enum AgentState {
     Error,
 }
 
+impl Default for AgentState {
+    fn default() -> Self {
+        Self::Initialized
+    }
+}
+
 /// Handle to control a running agent execution
 pub struct AgentExecutionHandle {
     task_handle: JoinHandle<Result<()>>,
@@ -72,12 +86,66 @@ impl AgentExecutionHandle {
     }
 }
 
+impl Drop for AgentExecutionHandle {
+    /// Cancel the execution's token and abort its spawned task, so a
+    /// dropped handle (e.g. one discarded on an early error path) never
+    /// leaves the conversation/event tasks running in the background
+    fn drop(&mut self) {
+        self.controller.cancel_token.cancel();
+        self.task_handle.abort();
+    }
+}
+
+/// How long [`Agent::process_events`] keeps reading events after
+/// cancellation, to pick up the `TurnAborted`/shutdown confirmation
+/// triggered by the `Op::Interrupt`/`Op::Shutdown` [`Agent::run_agent_loop`]
+/// submits in response to the same cancellation, before giving up
+const CANCEL_DRAIN_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Capacity of [`AgentController::output_broadcast`] and
+/// [`AgentController::plan_broadcast`]. A subscriber that falls this far
+/// behind the publisher silently skips the messages it missed (the lag
+/// error is dropped by [`AgentController::subscribe`]) rather than
+/// block the run loop, since the broadcast fan-out is a best-effort
+/// secondary observer, not the backpressure-sensitive primary path (that's
+/// what the mpsc channels passed to [`Agent::execute`] are for).
+const BROADCAST_CAPACITY: usize = 256;
+
 /// Controller for managing a running agent
 #[derive(Clone)]
 pub struct AgentController {
     state: Arc<RwLock<AgentState>>,
-    should_stop: Arc<AtomicBool>,
+    /// Cancelled by [`AgentController::stop`] and raced via `tokio::select!`
+    /// against `input_rx.recv()` in [`Agent::run_agent_loop`] and
+    /// `conversation.next_event()` in [`Agent::process_events`], so a
+    /// request to stop doesn't wait for the next poll of an `AtomicBool`
+    cancel_token: CancellationToken,
     turn_counter: Arc<AtomicU64>,
+    /// Set once [`Agent::run_agent_loop`] has a live conversation, so
+    /// [`AgentController::interrupt`] can reach it from outside the
+    /// execution task (e.g. a UI's Ctrl-C handler)
+    conversation: Arc<RwLock<Option<Arc<codex_core::CodexConversation>>>>,
+    /// Fan-out of every `OutputMessage` [`Agent::process_events`] emits, so
+    /// several independent observers (a TUI renderer, a logger, a metrics
+    /// collector) can attach to the same live conversation via
+    /// [`AgentController::subscribe`] without cloning the agent
+    output_broadcast: broadcast::Sender<OutputMessage>,
+    /// Fan-out counterpart of `output_broadcast` for plan updates; see
+    /// [`AgentController::subscribe_plan`]
+    plan_broadcast: broadcast::Sender<PlanMessage>,
+    /// Turns that [`Agent::process_events`] has fully settled (its
+    /// `TaskComplete`/`TurnAborted` observed and every `OutputMessage`
+    /// queued), so [`AgentController::sync`] for an already-settled turn
+    /// returns immediately instead of waiting on a barrier that will never
+    /// fire again
+    settled_turns: Arc<RwLock<std::collections::HashSet<u64>>>,
+    /// Per-turn settle barriers, removed once fired so the map doesn't grow
+    /// unboundedly across a long-running agent; see [`AgentController::sync`]
+    turn_barriers: Arc<tokio::sync::Mutex<std::collections::HashMap<u64, Arc<tokio::sync::Notify>>>>,
+    /// Set once [`Agent::run_agent_loop`] spawns its
+    /// [`crate::plan_channel::PlanChannel`], so [`AgentController::plan_metrics`]
+    /// can reach its throughput counters from outside the execution task
+    plan_metrics: Arc<RwLock<Option<Arc<crate::plan_channel::PlanChannelMetrics>>>>,
 }
 
 impl AgentController {
@@ -87,8 +155,13 @@ impl AgentController {
     }
 
     /// Stop the agent
+    ///
+    /// Cancels the shared [`CancellationToken`], which `run_agent_loop` and
+    /// `process_events` are both racing against via `tokio::select!`, so an
+    /// in-flight `conversation.next_event()` or a long-running exec is
+    /// interrupted immediately rather than on the next poll.
     pub async fn stop(&self) {
-        self.should_stop.store(true, Ordering::SeqCst);
+        self.cancel_token.cancel();
         *self.state.write().await = AgentState::Stopped;
     }
 
@@ -97,6 +170,20 @@ impl AgentController {
         *self.state.write().await = AgentState::Paused;
     }
 
+    /// Cancel the in-flight turn without tearing down the agent
+    ///
+    /// Submits [`Op::Interrupt`] to the live conversation, if one has been
+    /// established yet. The conversation responds with `EventMsg::TurnAborted`,
+    /// which [`Agent::process_events`] already surfaces as
+    /// [`OutputError::Interrupted`] through the normal output channel, so
+    /// callers observe the cancellation the same way they observe any other
+    /// turn-ending event. A no-op when no turn is in flight.
+    pub async fn interrupt(&self) {
+        if let Some(conversation) = self.conversation.read().await.as_ref() {
+            let _ = conversation.submit(Op::Interrupt).await;
+        }
+    }
+
     /// Resume the agent
     pub async fn resume(&self) {
         let mut state = self.state.write().await;
@@ -109,6 +196,81 @@ impl AgentController {
     pub fn turn_count(&self) -> u64 {
         self.turn_counter.load(Ordering::SeqCst)
     }
+
+    /// Seed the turn counter
+    ///
+    /// Used when resuming a persisted session so `max_turns` accounting
+    /// continues from where a previous process left off, rather than
+    /// restarting at zero.
+    pub fn set_turn_count(&self, count: u64) {
+        self.turn_counter.store(count, Ordering::SeqCst);
+    }
+
+    /// Wait until `turn_id` has fully settled: its `TaskComplete`/`TurnAborted`
+    /// has been observed by [`Agent::process_events`] and every
+    /// `OutputMessage` it queued has been sent. Resolves immediately if the
+    /// turn already settled before this call, so test harnesses and
+    /// step-by-step drivers can gate deterministically on "turn N is done"
+    /// instead of sleeping or polling [`AgentController::turn_count`].
+    pub async fn sync(&self, turn_id: u64) {
+        let notify = {
+            let mut barriers = self.turn_barriers.lock().await;
+            barriers
+                .entry(turn_id)
+                .or_insert_with(|| Arc::new(tokio::sync::Notify::new()))
+                .clone()
+        };
+
+        // Register interest in the notification before checking whether the
+        // turn already settled, so a `mark_turn_settled` that races in
+        // between the check and the `.await` below still wakes us.
+        let notified = notify.notified();
+        if self.settled_turns.read().await.contains(&turn_id) {
+            return;
+        }
+        notified.await;
+    }
+
+    /// Record `turn_id` as fully settled and wake any [`AgentController::sync`]
+    /// callers waiting on it
+    async fn mark_turn_settled(&self, turn_id: u64) {
+        self.settled_turns.write().await.insert(turn_id);
+        if let Some(notify) = self.turn_barriers.lock().await.remove(&turn_id) {
+            notify.notify_waiters();
+        }
+    }
+
+    /// Subscribe to every `OutputMessage` published from this point on.
+    ///
+    /// Unlike the mpsc `output_tx` passed to [`Agent::execute`], any number
+    /// of subscribers can attach at once, and a subscriber that joins
+    /// mid-run simply starts receiving from the next message rather than
+    /// needing to be wired in before the run starts. A subscriber that lags
+    /// more than `BROADCAST_CAPACITY` messages behind silently skips ahead
+    /// to the oldest message still retained, reported via
+    /// `BroadcastStreamRecvError` and dropped from this stream.
+    pub fn subscribe(&self) -> impl Stream<Item = OutputMessage> + '_ {
+        BroadcastStream::new(self.output_broadcast.subscribe()).filter_map(|msg| msg.ok())
+    }
+
+    /// Plan-update counterpart of [`AgentController::subscribe`]
+    pub fn subscribe_plan(&self) -> impl Stream<Item = PlanMessage> + '_ {
+        BroadcastStream::new(self.plan_broadcast.subscribe()).filter_map(|msg| msg.ok())
+    }
+
+    /// Fan `message` out to every [`AgentController::subscribe_plan`]
+    /// subscriber; used by [`crate::plan_channel::PlanChannel`]'s
+    /// forwarding task, which otherwise has no access to the private
+    /// `plan_broadcast` field
+    pub(crate) fn publish_plan_broadcast(&self, message: &PlanMessage) {
+        let _ = self.plan_broadcast.send(message.clone());
+    }
+
+    /// Throughput counters for the internal plan/todo buffer, once
+    /// [`Agent::execute`] has started; `None` before the first run
+    pub async fn plan_metrics(&self) -> Option<Arc<crate::plan_channel::PlanChannelMetrics>> {
+        self.plan_metrics.read().await.clone()
+    }
 }
 
 /// The main agent struct
@@ -121,12 +283,31 @@ pub struct Agent {
 
 impl Agent {
     /// Create a new agent with the given configuration
+    ///
+    /// Fails fast with a clear [`AgentError::ConfigError`] if `config`
+    /// requests tools on a provider family that doesn't support
+    /// tool/function calling, rather than letting the first turn fail
+    /// opaquely against the model API.
     pub fn new(config: AgentConfig) -> Result<Self> {
+        if !config.tools.is_empty() && !config.provider_kind.supports_tool_calling() {
+            return Err(AgentError::ConfigError(format!(
+                "provider {:?} does not support tool/function calling, but {} tool(s) were configured",
+                config.provider_kind,
+                config.tools.len()
+            )));
+        }
+
         let conversation_manager = Arc::new(ConversationManager::default());
         let controller = AgentController {
             state: Arc::new(RwLock::new(AgentState::Initialized)),
-            should_stop: Arc::new(AtomicBool::new(false)),
+            cancel_token: CancellationToken::new(),
             turn_counter: Arc::new(AtomicU64::new(0)),
+            conversation: Arc::new(RwLock::new(None)),
+            output_broadcast: broadcast::channel(BROADCAST_CAPACITY).0,
+            plan_broadcast: broadcast::channel(BROADCAST_CAPACITY).0,
+            settled_turns: Arc::new(RwLock::new(std::collections::HashSet::new())),
+            turn_barriers: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
+            plan_metrics: Arc::new(RwLock::new(None)),
         };
 
         Ok(Self {
@@ -135,35 +316,66 @@ impl Agent {
             controller,
         })
     }
-    
+
     /// Create an agent from a template configuration
     #[cfg(feature = "templates")]
     pub fn from_template(config: AgentConfig) -> Result<Self> {
         Self::new(config)
     }
-    
-    /// Simple request-response pattern - sends a prompt and collects the complete response
-    pub async fn query(&mut self, prompt: &str) -> Result<String> {
-        let (input_tx, input_rx) = mpsc::channel(1);
-        let (plan_tx, _plan_rx) = mpsc::channel(100);
-        let (output_tx, mut output_rx) = mpsc::channel(100);
-        
-        // Clone self for the execution
-        let agent = Self {
+
+    /// Spawn a background task that cancels this agent's execution on
+    /// `SIGINT` (Ctrl-C), for a clean shutdown without the caller having to
+    /// wire its own signal handler into [`AgentController::stop`]
+    pub fn with_signal_handling(self) -> Self {
+        let cancel_token = self.controller.cancel_token.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancel_token.cancel();
+            }
+        });
+        self
+    }
+
+    /// Clone `self` with a fresh [`AgentController`], for spawning an
+    /// independent conversation that shares this agent's configuration (and
+    /// thus its model/auth/tools) without sharing its running state --
+    /// used by [`Agent::query`] and [`Agent::run_plan`], each of which drive
+    /// their own short-lived conversation(s) rather than the long-running
+    /// one `self` might already be attached to.
+    fn scoped_clone(&self) -> Self {
+        Self {
             config: self.config.clone(),
             conversation_manager: self.conversation_manager.clone(),
             controller: AgentController {
                 state: Arc::new(RwLock::new(AgentState::Initialized)),
-                should_stop: Arc::new(AtomicBool::new(false)),
+                cancel_token: CancellationToken::new(),
                 turn_counter: Arc::new(AtomicU64::new(0)),
+                conversation: Arc::new(RwLock::new(None)),
+                output_broadcast: broadcast::channel(BROADCAST_CAPACITY).0,
+                plan_broadcast: broadcast::channel(BROADCAST_CAPACITY).0,
+                settled_turns: Arc::new(RwLock::new(std::collections::HashSet::new())),
+                turn_barriers: Arc::new(tokio::sync::Mutex::new(std::collections::HashMap::new())),
+                plan_metrics: Arc::new(RwLock::new(None)),
             },
-        };
-        
+        }
+    }
+
+    /// Simple request-response pattern - sends a prompt and collects the complete response
+    pub async fn query(&mut self, prompt: &str) -> Result<String> {
+        let (input_tx, input_rx) = mpsc::channel(1);
+        let (plan_tx, _plan_rx) = mpsc::channel(100);
+        let (output_tx, mut output_rx) = mpsc::channel(100);
+
+        let agent = self.scoped_clone();
+
         let handle = agent.execute(input_rx, plan_tx, output_tx).await?;
-        
+
         // Send the prompt
-        input_tx.send(prompt.into()).await.map_err(|_| AgentError::ChannelError)?;
-        
+        input_tx
+            .send(prompt.into())
+            .await
+            .map_err(|_| AgentError::ChannelError)?;
+
         // Collect the response
         let mut response = String::new();
         while let Some(output) = output_rx.recv().await {
@@ -176,14 +388,62 @@ impl Agent {
                 _ => {}
             }
         }
-        
+
         // Stop the agent
         handle.controller().stop().await;
         let _ = handle.join().await;
-        
+
         Ok(response)
     }
-    
+
+    /// Run `todos` as a dependency graph via [`crate::scheduler::DagScheduler`],
+    /// executing each ready task as an independent query against this
+    /// agent's configuration (see [`Agent::scoped_clone`]) -- the task's
+    /// `content` becomes the prompt, and the task is `Completed` if the
+    /// query succeeds or `Blocked` (with the query's error as the reason)
+    /// if it fails. Tasks with satisfied dependencies run concurrently, up
+    /// to `parallelism`; `status_tx` receives one [`crate::message::ExecutionStatus`]
+    /// per task so a caller can render progress the same way it would for
+    /// [`crate::message::PlanMessage`] updates.
+    pub async fn run_plan(
+        &self,
+        todos: Vec<crate::message::TodoItem>,
+        parallelism: usize,
+        status_tx: mpsc::Sender<crate::message::ExecutionStatus>,
+    ) -> Vec<crate::message::TodoItem> {
+        let scheduler = crate::scheduler::DagScheduler::new(parallelism);
+        let agent = self.clone();
+        scheduler
+            .run(todos, status_tx, move |todo| {
+                let mut task_agent = agent.scoped_clone();
+                async move { task_agent.query(&todo.content).await.map(|_| ()).map_err(|e| e.to_string()) }
+            })
+            .await
+    }
+
+    /// Run `handler` as a standalone streaming tool call, forwarding its
+    /// [`crate::tool::ToolChunk`]s onto `output_tx` via
+    /// [`Self::forward_tool_stream`] as `OutputData::ToolOutputDelta`/
+    /// `ToolComplete`/`Error` -- the same shape a model-initiated tool call
+    /// produces, so a caller can drive a
+    /// [`crate::tool::StreamingToolHandler`] (e.g. one registered for a
+    /// `ToolConfig::Custom` tool) through the regular output pipeline
+    /// instead of polling its chunk channel directly.
+    pub async fn run_streaming_tool(
+        handler: &dyn crate::tool::StreamingToolHandler,
+        tool_name: impl Into<String>,
+        arguments: serde_json::Value,
+        turn_id: u64,
+        output_tx: &mpsc::Sender<OutputMessage>,
+    ) -> Result<()> {
+        let (chunk_tx, chunk_rx) =
+            crate::tool::tool_stream_channel(crate::tool::DEFAULT_STREAM_CAPACITY);
+        let forward = Self::forward_tool_stream(tool_name.into(), turn_id, chunk_rx, output_tx);
+        let run = handler.execute_streaming(arguments, chunk_tx);
+        let (_, result) = tokio::join!(forward, run);
+        result
+    }
+
     /// Interactive session with callback for each message
     pub async fn interactive<F>(
         self,
@@ -195,9 +455,9 @@ impl Agent {
         let (input_tx, input_rx) = mpsc::channel(100);
         let (plan_tx, _plan_rx) = mpsc::channel(100);
         let (output_tx, mut output_rx) = mpsc::channel(100);
-        
+
         let handle = self.execute(input_rx, plan_tx, output_tx).await?;
-        
+
         // Spawn handler task
         tokio::spawn(async move {
             while let Some(msg) = output_rx.recv().await {
@@ -206,30 +466,26 @@ impl Agent {
                 }
             }
         });
-        
+
         Ok((input_tx, handle))
     }
-    
+
     /// Stream responses as they arrive
-    pub fn stream(
-        self,
-        prompt: String,
-    ) -> impl Stream<Item = Result<OutputMessage>> {
+    pub fn stream(self, prompt: String) -> impl Stream<Item = Result<OutputMessage>> {
         let (input_tx, input_rx) = mpsc::channel(1);
         let (plan_tx, _plan_rx) = mpsc::channel(100);
         let (output_tx, output_rx) = mpsc::channel(100);
-        
+
         // Create the stream
-        let stream = tokio_stream::wrappers::ReceiverStream::new(output_rx)
-            .map(Ok);
-        
+        let stream = tokio_stream::wrappers::ReceiverStream::new(output_rx).map(Ok);
+
         // Start the agent
         tokio::spawn(async move {
             match self.execute(input_rx, plan_tx, output_tx).await {
                 Ok(handle) => {
                     // Send the prompt
                     let _ = input_tx.send(prompt.into()).await;
-                    
+
                     // Wait for completion
                     let _ = handle.join().await;
                 }
@@ -238,7 +494,7 @@ impl Agent {
                 }
             }
         });
-        
+
         stream
     }
 
@@ -300,36 +556,76 @@ impl Agent {
 
         info!("Started conversation {}", conversation_id);
 
+        // Publish the conversation so `AgentController::interrupt` can reach
+        // it from outside this task
+        *self.controller.conversation.write().await = Some(conversation.clone());
+
         // Start the event processing task
         let conversation_clone = conversation.clone();
-        let plan_tx_clone = plan_tx.clone();
         let output_tx_clone = output_tx.clone();
         let controller_clone = self.controller.clone();
+        let mut event_handlers = self.config.event_handlers.clone();
+        #[cfg(feature = "audit")]
+        if let Some(sink) = self.config.audit_sink.clone() {
+            let (_, approval_policy, sandbox_policy) = self.config.profile_resolved();
+            crate::audit::install(sink, sandbox_policy, approval_policy, &mut event_handlers);
+        }
 
+        let plan_channel = crate::plan_channel::PlanChannel::spawn(
+            self.config.plan_channel_capacity,
+            plan_tx,
+            self.controller.clone(),
+        );
+        *self.controller.plan_metrics.write().await = Some(plan_channel.metrics());
+
+        let retry_config = self.config.retry;
         let event_task = tokio::spawn(async move {
             Self::process_events(
                 conversation_clone,
-                plan_tx_clone,
+                plan_channel,
                 output_tx_clone,
                 controller_clone,
+                event_handlers,
+                retry_config,
             )
             .await
         });
 
-        // Process input messages
-        while let Some(input_msg) = input_rx.recv().await {
-            // Check if we should stop
-            if self.controller.should_stop.load(Ordering::SeqCst) {
-                break;
-            }
+        // Give slow backends a chance to warm up before the first turn
+        if !self.config.bootstrap.is_zero() {
+            tokio::time::sleep(self.config.bootstrap).await;
+        }
+
+        // Process input messages. Cancellation is raced against
+        // `input_rx.recv()` rather than polled, so a blocked-on-empty-
+        // channel turn still reacts to `AgentController::stop` immediately.
+        let mut cancelled = false;
+        loop {
+            let input_msg = tokio::select! {
+                biased;
+                () = self.controller.cancel_token.cancelled() => {
+                    cancelled = true;
+                    break;
+                }
+                maybe_input = input_rx.recv() => {
+                    match maybe_input {
+                        Some(input_msg) => input_msg,
+                        None => break,
+                    }
+                }
+            };
 
             // Check if paused
             while *self.controller.state.read().await == AgentState::Paused {
                 tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                if self.controller.should_stop.load(Ordering::SeqCst) {
+                if self.controller.cancel_token.is_cancelled() {
+                    cancelled = true;
                     break;
                 }
             }
+            if cancelled {
+                break;
+            }
 
             // Check turn limit
             let turn_count = self.controller.turn_counter.load(Ordering::SeqCst);
@@ -343,27 +639,64 @@ impl Agent {
                 break;
             }
 
-            // Submit the input to the conversation
-            let input_items = vec![InputItem::Text {
+            // Submit the input to the conversation, retrying recoverable
+            // errors with exponential backoff before surfacing a terminal one
+            let mut input_items = vec![InputItem::Text {
                 text: input_msg.message,
             }];
+            input_items.extend(input_msg.images.into_iter().map(InputItem::from));
+
+            let mut attempt = 0u32;
+            loop {
+                let op = Op::UserInput {
+                    items: input_items.clone(),
+                };
+
+                match conversation.submit(op).await {
+                    Ok(_) => break,
+                    Err(e) => {
+                        let output_err = OutputError::from(e);
+                        if output_err.is_recoverable() && attempt < self.config.retry.max_attempts {
+                            let delay = self.config.retry.delay_for_attempt(attempt);
+                            attempt += 1;
+                            let _ = output_tx
+                                .send(OutputMessage {
+                                    turn_id: turn_count,
+                                    data: OutputData::Detail(format!(
+                                        "Retrying after recoverable error ({output_err}), \
+                                         attempt {attempt}/{} in {delay:?}",
+                                        self.config.retry.max_attempts
+                                    )),
+                                })
+                                .await;
+                            tokio::time::sleep(delay).await;
+                            continue;
+                        }
 
-            let op = Op::UserInput { items: input_items };
-
-            if let Err(e) = conversation.submit(op).await {
-                error!("Failed to submit input: {}", e);
-                let _ = output_tx
-                    .send(OutputMessage {
-                        turn_id: turn_count,
-                        data: OutputData::Error(OutputError::from(e)),
-                    })
-                    .await;
+                        error!("Failed to submit input: {}", output_err);
+                        let _ = output_tx
+                            .send(OutputMessage {
+                                turn_id: turn_count,
+                                data: OutputData::Error(output_err),
+                            })
+                            .await;
+                        break;
+                    }
+                }
             }
 
             // Increment turn counter
             self.controller.turn_counter.fetch_add(1, Ordering::SeqCst);
         }
 
+        // If we exited because the token was cancelled rather than because
+        // `input_rx` closed, abort whatever turn is in flight before the
+        // shutdown handshake, so `process_events` sees a `TurnAborted`
+        // instead of waiting on a turn that will never finish on its own.
+        if cancelled {
+            let _ = conversation.submit(Op::Interrupt).await;
+        }
+
         // Send shutdown signal
         let _ = conversation.submit(Op::Shutdown).await;
 
@@ -376,64 +709,209 @@ impl Agent {
         Ok(())
     }
 
+    /// Send an `OutputMessage` to the single mpsc consumer passed to
+    /// [`Agent::execute`] and fan it out to every [`AgentController::subscribe`]
+    /// subscriber. The broadcast send is fire-and-forget: with no
+    /// subscribers attached it simply returns an error we ignore, which is
+    /// the expected steady state for callers that only use the mpsc path.
+    pub(crate) async fn publish_output(
+        output_tx: &mpsc::Sender<OutputMessage>,
+        controller: &AgentController,
+        message: OutputMessage,
+    ) {
+        let _ = controller.output_broadcast.send(message.clone());
+        let _ = output_tx.send(message).await;
+    }
+
+    /// Plan-update counterpart of [`Agent::publish_output`]: buffers
+    /// `message` through `plan_channel` instead of sending directly, and
+    /// surfaces a full buffer or a dead forwarding task as an
+    /// `OutputData::Error(OutputError::Unknown)` instead of silently dropping
+    /// the update; the run itself carries on
+    async fn publish_plan(
+        plan_channel: &crate::plan_channel::PlanChannel,
+        output_tx: &mpsc::Sender<OutputMessage>,
+        controller: &AgentController,
+        turn_id: u64,
+        message: PlanMessage,
+    ) {
+        if let Err(err) = plan_channel.send(message).await {
+            Self::publish_output(
+                output_tx,
+                controller,
+                OutputMessage {
+                    turn_id,
+                    data: OutputData::Error(OutputError::Unknown(err.to_string())),
+                },
+            )
+            .await;
+        }
+    }
+
+    /// Derive a [`TurnProgress::InProgress`] snapshot from a todo list:
+    /// `current` counts steps that have reached [`crate::message::TodoStatus::Completed`],
+    /// `total` is the step count, and `unit` is `"steps"`.
+    fn plan_progress(todos: &[crate::message::TodoItem]) -> TurnProgress {
+        let total = todos.len() as u64;
+        let current = todos
+            .iter()
+            .filter(|todo| todo.status == crate::message::TodoStatus::Completed)
+            .count() as u64;
+        TurnProgress::InProgress {
+            current,
+            total,
+            unit: "steps".to_string(),
+        }
+    }
+
     /// Process events from the conversation
     async fn process_events(
         conversation: Arc<codex_core::CodexConversation>,
-        plan_tx: mpsc::Sender<PlanMessage>,
+        plan_channel: crate::plan_channel::PlanChannel,
         output_tx: mpsc::Sender<OutputMessage>,
         controller: AgentController,
+        event_handlers: crate::event_handlers::EventHandlerRegistry,
+        retry: crate::config::RetryConfig,
     ) -> Result<()> {
         let mut current_turn_id = 0u64;
 
+        // Recoverable `EventMsg::Error`s seen for `current_turn_id` so far,
+        // reset whenever the turn settles (`TaskComplete`/`TurnAborted`).
+        // Mirrors the backoff-and-retry the input-submission loop in
+        // `run_agent_loop` applies to `conversation.submit`, but for errors
+        // that surface mid-turn instead of at submission time.
+        let mut turn_error_attempts = 0u32;
+
+        // Raw argument text accumulated per in-flight tool call id (see
+        // `EventMsg::McpToolCallArgumentsDelta` below). Nothing here reads it
+        // back: only the raw fragment is forwarded as `ToolArgsDelta`, and a
+        // consumer repairs its own accumulated text (e.g. with
+        // `crate::tool::repair_partial_json`).
+        let mut tool_arg_fragments: std::collections::HashMap<String, String> =
+            std::collections::HashMap::new();
+
+        // File contents captured at `PatchApplyBegin`, keyed by call id then
+        // path, so `PatchApplyEnd` can diff them against the file's new
+        // contents and emit range+content `TextChange`s instead of the raw
+        // before/after text.
+        let mut patch_snapshots: std::collections::HashMap<
+            String,
+            std::collections::HashMap<std::path::PathBuf, String>,
+        > = std::collections::HashMap::new();
+
+        // Set once `controller.cancel_token` fires, bounding how much longer
+        // we keep reading events for the `TurnAborted`/shutdown confirmation
+        // `run_agent_loop` triggers via `Op::Interrupt`/`Op::Shutdown` in
+        // response to the same cancellation (see `CANCEL_DRAIN_TIMEOUT`).
+        let mut drain_deadline: Option<tokio::time::Instant> = None;
+
         loop {
-            // Check if we should stop
-            if controller.should_stop.load(Ordering::SeqCst) {
-                break;
-            }
+            let next_event = match drain_deadline {
+                Some(deadline) => {
+                    match tokio::time::timeout_at(deadline, conversation.next_event()).await {
+                        Ok(result) => result,
+                        Err(_) => {
+                            debug!("Timed out draining events after cancellation");
+                            break;
+                        }
+                    }
+                }
+                None => {
+                    tokio::select! {
+                        biased;
+                        () = controller.cancel_token.cancelled() => {
+                            drain_deadline = Some(tokio::time::Instant::now() + CANCEL_DRAIN_TIMEOUT);
+                            continue;
+                        }
+                        result = conversation.next_event() => result,
+                    }
+                }
+            };
 
             // Get next event
-            let event = match conversation.next_event().await {
+            let event = match next_event {
                 Ok(event) => event,
                 Err(e) => {
                     error!("Failed to get next event: {}", e);
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::Error(OutputError::from(e)),
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
                     break;
                 }
             };
 
+            // Give registered handlers first look at every event, before
+            // the built-in plan/todo logic below
+            let handler_ctx = crate::event_handlers::EventHandlerContext::new(
+                output_tx.clone(),
+                controller.clone(),
+                current_turn_id,
+            );
+            event_handlers.dispatch(&event.msg, &handler_ctx).await;
+
             // Process the event
             match event.msg {
                 EventMsg::AgentMessage(msg) => {
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::Primary(msg.message),
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
                 }
 
                 EventMsg::AgentMessageDelta(delta) => {
                     // Send streaming delta
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::PrimaryDelta(delta.delta),
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
                 }
 
                 EventMsg::AgentReasoning(reasoning) => {
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::Reasoning(reasoning.text),
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
+                }
+
+                EventMsg::McpToolCallArgumentsDelta(delta) => {
+                    tool_arg_fragments
+                        .entry(delta.call_id.clone())
+                        .or_default()
+                        .push_str(&delta.delta);
+
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
+                            turn_id: current_turn_id,
+                            data: OutputData::ToolArgsDelta {
+                                id: delta.call_id,
+                                delta: delta.delta,
+                            },
+                        },
+                    )
+                    .await;
                 }
 
                 EventMsg::McpToolCallBegin(tool_call) => {
@@ -444,10 +922,16 @@ impl Agent {
                     {
                         let todos: Vec<crate::message::TodoItem> = plan_array
                             .iter()
-                            .filter_map(|item| {
+                            .enumerate()
+                            .filter_map(|(idx, item)| {
                                 let step = item.get("step")?.as_str()?;
                                 let status = item.get("status")?.as_str()?;
                                 Some(crate::message::TodoItem {
+                                    id: item
+                                        .get("id")
+                                        .and_then(|v| v.as_str())
+                                        .map(str::to_string)
+                                        .unwrap_or_else(|| idx.to_string()),
                                     content: step.to_string(),
                                     status: match status {
                                         "pending" => crate::message::TodoStatus::Pending,
@@ -455,12 +939,26 @@ impl Agent {
                                         "completed" => crate::message::TodoStatus::Completed,
                                         _ => crate::message::TodoStatus::Pending,
                                     },
+                                    depends_on: item
+                                        .get("depends_on")
+                                        .and_then(|v| v.as_array())
+                                        .map(|deps| {
+                                            deps.iter()
+                                                .filter_map(|d| d.as_str().map(str::to_string))
+                                                .collect()
+                                        })
+                                        .unwrap_or_default(),
                                 })
                             })
                             .collect();
 
-                        let _ = plan_tx
-                            .send(PlanMessage {
+                        let progress = Self::plan_progress(&todos);
+                        Self::publish_plan(
+                            &plan_channel,
+                            &output_tx,
+                            &controller,
+                            current_turn_id,
+                            PlanMessage {
                                 todos,
                                 metadata: Some(PlanMetadata {
                                     turn_id: current_turn_id,
@@ -468,12 +966,24 @@ impl Agent {
                                         "Plan updated via update_plan tool".to_string(),
                                     ),
                                 }),
-                            })
-                            .await;
+                            },
+                        )
+                        .await;
+                        Self::publish_output(
+                            &output_tx,
+                            &controller,
+                            OutputMessage {
+                                turn_id: current_turn_id,
+                                data: OutputData::Progress(progress),
+                            },
+                        )
+                        .await;
                     }
 
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::ToolStart {
                                 tool_name: tool_call.invocation.tool.clone(),
@@ -483,8 +993,9 @@ impl Agent {
                                     .clone()
                                     .unwrap_or_default(),
                             },
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
                 }
 
                 EventMsg::McpToolCallEnd(tool_call) => {
@@ -509,8 +1020,14 @@ impl Agent {
                                 {
                                     let todos: Vec<crate::message::TodoItem> = plan_array
                                         .iter()
-                                        .filter_map(|item| {
+                                        .enumerate()
+                                        .filter_map(|(idx, item)| {
                                             Some(crate::message::TodoItem {
+                                                id: item
+                                                    .get("id")
+                                                    .and_then(|v| v.as_str())
+                                                    .map(str::to_string)
+                                                    .unwrap_or_else(|| idx.to_string()),
                                                 content: item.get("step")?.as_str()?.to_string(),
                                                 status: match item.get("status")?.as_str()? {
                                                     "pending" => {
@@ -524,12 +1041,28 @@ impl Agent {
                                                     }
                                                     _ => crate::message::TodoStatus::Pending,
                                                 },
+                                                depends_on: item
+                                                    .get("depends_on")
+                                                    .and_then(|v| v.as_array())
+                                                    .map(|deps| {
+                                                        deps.iter()
+                                                            .filter_map(|d| {
+                                                                d.as_str().map(str::to_string)
+                                                            })
+                                                            .collect()
+                                                    })
+                                                    .unwrap_or_default(),
                                             })
                                         })
                                         .collect();
 
-                                    let _ = plan_tx
-                                        .send(PlanMessage {
+                                    let progress = Self::plan_progress(&todos);
+                                    Self::publish_plan(
+                                        &plan_channel,
+                                        &output_tx,
+                                        &controller,
+                                        current_turn_id,
+                                        PlanMessage {
                                             todos,
                                             metadata: Some(PlanMetadata {
                                                 turn_id: current_turn_id,
@@ -538,8 +1071,18 @@ impl Agent {
                                                         .to_string(),
                                                 ),
                                             }),
-                                        })
-                                        .await;
+                                        },
+                                    )
+                                    .await;
+                                    Self::publish_output(
+                                        &output_tx,
+                                        &controller,
+                                        OutputMessage {
+                                            turn_id: current_turn_id,
+                                            data: OutputData::Progress(progress),
+                                        },
+                                    )
+                                    .await;
                                 }
                             }
                             text
@@ -547,92 +1090,224 @@ impl Agent {
                         Err(e) => format!("Error: {}", e),
                     };
 
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::ToolComplete {
                                 tool_name: tool_call.invocation.tool.clone(),
                                 result,
                             },
+                        },
+                    )
+                    .await;
+                }
+
+                EventMsg::PatchApplyBegin(patch) => {
+                    // Snapshot every file the patch is about to touch so the
+                    // diff against its post-apply contents at `PatchApplyEnd`
+                    // only spans what the patch actually changed.
+                    let snapshot = patch
+                        .changes
+                        .keys()
+                        .map(|path| {
+                            let before = std::fs::read_to_string(path).unwrap_or_default();
+                            (path.clone(), before)
                         })
-                        .await;
+                        .collect();
+                    patch_snapshots.insert(patch.call_id.clone(), snapshot);
+
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
+                            turn_id: current_turn_id,
+                            data: OutputData::ToolStart {
+                                tool_name: "apply_patch".to_string(),
+                                arguments: serde_json::json!({
+                                    "paths": patch.changes.keys().collect::<Vec<_>>(),
+                                }),
+                            },
+                        },
+                    )
+                    .await;
+                }
+
+                EventMsg::PatchApplyEnd(patch) => {
+                    let before = patch_snapshots.remove(&patch.call_id).unwrap_or_default();
+
+                    if patch.success {
+                        for (path, before_content) in &before {
+                            let after_content = std::fs::read_to_string(path).unwrap_or_default();
+                            for change in Self::diff_text_changes(path, before_content, &after_content) {
+                                Self::publish_output(
+                                    &output_tx,
+                                    &controller,
+                                    OutputMessage {
+                                        turn_id: current_turn_id,
+                                        data: OutputData::FileEdit(change),
+                                    },
+                                )
+                                .await;
+                            }
+                        }
+                    }
+
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
+                            turn_id: current_turn_id,
+                            data: OutputData::ToolComplete {
+                                tool_name: "apply_patch".to_string(),
+                                result: if patch.success {
+                                    patch.stdout
+                                } else {
+                                    patch.stderr
+                                },
+                            },
+                        },
+                    )
+                    .await;
                 }
 
                 EventMsg::ExecCommandBegin(exec) => {
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::ToolStart {
                                 tool_name: "bash".to_string(),
                                 arguments: serde_json::json!({ "command": exec.command }),
                             },
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
                 }
 
                 EventMsg::ExecCommandOutputDelta(output) => {
                     // Convert ByteBuf to String (best effort, may contain invalid UTF-8)
                     let output_str = String::from_utf8_lossy(&output.chunk).to_string();
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
-                            data: OutputData::ToolOutput {
+                            data: OutputData::ToolOutputDelta {
                                 tool_name: "bash".to_string(),
-                                output: output_str,
+                                chunk: output_str,
                             },
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
                 }
 
                 EventMsg::ExecCommandEnd(exec) => {
                     let result = format!("Exit code: {}", exec.exit_code);
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::ToolComplete {
                                 tool_name: "bash".to_string(),
                                 result,
                             },
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
                 }
 
                 EventMsg::TaskComplete(_) => {
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
+                            turn_id: current_turn_id,
+                            data: OutputData::Progress(TurnProgress::Complete),
+                        },
+                    )
+                    .await;
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::Completed,
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
+                    controller.mark_turn_settled(current_turn_id).await;
                     current_turn_id += 1;
+                    turn_error_attempts = 0;
                 }
 
                 EventMsg::Error(err) => {
-                    let _ = output_tx
-                        .send(OutputMessage {
-                            turn_id: current_turn_id,
-                            data: OutputData::Error(OutputError::Unknown(err.message)),
-                        })
+                    let output_err = OutputError::from_event_message(err.message);
+                    if output_err.is_recoverable() && turn_error_attempts < retry.max_attempts {
+                        let delay = retry.delay_for_attempt(turn_error_attempts);
+                        turn_error_attempts += 1;
+                        Self::publish_output(
+                            &output_tx,
+                            &controller,
+                            OutputMessage {
+                                turn_id: current_turn_id,
+                                data: OutputData::Detail(format!(
+                                    "Retrying after recoverable error ({output_err}), \
+                                     attempt {turn_error_attempts}/{} in {delay:?}",
+                                    retry.max_attempts
+                                )),
+                            },
+                        )
                         .await;
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    }
+
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
+                            turn_id: current_turn_id,
+                            data: OutputData::Error(output_err),
+                        },
+                    )
+                    .await;
                 }
 
                 EventMsg::TurnAborted(_abort) => {
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::Error(OutputError::Interrupted),
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
+                    controller.mark_turn_settled(current_turn_id).await;
+                    turn_error_attempts = 0;
+                    // This is the confirmation `drain_deadline` was waiting
+                    // on; no need to keep reading events after a cancelled
+                    // shutdown.
+                    if drain_deadline.is_some() {
+                        break;
+                    }
                 }
 
                 EventMsg::SessionConfigured(_) => {
                     // Session start - send a start message
-                    let _ = output_tx
-                        .send(OutputMessage {
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
                             turn_id: current_turn_id,
                             data: OutputData::Start,
-                        })
-                        .await;
+                        },
+                    )
+                    .await;
                 }
 
                 EventMsg::PlanUpdate(plan_update) => {
@@ -640,25 +1315,67 @@ impl Agent {
                     let todos: Vec<crate::message::TodoItem> = plan_update
                         .plan
                         .iter()
-                        .map(|item| crate::message::TodoItem {
+                        .enumerate()
+                        .map(|(idx, item)| crate::message::TodoItem {
+                            id: idx.to_string(),
                             content: item.step.clone(),
                             status: match item.status {
                                 StepStatus::Pending => crate::message::TodoStatus::Pending,
                                 StepStatus::InProgress => crate::message::TodoStatus::InProgress,
                                 StepStatus::Completed => crate::message::TodoStatus::Completed,
                             },
+                            depends_on: Vec::new(),
                         })
                         .collect();
 
-                    let _ = plan_tx
-                        .send(PlanMessage {
+                    let progress = Self::plan_progress(&todos);
+                    Self::publish_plan(
+                        &plan_channel,
+                        &output_tx,
+                        &controller,
+                        current_turn_id,
+                        PlanMessage {
                             todos,
                             metadata: Some(PlanMetadata {
                                 turn_id: current_turn_id,
                                 description: plan_update.explanation,
                             }),
-                        })
+                        },
+                    )
+                    .await;
+                    Self::publish_output(
+                        &output_tx,
+                        &controller,
+                        OutputMessage {
+                            turn_id: current_turn_id,
+                            data: OutputData::Progress(progress),
+                        },
+                    )
+                    .await;
+                }
+
+                EventMsg::TokenCount(token_count) => {
+                    // `model_context_window` is the only sensible `total` for a
+                    // token-usage progress bar; without it there's nothing to
+                    // show progress against, so skip the event rather than
+                    // guess.
+                    if let Some(info) = token_count.info
+                        && let Some(context_window) = info.model_context_window
+                    {
+                        Self::publish_output(
+                            &output_tx,
+                            &controller,
+                            OutputMessage {
+                                turn_id: current_turn_id,
+                                data: OutputData::Progress(TurnProgress::InProgress {
+                                    current: info.total_token_usage.total_tokens,
+                                    total: context_window,
+                                    unit: "tokens".to_string(),
+                                }),
+                            },
+                        )
                         .await;
+                    }
                 }
 
                 _ => {
@@ -671,22 +1388,250 @@ impl Agent {
         Ok(())
     }
 
+    /// Compute the minimal set of [`TextChange`]s that turn `before` into
+    /// `after`, so a patch that only touched a few lines doesn't force a
+    /// host editor to rewrite the whole buffer
+    fn diff_text_changes(path: &std::path::Path, before: &str, after: &str) -> Vec<TextChange> {
+        let path = path.to_string_lossy().to_string();
+        let diff = similar::TextDiff::from_chars(before, after);
+        let mut changes = Vec::new();
+
+        // `from_chars` ops index chars; map them to byte offsets, with one
+        // trailing entry for the end of the string
+        let byte_offsets = |text: &str| -> Vec<usize> {
+            text.char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(text.len()))
+                .collect()
+        };
+        let old_at = byte_offsets(before);
+        let new_at = byte_offsets(after);
+
+        for op in diff.ops() {
+            use similar::DiffOp;
+            match *op {
+                DiffOp::Equal { .. } => {}
+                DiffOp::Delete {
+                    old_index, old_len, ..
+                } => changes.push(TextChange {
+                    path: path.clone(),
+                    start: old_at[old_index],
+                    end: old_at[old_index + old_len],
+                    content: String::new(),
+                }),
+                DiffOp::Insert {
+                    old_index,
+                    new_index,
+                    new_len,
+                } => changes.push(TextChange {
+                    path: path.clone(),
+                    start: old_at[old_index],
+                    end: old_at[old_index],
+                    content: after[new_at[new_index]..new_at[new_index + new_len]].to_string(),
+                }),
+                DiffOp::Replace {
+                    old_index,
+                    old_len,
+                    new_index,
+                    new_len,
+                } => changes.push(TextChange {
+                    path: path.clone(),
+                    start: old_at[old_index],
+                    end: old_at[old_index + old_len],
+                    content: after[new_at[new_index]..new_at[new_index + new_len]].to_string(),
+                }),
+            }
+        }
+
+        changes
+    }
+
+    /// Drain a streaming tool call's chunk channel, forwarding each chunk as
+    /// an `OutputData::ToolOutputDelta` and finishing with exactly one
+    /// `OutputData::ToolComplete` (or a terminal `OutputData::Error`).
+    ///
+    /// A `Close` frame or a closed channel collapses the stream immediately,
+    /// without waiting for a `Done`/`Error` frame to arrive.
+    pub(crate) async fn forward_tool_stream(
+        tool_name: String,
+        turn_id: u64,
+        mut chunks: mpsc::Receiver<ToolChunk>,
+        output_tx: &mpsc::Sender<OutputMessage>,
+    ) {
+        while let Some(chunk) = chunks.recv().await {
+            match chunk {
+                ToolChunk::Stdout { data, .. } | ToolChunk::Stderr { data, .. } => {
+                    let _ = output_tx
+                        .send(OutputMessage {
+                            turn_id,
+                            data: OutputData::ToolOutputDelta {
+                                tool_name: tool_name.clone(),
+                                chunk: data,
+                            },
+                        })
+                        .await;
+                }
+                ToolChunk::Done(result) => {
+                    let _ = output_tx
+                        .send(OutputMessage {
+                            turn_id,
+                            data: OutputData::ToolComplete {
+                                tool_name,
+                                result: result.output,
+                            },
+                        })
+                        .await;
+                    return;
+                }
+                ToolChunk::Error(message) => {
+                    let _ = output_tx
+                        .send(OutputMessage {
+                            turn_id,
+                            data: OutputData::Error(OutputError::ToolError(message)),
+                        })
+                        .await;
+                    return;
+                }
+                ToolChunk::Close => return,
+            }
+        }
+    }
+
+    /// Validate the assembled [`AgentConfig`] before it's used to build a
+    /// `codex_core` conversation, collecting every problem found instead of
+    /// failing on the first one so a user fixes all of them in one pass
+    /// rather than discovering them one `ConfigError` at a time.
+    ///
+    /// Checks that `working_directory` exists and is a directory, that
+    /// `model_provider` is a known provider family, that `model` is
+    /// non-empty and compatible with `disable_response_storage`, and that
+    /// `system_prompt`/`base_instructions` is readable if it names a file
+    /// path rather than inline text.
+    pub fn verify_configuration(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        let working_directory = self.resolve_path(&self.config.working_directory);
+        if !working_directory.is_dir() {
+            problems.push(format!(
+                "working_directory {working_directory:?} does not exist or is not a directory"
+            ));
+        }
+
+        const KNOWN_PROVIDERS: &[&str] = &["openai", "azure", "ollama", "anthropic"];
+        if self.config.model_provider.trim().is_empty() {
+            problems.push("model_provider must not be empty".to_string());
+        } else if self.config.provider_kind != ModelProviderKind::OpenAiCompatible
+            && !KNOWN_PROVIDERS.contains(&self.config.model_provider.as_str())
+        {
+            problems.push(format!(
+                "model_provider {:?} is not a known provider ({KNOWN_PROVIDERS:?}); use ModelProviderKind::OpenAiCompatible for a custom endpoint",
+                self.config.model_provider
+            ));
+        }
+
+        if self.config.model.trim().is_empty() {
+            problems.push("model must not be empty".to_string());
+        } else if self.config.disable_response_storage
+            && self.config.provider_kind == ModelProviderKind::Azure
+        {
+            problems.push(
+                "disable_response_storage is not supported on Azure OpenAI deployments".to_string(),
+            );
+        }
+
+        if let Some(prompt) = &self.config.system_prompt {
+            let path = std::path::Path::new(prompt);
+            if path.is_absolute() && !path.exists() {
+                problems.push(format!("system_prompt names file path {prompt:?}, which does not exist"));
+            }
+        }
+        if let Some(instructions) = &self.config.base_instructions {
+            let path = std::path::Path::new(instructions);
+            if path.is_absolute() && !path.exists() {
+                problems.push(format!(
+                    "base_instructions names file path {instructions:?}, which does not exist"
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(AgentError::ConfigError(format!(
+                "invalid agent configuration:\n- {}",
+                problems.join("\n- ")
+            )))
+        }
+    }
+
+    /// Resolve a config path against `config.config_root` if it's relative
+    /// and a root is set (see [`AgentConfig::config_root`]); an absolute
+    /// path, or any path when no root is configured, passes through unchanged
+    fn resolve_path(&self, path: &std::path::Path) -> std::path::PathBuf {
+        if path.is_relative()
+            && let Some(root) = &self.config.config_root
+        {
+            root.join(path)
+        } else {
+            path.to_path_buf()
+        }
+    }
+
+    /// Resolve `path` like [`Self::resolve_path`], then canonicalize it to
+    /// an absolute path, naming `key` in the error if that fails
+    fn resolve_and_canonicalize(&self, key: &str, path: &std::path::Path) -> Result<std::path::PathBuf> {
+        let resolved = self.resolve_path(path);
+        std::fs::canonicalize(&resolved).map_err(|e| {
+            AgentError::ConfigError(format!("resolving {key} ({}): {e}", resolved.display()))
+        })
+    }
+
     /// Build core config from agent config
+    ///
+    /// `base_url` isn't one of `codex_core`'s override fields today — a
+    /// custom endpoint has to be registered as a named provider in
+    /// `codex_home`'s `config.toml` for `model_provider` to resolve to it.
+    /// We still resolve and validate the endpoint here, via
+    /// [`AgentConfig::resolved_base_url`] (falling back to `provider_kind`'s
+    /// well-known default when `base_url` is unset), so a typo or a
+    /// `provider_kind` with no default surfaces immediately instead of at
+    /// the first request.
     fn build_core_config(&self) -> Result<Config> {
+        self.verify_configuration()?;
+        let working_directory = self.resolve_and_canonicalize("working_directory", &self.config.working_directory)?;
+
+        match self.config.resolved_base_url() {
+            Some(base_url) => {
+                if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+                    return Err(AgentError::ConfigError(format!(
+                        "invalid base_url {base_url:?}: must start with http:// or https://"
+                    )));
+                }
+            }
+            None => {
+                return Err(AgentError::ConfigError(format!(
+                    "no base_url configured and provider_kind {:?} has no well-known default; set AgentConfig::base_url explicitly",
+                    self.config.provider_kind
+                )));
+            }
+        }
+
+        let (model, approval_policy, sandbox_policy) = self.config.profile_resolved();
+
         // Build overrides for Config
         let overrides = codex_core::config::ConfigOverrides {
-            model: Some(self.config.model.clone()),
+            model: Some(model),
             model_provider: Some(self.config.model_provider.clone()),
-            cwd: Some(self.config.working_directory.clone()),
-            approval_policy: Some(self.config.approval_policy.into()),
-            sandbox_mode: Some(self.config.sandbox_policy.into()),
+            cwd: Some(working_directory),
+            approval_policy: Some(approval_policy.into()),
+            sandbox_mode: Some(sandbox_policy.into()),
             disable_response_storage: Some(self.config.disable_response_storage),
             base_instructions: self.config.system_prompt.clone(),
             include_plan_tool: Some(true), // Enable plan tool for task tracking
             // Always enable apply_patch tool for file operations
             include_apply_patch_tool: Some(true),
             codex_linux_sandbox_exe: None,
-            config_profile: None,
+            config_profile: self.config.profile.clone(),
             show_raw_agent_reasoning: Some(self.config.show_raw_reasoning),
         };
 
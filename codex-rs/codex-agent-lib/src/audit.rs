@@ -0,0 +1,396 @@
+//! Structured audit log of every tool invocation the agent performs
+//!
+//! MCP, `apply_patch`, and `bash` calls are already translated into
+//! [`crate::OutputData::ToolStart`]/[`crate::OutputData::ToolComplete`] by
+//! `Agent::process_events`, but that stream is transient and carries no
+//! fixed schema. Setting [`crate::AgentConfig::audit_sink`] gives a host a
+//! durable, structured [`AuditEvent`] for every invocation instead --
+//! `tool_name`, `arguments`, the `sandbox_policy`/`approval_policy` in
+//! effect, the call's output, its success/exit status, and start/end
+//! timestamps -- via whichever [`AuditSink`] variant fits: a JSONL file, an
+//! in-memory [`AuditRingBuffer`] queryable after the run, or a custom
+//! [`AuditWriter`] forwarding to something like a time-series database.
+
+#[cfg(feature = "audit")]
+use crate::SandboxPolicy;
+#[cfg(feature = "audit")]
+use crate::config::ApprovalPolicy;
+#[cfg(feature = "audit")]
+use crate::event_handlers::EventHandler;
+#[cfg(feature = "audit")]
+use crate::event_handlers::EventHandlerContext;
+#[cfg(feature = "audit")]
+use crate::event_handlers::EventHandlerRegistry;
+#[cfg(feature = "audit")]
+use crate::event_handlers::EventKind;
+#[cfg(feature = "audit")]
+use codex_core::protocol::EventMsg;
+#[cfg(feature = "audit")]
+use serde::Deserialize;
+#[cfg(feature = "audit")]
+use serde::Serialize;
+#[cfg(feature = "audit")]
+use std::collections::HashMap;
+#[cfg(feature = "audit")]
+use std::collections::VecDeque;
+#[cfg(feature = "audit")]
+use std::future::Future;
+#[cfg(feature = "audit")]
+use std::path::PathBuf;
+#[cfg(feature = "audit")]
+use std::pin::Pin;
+#[cfg(feature = "audit")]
+use std::sync::Arc;
+#[cfg(feature = "audit")]
+use std::sync::Mutex;
+
+/// One recorded tool invocation
+#[cfg(feature = "audit")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEvent {
+    pub tool_name: String,
+    pub arguments: serde_json::Value,
+    pub sandbox_policy: SandboxPolicy,
+    pub approval_policy: ApprovalPolicy,
+    pub output: String,
+    pub success: bool,
+    /// Process exit code, when the call was a `bash` execution
+    pub exit_status: Option<i64>,
+    pub started_at_unix_ms: u64,
+    pub ended_at_unix_ms: u64,
+}
+
+/// Custom async destination for [`AuditEvent`]s, e.g. forwarding them to a
+/// time-series database rather than a file or in-memory buffer
+#[cfg(feature = "audit")]
+pub trait AuditWriter: Send + Sync {
+    fn write(&self, event: &AuditEvent) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+/// Fixed-capacity, thread-safe buffer of the most recent [`AuditEvent`]s,
+/// queryable after a run finishes rather than only streamed live
+#[cfg(feature = "audit")]
+#[derive(Debug)]
+pub struct AuditRingBuffer {
+    capacity: usize,
+    events: Mutex<VecDeque<AuditEvent>>,
+}
+
+#[cfg(feature = "audit")]
+impl AuditRingBuffer {
+    /// Create a buffer holding at most `capacity` events (rounded up to 1)
+    pub fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            capacity: capacity.max(1),
+            events: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    fn push(&self, event: AuditEvent) {
+        let mut events = self.events.lock().unwrap_or_else(|e| e.into_inner());
+        if events.len() >= self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event);
+    }
+
+    /// Snapshot of every event currently held, oldest first
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+/// Where recorded [`AuditEvent`]s are sent
+#[cfg(feature = "audit")]
+#[derive(Clone)]
+pub enum AuditSink {
+    /// Append each event as one line of JSON to this file, creating it (and
+    /// its parent directory) if missing
+    JsonlFile(PathBuf),
+
+    /// Keep the most recent events in memory; see [`AuditRingBuffer::new`]
+    RingBuffer(Arc<AuditRingBuffer>),
+
+    /// Forward each event through a custom [`AuditWriter`]
+    Writer(Arc<dyn AuditWriter>),
+}
+
+#[cfg(feature = "audit")]
+impl std::fmt::Debug for AuditSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::JsonlFile(path) => f.debug_tuple("JsonlFile").field(path).finish(),
+            Self::RingBuffer(_) => f.debug_tuple("RingBuffer").finish(),
+            Self::Writer(_) => f.debug_tuple("Writer").finish(),
+        }
+    }
+}
+
+#[cfg(feature = "audit")]
+impl AuditSink {
+    async fn record(&self, event: AuditEvent) {
+        match self {
+            Self::JsonlFile(path) => Self::append_to_file(path, &event).await,
+            Self::RingBuffer(buffer) => buffer.push(event),
+            Self::Writer(writer) => writer.write(&event).await,
+        }
+    }
+
+    async fn append_to_file(path: &std::path::Path, event: &AuditEvent) {
+        let line = match serde_json::to_string(event) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!("failed to serialize audit event: {e}");
+                return;
+            }
+        };
+
+        let path = path.to_path_buf();
+        let result = tokio::task::spawn_blocking(move || -> std::io::Result<()> {
+            use std::io::Write;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)?;
+            writeln!(file, "{line}")
+        })
+        .await;
+
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::warn!("failed to write audit event: {e}"),
+            Err(e) => tracing::warn!("audit writer task panicked: {e}"),
+        }
+    }
+}
+
+/// A tool call observed at `*Begin`, awaiting its matching `*End` so the two
+/// can be combined into one [`AuditEvent`]
+#[cfg(feature = "audit")]
+struct PendingCall {
+    tool_name: String,
+    arguments: serde_json::Value,
+    started_at_unix_ms: u64,
+}
+
+/// What a raw `EventMsg` means for the audit log, extracted synchronously so
+/// [`AuditEventHandler::handle`] can hand an owned value into its `'static`
+/// future instead of borrowing the event past this call
+#[cfg(feature = "audit")]
+enum AuditAction {
+    Begin {
+        kind: EventKind,
+        tool_name: String,
+        arguments: serde_json::Value,
+    },
+    End {
+        kind: EventKind,
+        tool_name: String,
+        output: String,
+        success: bool,
+        exit_status: Option<i64>,
+    },
+}
+
+#[cfg(feature = "audit")]
+fn classify(event: &EventMsg) -> Option<AuditAction> {
+    match event {
+        EventMsg::McpToolCallBegin(tool_call) => Some(AuditAction::Begin {
+            kind: EventKind::ToolCall,
+            tool_name: tool_call.invocation.tool.clone(),
+            arguments: tool_call.invocation.arguments.clone().unwrap_or_default(),
+        }),
+        EventMsg::McpToolCallEnd(tool_call) => {
+            let (success, output) = match &tool_call.result {
+                Ok(result) => (
+                    true,
+                    if let Some(mcp_types::ContentBlock::TextContent(text_content)) =
+                        result.content.first()
+                    {
+                        text_content.text.clone()
+                    } else {
+                        String::new()
+                    },
+                ),
+                Err(e) => (false, format!("Error: {e}")),
+            };
+            Some(AuditAction::End {
+                kind: EventKind::ToolCall,
+                tool_name: tool_call.invocation.tool.clone(),
+                output,
+                success,
+                exit_status: None,
+            })
+        }
+        EventMsg::PatchApplyBegin(patch) => Some(AuditAction::Begin {
+            kind: EventKind::PatchApply,
+            tool_name: "apply_patch".to_string(),
+            arguments: serde_json::json!({
+                "paths": patch.changes.keys().collect::<Vec<_>>(),
+            }),
+        }),
+        EventMsg::PatchApplyEnd(patch) => Some(AuditAction::End {
+            kind: EventKind::PatchApply,
+            tool_name: "apply_patch".to_string(),
+            output: if patch.success {
+                patch.stdout.clone()
+            } else {
+                patch.stderr.clone()
+            },
+            success: patch.success,
+            exit_status: None,
+        }),
+        EventMsg::ExecCommandBegin(exec) => Some(AuditAction::Begin {
+            kind: EventKind::Exec,
+            tool_name: "bash".to_string(),
+            arguments: serde_json::json!({ "command": exec.command }),
+        }),
+        EventMsg::ExecCommandEnd(exec) => Some(AuditAction::End {
+            kind: EventKind::Exec,
+            tool_name: "bash".to_string(),
+            output: format!("Exit code: {}", exec.exit_code),
+            success: exec.exit_code == 0,
+            exit_status: Some(exec.exit_code as i64),
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "audit")]
+fn now_unix_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Shared state behind every [`AuditEventHandler`] registered for the same
+/// `audit_sink`, so a `ToolCall`/`PatchApply`/`Exec` begin-end pair recorded
+/// under different [`EventKind`]s doesn't get mixed up
+#[cfg(feature = "audit")]
+struct AuditState {
+    sink: AuditSink,
+    sandbox_policy: SandboxPolicy,
+    approval_policy: ApprovalPolicy,
+    pending: Mutex<HashMap<EventKind, VecDeque<PendingCall>>>,
+}
+
+#[cfg(feature = "audit")]
+impl AuditState {
+    async fn apply(&self, action: AuditAction) {
+        match action {
+            AuditAction::Begin {
+                kind,
+                tool_name,
+                arguments,
+            } => {
+                self.pending
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .entry(kind)
+                    .or_default()
+                    .push_back(PendingCall {
+                        tool_name,
+                        arguments,
+                        started_at_unix_ms: now_unix_ms(),
+                    });
+            }
+            AuditAction::End {
+                kind,
+                tool_name,
+                output,
+                success,
+                exit_status,
+            } => {
+                let pending = self
+                    .pending
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .get_mut(&kind)
+                    .and_then(VecDeque::pop_front);
+
+                // Fall back to a call with no recorded arguments if `Begin`
+                // was missed (e.g. auditing was enabled mid-turn)
+                let (tool_name, arguments, started_at_unix_ms) = match pending {
+                    Some(call) => (call.tool_name, call.arguments, call.started_at_unix_ms),
+                    None => (tool_name, serde_json::Value::Null, now_unix_ms()),
+                };
+
+                self.sink
+                    .record(AuditEvent {
+                        tool_name,
+                        arguments,
+                        sandbox_policy: self.sandbox_policy,
+                        approval_policy: self.approval_policy,
+                        output,
+                        success,
+                        exit_status,
+                        started_at_unix_ms,
+                        ended_at_unix_ms: now_unix_ms(),
+                    })
+                    .await;
+            }
+        }
+    }
+}
+
+/// Bridges one [`EventKind`] of raw event into [`AuditState::apply`] through
+/// the existing [`EventHandlerRegistry`]
+#[cfg(feature = "audit")]
+struct AuditEventHandler {
+    kind: EventKind,
+    state: Arc<AuditState>,
+}
+
+#[cfg(feature = "audit")]
+impl EventHandler for AuditEventHandler {
+    fn kind(&self) -> EventKind {
+        self.kind
+    }
+
+    fn handle(
+        &self,
+        event: &EventMsg,
+        _ctx: &EventHandlerContext,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let state = self.state.clone();
+        let action = classify(event);
+        Box::pin(async move {
+            if let Some(action) = action {
+                state.apply(action).await;
+            }
+        })
+    }
+}
+
+/// Register the handlers that drive `sink` into `registry`, one per
+/// [`EventKind`] a tool invocation can arrive as
+#[cfg(feature = "audit")]
+pub(crate) fn install(
+    sink: AuditSink,
+    sandbox_policy: SandboxPolicy,
+    approval_policy: ApprovalPolicy,
+    registry: &mut EventHandlerRegistry,
+) {
+    let state = Arc::new(AuditState {
+        sink,
+        sandbox_policy,
+        approval_policy,
+        pending: Mutex::new(HashMap::new()),
+    });
+
+    for kind in [EventKind::ToolCall, EventKind::PatchApply, EventKind::Exec] {
+        registry.register(Arc::new(AuditEventHandler {
+            kind,
+            state: state.clone(),
+        }));
+    }
+}
@@ -0,0 +1,390 @@
+//! Workload-file benchmark harness for agent runs
+//!
+//! A [`WorkloadFile`] describes one or more [`ScenarioSpec`]s -- model,
+//! provider, system prompt, a sequence of prompts, tools, and `max_turns` --
+//! each of which [`BenchmarkRunner`] builds into a fresh [`crate::Agent`] and
+//! drives prompt-by-prompt, recording wall-clock time, tool-call counts, and
+//! token usage (when the model reports it) per turn. The resulting
+//! [`BenchmarkReport`] is plain JSON, so it can be diffed across runs to
+//! catch latency/turn-count regressions from a model or prompt change, and
+//! optionally handed to a [`ResultsSink`] for forwarding to a dashboard --
+//! left as a trait rather than a networking dependency, the same way
+//! [`crate::webui::WebTransport`] leaves the actual HTTP server to the host.
+
+#[cfg(feature = "bench")]
+use crate::Agent;
+#[cfg(feature = "bench")]
+use crate::AgentConfig;
+#[cfg(feature = "bench")]
+use crate::Result;
+#[cfg(feature = "bench")]
+use crate::SandboxPolicy;
+#[cfg(feature = "bench")]
+use crate::ToolConfig;
+#[cfg(feature = "bench")]
+use crate::config::ApprovalPolicy;
+#[cfg(feature = "bench")]
+use crate::error::AgentError;
+#[cfg(feature = "bench")]
+use crate::message::OutputData;
+#[cfg(feature = "bench")]
+use crate::message::TurnProgress;
+#[cfg(feature = "bench")]
+use serde::Deserialize;
+#[cfg(feature = "bench")]
+use serde::Serialize;
+#[cfg(feature = "bench")]
+use std::future::Future;
+#[cfg(feature = "bench")]
+use std::path::Path;
+#[cfg(feature = "bench")]
+use std::pin::Pin;
+#[cfg(feature = "bench")]
+use std::time::Instant;
+
+/// On-disk shape of a workload file: one or more scenarios run back-to-back,
+/// each against its own freshly built [`Agent`]
+#[cfg(feature = "bench")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkloadFile {
+    pub scenarios: Vec<ScenarioSpec>,
+}
+
+/// A single benchmarked agent scenario
+#[cfg(feature = "bench")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioSpec {
+    /// Label this scenario's [`ScenarioResult`] is reported under
+    pub name: String,
+
+    /// Model to use (e.g., "gpt-5-mini", "o3")
+    #[serde(default = "default_model")]
+    pub model: String,
+
+    /// `model_provider` to use; defaults to [`AgentConfig`]'s own default
+    /// when omitted
+    #[serde(default)]
+    pub provider: Option<String>,
+
+    /// System prompt for the agent
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+
+    /// Prompts submitted one at a time, in order; each produces one
+    /// [`TurnMetrics`] entry in the scenario's result
+    pub prompts: Vec<String>,
+
+    /// Built-in tools to enable, by name: `"bash"`, `"web_search"`,
+    /// `"file_read"`, `"file_write"`, `"apply_patch"` -- the same vocabulary
+    /// [`crate::templates::TemplateFile::tools`] uses
+    #[serde(default)]
+    pub tools: Vec<String>,
+
+    /// Maximum number of turns before the agent stops
+    #[serde(default = "default_max_turns")]
+    pub max_turns: usize,
+}
+
+#[cfg(feature = "bench")]
+fn default_model() -> String {
+    "gpt-5-mini".to_string()
+}
+
+#[cfg(feature = "bench")]
+fn default_max_turns() -> usize {
+    100
+}
+
+/// Metrics collected for a single prompt/turn within a scenario
+#[cfg(feature = "bench")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnMetrics {
+    /// Turn ID this scenario's agent assigned, matching
+    /// [`crate::OutputMessage::turn_id`]
+    pub turn_id: u64,
+    /// Wall-clock time from submitting the prompt to the turn's
+    /// `Completed`/`Error` output
+    pub wall_clock_ms: u64,
+    /// Number of [`OutputData::ToolStart`] events seen during this turn
+    pub tool_calls: u64,
+    /// Tokens used against the context window, if the model reported a
+    /// [`TurnProgress::InProgress`] update with `unit == "tokens"`
+    pub tokens: Option<u64>,
+}
+
+/// Outcome of running one [`ScenarioSpec`]
+#[cfg(feature = "bench")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    pub name: String,
+    pub model: String,
+    pub provider: String,
+    pub turns: Vec<TurnMetrics>,
+    pub total_turns: usize,
+    pub total_wall_clock_ms: u64,
+    pub total_tool_calls: u64,
+    /// The highest per-turn token count seen, since turns report cumulative
+    /// context usage rather than a per-turn delta; `None` if the model never
+    /// reported token usage
+    pub total_tokens: Option<u64>,
+    /// Set if the scenario failed to build, start, or complete before
+    /// exhausting `prompts`; turns recorded before the failure are still
+    /// included
+    pub error: Option<String>,
+}
+
+/// A full benchmark run's results, in scenario order
+#[cfg(feature = "bench")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub scenarios: Vec<ScenarioResult>,
+}
+
+#[cfg(feature = "bench")]
+impl BenchmarkReport {
+    /// Render this report as pretty-printed JSON, the shape persisted by
+    /// [`BenchmarkRunner::run_file`] callers and posted by a [`ResultsSink`]
+    pub fn to_json_pretty(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| AgentError::InternalError(e.to_string()))
+    }
+
+    /// Write this report to `path` as pretty-printed JSON
+    pub async fn write_to_file(&self, path: &Path) -> Result<()> {
+        let json = self.to_json_pretty()?;
+        tokio::fs::write(path, json)
+            .await
+            .map_err(|e| AgentError::InternalError(e.to_string()))
+    }
+}
+
+/// Destination a finished [`BenchmarkReport`] can be forwarded to, in
+/// addition to whatever the caller does with the value [`BenchmarkRunner`]
+/// returns -- e.g. posting it to a regression-tracking dashboard. Left as a
+/// trait rather than this crate depending on an HTTP client directly, the
+/// same way [`crate::webui::WebTransport`] leaves the networking to the host.
+#[cfg(feature = "bench")]
+pub trait ResultsSink: Send + Sync {
+    /// Submit a completed report; implementations decide how (and whether)
+    /// to retry a failed submission
+    fn submit(&self, report: &BenchmarkReport) -> Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+}
+
+/// Runs [`WorkloadFile`]s against [`Agent`], under a shared sandbox/approval
+/// policy so results across scenarios (and providers) are comparable
+#[cfg(feature = "bench")]
+#[derive(Debug, Clone, Copy)]
+pub struct BenchmarkRunner {
+    sandbox_policy: SandboxPolicy,
+    approval_policy: ApprovalPolicy,
+}
+
+#[cfg(feature = "bench")]
+impl Default for BenchmarkRunner {
+    fn default() -> Self {
+        Self {
+            sandbox_policy: SandboxPolicy::WorkspaceWrite,
+            approval_policy: ApprovalPolicy::Never,
+        }
+    }
+}
+
+#[cfg(feature = "bench")]
+impl BenchmarkRunner {
+    /// Create a runner with the default sandbox/approval policies
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a runner applying `sandbox_policy`/`approval_policy` to every
+    /// scenario's [`AgentConfig`], so e.g. comparing providers doesn't also
+    /// accidentally compare approval friction
+    pub fn with_policies(sandbox_policy: SandboxPolicy, approval_policy: ApprovalPolicy) -> Self {
+        Self {
+            sandbox_policy,
+            approval_policy,
+        }
+    }
+
+    /// Parse `path` as a [`WorkloadFile`] and run it
+    pub async fn run_file(&self, path: &Path) -> Result<BenchmarkReport> {
+        let contents = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| AgentError::ConfigError(format!("reading {}: {e}", path.display())))?;
+        let workload: WorkloadFile = serde_json::from_str(&contents)
+            .map_err(|e| AgentError::ConfigError(format!("parsing {}: {e}", path.display())))?;
+        self.run(workload).await
+    }
+
+    /// Run every scenario in `workload`, in order
+    pub async fn run(&self, workload: WorkloadFile) -> Result<BenchmarkReport> {
+        let mut scenarios = Vec::with_capacity(workload.scenarios.len());
+        for scenario in workload.scenarios {
+            scenarios.push(self.run_scenario(scenario).await);
+        }
+        Ok(BenchmarkReport { scenarios })
+    }
+
+    /// Run `workload` like [`Self::run`], then hand the finished report to
+    /// `sink`
+    pub async fn run_with_sink(
+        &self,
+        workload: WorkloadFile,
+        sink: &dyn ResultsSink,
+    ) -> Result<BenchmarkReport> {
+        let report = self.run(workload).await?;
+        sink.submit(&report).await?;
+        Ok(report)
+    }
+
+    /// Build the [`AgentConfig`] a scenario runs under
+    fn build_config(&self, spec: &ScenarioSpec) -> Result<AgentConfig> {
+        let tools = spec
+            .tools
+            .iter()
+            .map(|name| match name.as_str() {
+                "bash" => Ok(ToolConfig::Bash {
+                    allow_network: false,
+                }),
+                "web_search" => Ok(ToolConfig::WebSearch),
+                "file_read" => Ok(ToolConfig::FileRead),
+                "file_write" => Ok(ToolConfig::FileWrite),
+                "apply_patch" => Ok(ToolConfig::ApplyPatch),
+                other => Err(AgentError::ConfigError(format!(
+                    "unknown tool {other:?} in scenario {:?}; expected bash, web_search, \
+                     file_read, file_write, or apply_patch",
+                    spec.name
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut config = AgentConfig::builder()
+            .model(spec.model.clone())
+            .system_prompt(spec.system_prompt.clone())
+            .sandbox_policy(self.sandbox_policy)
+            .approval_policy(self.approval_policy)
+            .max_turns(spec.max_turns)
+            .tools(tools)
+            .build();
+        if let Some(provider) = &spec.provider {
+            config.model_provider = provider.clone();
+        }
+
+        Ok(config)
+    }
+
+    /// Build, run, and tear down one scenario's agent, recording
+    /// [`TurnMetrics`] for each prompt submitted
+    async fn run_scenario(&self, spec: ScenarioSpec) -> ScenarioResult {
+        let name = spec.name.clone();
+        let model = spec.model.clone();
+        let provider = spec
+            .provider
+            .clone()
+            .unwrap_or_else(|| "openai".to_string());
+
+        let config = match self.build_config(&spec) {
+            Ok(config) => config,
+            Err(e) => return Self::failed_result(name, model, provider, e.to_string()),
+        };
+        let agent = match Agent::new(config) {
+            Ok(agent) => agent,
+            Err(e) => return Self::failed_result(name, model, provider, e.to_string()),
+        };
+
+        let (input_tx, input_rx) = tokio::sync::mpsc::channel(1);
+        let (plan_tx, _plan_rx) = tokio::sync::mpsc::channel(100);
+        let (output_tx, mut output_rx) = tokio::sync::mpsc::channel(100);
+
+        let handle = match agent.execute(input_rx, plan_tx, output_tx).await {
+            Ok(handle) => handle,
+            Err(e) => return Self::failed_result(name, model, provider, e.to_string()),
+        };
+
+        let mut turns = Vec::with_capacity(spec.prompts.len());
+        let mut error = None;
+
+        for prompt in spec.prompts {
+            let start = Instant::now();
+            if input_tx.send(prompt.into()).await.is_err() {
+                error = Some("agent input channel closed".to_string());
+                break;
+            }
+
+            let mut turn_id = 0;
+            let mut tool_calls = 0u64;
+            let mut tokens = None;
+            let mut turn_error = None;
+
+            loop {
+                let Some(output) = output_rx.recv().await else {
+                    turn_error = Some("agent output channel closed".to_string());
+                    break;
+                };
+                turn_id = output.turn_id;
+                match output.data {
+                    OutputData::ToolStart { .. } => tool_calls += 1,
+                    OutputData::Progress(TurnProgress::InProgress { current, unit, .. })
+                        if unit == "tokens" =>
+                    {
+                        tokens = Some(current);
+                    }
+                    OutputData::Completed => break,
+                    OutputData::Error(err) => {
+                        turn_error = Some(err.to_string());
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            turns.push(TurnMetrics {
+                turn_id,
+                wall_clock_ms: start.elapsed().as_millis() as u64,
+                tool_calls,
+                tokens,
+            });
+
+            if turn_error.is_some() {
+                error = turn_error;
+                break;
+            }
+        }
+
+        drop(input_tx);
+        handle.controller().stop().await;
+        let _ = handle.join().await;
+
+        let total_wall_clock_ms = turns.iter().map(|t| t.wall_clock_ms).sum();
+        let total_tool_calls = turns.iter().map(|t| t.tool_calls).sum();
+        let total_tokens = turns.iter().filter_map(|t| t.tokens).max();
+
+        ScenarioResult {
+            name,
+            model,
+            provider,
+            total_turns: turns.len(),
+            turns,
+            total_wall_clock_ms,
+            total_tool_calls,
+            total_tokens,
+            error,
+        }
+    }
+
+    /// An empty [`ScenarioResult`] recording why a scenario never produced
+    /// any [`TurnMetrics`] -- its config failed to build, or the agent
+    /// failed to start
+    fn failed_result(name: String, model: String, provider: String, error: String) -> ScenarioResult {
+        ScenarioResult {
+            name,
+            model,
+            provider,
+            turns: Vec::new(),
+            total_turns: 0,
+            total_wall_clock_ms: 0,
+            total_tool_calls: 0,
+            total_tokens: None,
+            error: Some(error),
+        }
+    }
+}
@@ -1,13 +1,23 @@
 //! Configuration types for the agent
 
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use typed_builder::TypedBuilder;
 
-/// Type alias for custom tool handler function
-pub type CustomToolHandler =
-    fn(
-        serde_json::Value,
-    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<String, String>> + Send>>;
+/// Type alias for a custom tool's handler
+///
+/// An `Arc<dyn Fn>` rather than a bare `fn` pointer, so a handler can close
+/// over instance state (e.g. a [`crate::uv::UvEnvironment`]'s working
+/// directory) instead of being limited to stateless, capture-free closures.
+pub type CustomToolHandler = Arc<
+    dyn Fn(
+            serde_json::Value,
+        ) -> std::pin::Pin<
+            Box<dyn std::future::Future<Output = Result<String, String>> + Send>,
+        > + Send
+        + Sync,
+>;
 
 /// Main configuration for the agent
 #[derive(Debug, Clone, TypedBuilder)]
@@ -20,10 +30,23 @@ pub struct AgentConfig {
     #[builder(setter(into), default)]
     pub api_key: Option<String>,
 
-    /// Model provider (e.g., "openai", "azure", "ollama")
+    /// Model provider (e.g., "openai", "azure", "ollama"); looked up in
+    /// `codex_core`'s provider registry, and also the provider name used
+    /// when `provider_kind` is [`ModelProviderKind::OpenAiCompatible`]
     #[builder(default = "openai".to_string())]
     pub model_provider: String,
 
+    /// Known provider family backing `model_provider`, used to decide
+    /// auth header conventions and tool-calling support without needing
+    /// a network round trip to find out
+    #[builder(default = ModelProviderKind::OpenAi)]
+    pub provider_kind: ModelProviderKind,
+
+    /// Override the provider's default API base URL (e.g. a local Ollama
+    /// server or an Azure deployment endpoint)
+    #[builder(setter(into), default)]
+    pub base_url: Option<String>,
+
     /// System prompt for the agent
     #[builder(setter(into), default)]
     pub system_prompt: Option<String>,
@@ -40,6 +63,21 @@ pub struct AgentConfig {
     #[builder(default)]
     pub mcp_servers: Vec<McpServerConfig>,
 
+    /// Handlers dispatched on every core event before the agent's built-in
+    /// plan/todo logic, for observing reasoning, tool calls, and token
+    /// usage events without forking the event loop; empty by default, which
+    /// leaves today's built-in behavior unaffected
+    #[builder(default)]
+    pub event_handlers: crate::event_handlers::EventHandlerRegistry,
+
+    /// Capacity of the internal buffer [`Agent`](crate::Agent) maintains
+    /// between generating plan/todo updates and forwarding them to the
+    /// `plan_tx` channel passed to [`Agent::execute`](crate::Agent::execute),
+    /// so a slow consumer applies backpressure (or drops updates) instead
+    /// of blocking turn processing
+    #[builder(default)]
+    pub plan_channel_capacity: crate::plan_channel::PlanChannelCapacity,
+
     /// Maximum number of turns before stopping
     #[builder(default = 100)]
     pub max_turns: usize,
@@ -64,6 +102,32 @@ pub struct AgentConfig {
     #[builder(setter(into), default)]
     pub codex_home: Option<PathBuf>,
 
+    /// Name of the active profile, looked up in `profiles` and also passed
+    /// through to `codex_core`'s own `config_profile` mechanism
+    #[builder(setter(into), default)]
+    pub profile: Option<String>,
+
+    /// Named profiles overriding a subset of this config's fields when
+    /// selected via `profile` -- e.g. `dev`/`ci`/`prod` profiles that swap
+    /// model, approval policy, and sandbox mode without maintaining
+    /// separate `AgentConfig`s, analogous to how rustc lets a target
+    /// override the default codegen backend
+    #[builder(default)]
+    pub profiles: std::collections::HashMap<String, AgentProfile>,
+
+    /// Directory a [`crate::config_loader::ConfigLoader`]-assembled config
+    /// was loaded from, if any. When set, a relative `working_directory` is
+    /// resolved against this directory rather than the process's current
+    /// directory, so a config file's relative paths mean what the file's
+    /// author intended regardless of where the process happens to run
+    /// from. `system_prompt`/`base_instructions` are plain text, not paths
+    /// -- a relative string in either is ambiguous with inline prompt
+    /// text, so it's left alone; only an *absolute* path in one of them is
+    /// validated (by [`crate::Agent::verify_configuration`]) to
+    /// actually exist.
+    #[builder(setter(into), default)]
+    pub config_root: Option<PathBuf>,
+
     /// Disable response storage (for zero data retention)
     #[builder(default = false)]
     pub disable_response_storage: bool,
@@ -71,10 +135,207 @@ pub struct AgentConfig {
     /// Show raw agent reasoning (for supported models)
     #[builder(default = false)]
     pub show_raw_reasoning: bool,
+
+    /// Retry behavior applied to recoverable errors during a turn
+    #[builder(default)]
+    pub retry: RetryConfig,
+
+    /// Startup delay before the first turn is submitted, to give slow
+    /// backends (e.g. a cold model provider) time to become ready
+    #[builder(default = Duration::ZERO)]
+    pub bootstrap: Duration,
+
+    /// Shell used to wrap a tool call's `command` argument before spawning
+    /// it, letting the same agent run unchanged on Windows or with a
+    /// non-bash interpreter; defaults to whatever [`Shell::detect`] picks
+    /// for the host platform
+    #[builder(default = Shell::detect())]
+    pub shell: Shell,
+
+    /// Maximum number of independent tool calls from a single turn that
+    /// [`crate::tool::execute_tool_calls`] runs concurrently
+    ///
+    /// Defaults to the host's CPU count, capped at
+    /// [`crate::tool::DEFAULT_TOOL_CONCURRENCY`] so a turn with dozens of
+    /// tool calls doesn't spawn dozens of subprocesses or HTTP requests at
+    /// once.
+    #[builder(default = num_cpus::get().min(crate::tool::DEFAULT_TOOL_CONCURRENCY))]
+    pub tool_concurrency: usize,
+
+    /// Where to record a structured audit event for every tool invocation
+    /// (MCP, `apply_patch`, `bash`) the agent performs; `None` disables
+    /// auditing entirely, which is the default
+    #[cfg(feature = "audit")]
+    #[builder(default)]
+    pub audit_sink: Option<crate::audit::AuditSink>,
+}
+
+/// A named override applied on top of the base [`AgentConfig`] when
+/// `AgentConfig::profile` selects it; every field is optional so a profile
+/// only needs to specify what it overrides
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(
+    feature = "schema",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema)
+)]
+pub struct AgentProfile {
+    #[cfg_attr(feature = "schema", serde(default))]
+    pub model: Option<String>,
+    #[cfg_attr(feature = "schema", serde(default))]
+    pub approval_policy: Option<ApprovalPolicy>,
+    #[cfg_attr(feature = "schema", serde(default))]
+    pub sandbox_policy: Option<SandboxPolicy>,
+}
+
+impl AgentConfig {
+    /// Resolve `(model, approval_policy, sandbox_policy)` with the active
+    /// profile's overrides (if `profile` names one in `profiles`) applied
+    /// on top of this config's own values
+    pub(crate) fn profile_resolved(&self) -> (String, ApprovalPolicy, SandboxPolicy) {
+        let Some(profile) = self.profile.as_ref().and_then(|name| self.profiles.get(name)) else {
+            return (self.model.clone(), self.approval_policy, self.sandbox_policy);
+        };
+
+        (
+            profile.model.clone().unwrap_or_else(|| self.model.clone()),
+            profile.approval_policy.unwrap_or(self.approval_policy),
+            profile.sandbox_policy.unwrap_or(self.sandbox_policy),
+        )
+    }
+
+    /// `base_url`, falling back to `provider_kind`'s well-known default if
+    /// unset. `None` when neither is available (e.g. `OpenAiCompatible`
+    /// without an explicit `base_url`) -- callers should surface that as a
+    /// configuration error rather than guessing an endpoint.
+    pub fn resolved_base_url(&self) -> Option<String> {
+        self.base_url
+            .clone()
+            .or_else(|| self.provider_kind.default_base_url().map(str::to_string))
+    }
+}
+
+/// Retry behavior for recoverable errors encountered while running a turn
+#[derive(Debug, Clone, Copy, TypedBuilder)]
+pub struct RetryConfig {
+    /// Delay before the first retry attempt
+    #[builder(default = Duration::from_millis(500))]
+    pub base_delay: Duration,
+
+    /// Upper bound on the exponential backoff delay between retries
+    #[builder(default = Duration::from_secs(30))]
+    pub max_delay: Duration,
+
+    /// Maximum number of retries before a recoverable error becomes terminal
+    #[builder(default = 5)]
+    pub max_attempts: u32,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl RetryConfig {
+    /// Compute the backoff delay for the given zero-indexed attempt number
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(1 << attempt.min(31))
+            .min(self.max_delay)
+    }
+}
+
+#[cfg(feature = "templates")]
+impl AgentConfig {
+    /// Load an [`AgentConfig`] from a declarative TOML/YAML agent
+    /// definition file -- model, `api_key_env`, sandbox policy, max turns,
+    /// system prompt, and enabled tools -- so a persona like `python-dev`
+    /// can be iterated on without recompiling this crate
+    ///
+    /// See [`crate::templates::TemplateFile`] for the on-disk shape.
+    pub fn from_file(path: &std::path::Path) -> crate::Result<Self> {
+        Self::from_file_with_context(path, &std::collections::HashMap::new())
+    }
+
+    /// Load an [`AgentConfig`] from a file like [`Self::from_file`],
+    /// substituting `{{variable}}` placeholders in the system prompt from
+    /// `context`
+    pub fn from_file_with_context(
+        path: &std::path::Path,
+        context: &std::collections::HashMap<String, String>,
+    ) -> crate::Result<Self> {
+        crate::templates::TemplateFile::load(path)?.into_config_with_context(context)
+    }
+}
+
+/// JSON Schema document for [`AgentConfig`] (via [`crate::schema::AgentConfigSchema`])
+///
+/// Lets editors offer autocompletion/validation of agent config files, and
+/// lets hosts validate a user-supplied config before constructing an
+/// [`crate::Agent`], surfacing a precise [`crate::AgentError`] on mismatch
+/// instead of a panic deep inside the builder.
+#[cfg(feature = "schema")]
+pub fn schema() -> schemars::schema::RootSchema {
+    crate::schema::agent_config_schema()
+}
+
+/// Known provider families, following aichat's `OPENAI_COMPATIBLE_PLATFORMS`
+/// / `list_client_types` split: most providers speak an OpenAI-compatible
+/// wire format, but differ in auth header conventions and which features
+/// (notably tool/function calling) their models actually support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "schema",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema)
+)]
+pub enum ModelProviderKind {
+    /// api.openai.com or an OpenAI-compatible `base_url` override
+    OpenAi,
+    /// Azure OpenAI Service deployment
+    Azure,
+    /// Local or self-hosted Ollama server
+    Ollama,
+    /// Anthropic's native Messages API
+    Anthropic,
+    /// Any other OpenAI-compatible endpoint identified by `model_provider`
+    OpenAiCompatible,
+}
+
+impl ModelProviderKind {
+    /// Whether this provider family supports tool/function calling
+    ///
+    /// Most Ollama-served models don't implement the OpenAI tool-calling
+    /// contract; everything else in this list does.
+    pub fn supports_tool_calling(&self) -> bool {
+        !matches!(self, Self::Ollama)
+    }
+
+    /// The HTTP header used to carry the API key for this provider family
+    pub fn auth_header(&self) -> &'static str {
+        match self {
+            Self::Azure => "api-key",
+            Self::Anthropic => "x-api-key",
+            Self::OpenAi | Self::Ollama | Self::OpenAiCompatible => "Authorization",
+        }
+    }
+
+    /// This family's well-known default API base URL, used when
+    /// [`AgentConfig::base_url`] is unset. `None` for families with no
+    /// universal default -- Azure's is tenant-specific, and a plain
+    /// `OpenAiCompatible` endpoint has no default at all -- so those must
+    /// set `base_url` explicitly.
+    pub fn default_base_url(&self) -> Option<&'static str> {
+        match self {
+            Self::OpenAi => Some("https://api.openai.com"),
+            Self::Ollama => Some("http://localhost:11434"),
+            Self::Anthropic => Some("https://api.anthropic.com"),
+            Self::Azure | Self::OpenAiCompatible => None,
+        }
+    }
 }
 
 /// Tool configuration
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum ToolConfig {
     /// Built-in bash/shell tool
     Bash {
@@ -100,11 +361,83 @@ pub enum ToolConfig {
         description: String,
         parameters: serde_json::Value,
         handler: CustomToolHandler,
+
+        /// Whether this tool is side-effecting (mutates the filesystem,
+        /// network, or a running process) rather than a pure/read-only
+        /// lookup. Mirrors [`crate::tool::Tool::requires_approval`]: once
+        /// registered via [`Self::register_into`], side-effecting calls run
+        /// one at a time through [`crate::tool::execute_tool_calls`] instead
+        /// of concurrently with the rest of the batch, so a host's approval
+        /// gate has a chance to run before each one.
+        requires_approval: bool,
     },
 }
 
+impl std::fmt::Debug for ToolConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bash { allow_network } => f
+                .debug_struct("Bash")
+                .field("allow_network", allow_network)
+                .finish(),
+            Self::WebSearch => write!(f, "WebSearch"),
+            Self::FileRead => write!(f, "FileRead"),
+            Self::FileWrite => write!(f, "FileWrite"),
+            Self::ApplyPatch => write!(f, "ApplyPatch"),
+            Self::Custom {
+                name,
+                description,
+                parameters,
+                handler: _,
+                requires_approval,
+            } => f
+                .debug_struct("Custom")
+                .field("name", name)
+                .field("description", description)
+                .field("parameters", parameters)
+                .field("handler", &"<fn>")
+                .field("requires_approval", requires_approval)
+                .finish(),
+        }
+    }
+}
+
+impl ToolConfig {
+    /// Register this config into `registry`, if it's a [`Self::Custom`]
+    /// tool -- built-in variants are executed by `codex_core` directly and
+    /// have no [`crate::tool::ToolHandler`] of their own to register. Wraps
+    /// `handler` so the custom tool is gated/run sequentially by
+    /// `requires_approval` the same way any other registered
+    /// [`crate::tool::ToolHandler`] is, enabling multi-step tool loops
+    /// through [`crate::tool::execute_tool_calls`].
+    pub fn register_into(&self, registry: &mut crate::tool::ToolRegistry) {
+        if let Self::Custom {
+            name,
+            description,
+            parameters,
+            handler,
+            requires_approval,
+        } = self
+        {
+            registry.register(
+                &crate::tool::Tool {
+                    name: name.clone(),
+                    description: description.clone(),
+                    parameters: parameters.clone(),
+                    requires_approval: *requires_approval,
+                },
+                crate::tool::custom_tool_handler(handler.clone()),
+            );
+        }
+    }
+}
+
 /// MCP server configuration
 #[derive(Debug, Clone, TypedBuilder)]
+#[cfg_attr(
+    feature = "schema",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema)
+)]
 pub struct McpServerConfig {
     /// Name of the MCP server
     pub name: String,
@@ -123,6 +456,14 @@ pub struct McpServerConfig {
 
 /// Sandbox policy for tool execution
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "schema",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema)
+)]
+#[cfg_attr(
+    all(feature = "audit", not(feature = "schema")),
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum SandboxPolicy {
     /// No restrictions (dangerous!)
     DangerFullAccess,
@@ -134,6 +475,113 @@ pub enum SandboxPolicy {
     WorkspaceWrite,
 }
 
+/// How a tool call's `command` argument gets wrapped into the argv actually
+/// spawned, mirroring how a command string gets wrapped before being
+/// handed to e.g. `std::process::Command`
+#[derive(Debug, Clone)]
+#[cfg_attr(
+    feature = "schema",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema)
+)]
+pub enum Shell {
+    /// POSIX shell at the given path, wrapping as `[sh, "-c", cmd]`
+    Unix(PathBuf),
+
+    /// PowerShell, wrapping as `["pwsh", "-Command", cmd]`
+    Powershell,
+
+    /// `cmd.exe`-compatible shell named by the given executable, wrapping
+    /// as `[shell, "/C", cmd]`
+    Cmd(String),
+
+    /// No shell wrapping: a pre-split argv is executed as-is, and a shell
+    /// string is whitespace-split into one
+    None,
+}
+
+impl Shell {
+    /// Platform-appropriate default: a POSIX shell on Unix, PowerShell on
+    /// Windows, and no wrapping anywhere else
+    pub fn detect() -> Self {
+        if cfg!(windows) {
+            Self::Powershell
+        } else if cfg!(unix) {
+            Self::Unix(PathBuf::from("/bin/sh"))
+        } else {
+            Self::None
+        }
+    }
+
+    /// Wrap `command` into the full argv to spawn, accepting either a
+    /// shell-string or a pre-split argv tool call argument
+    pub fn wrap(&self, command: CommandInput) -> Vec<String> {
+        match self {
+            Self::None => match command {
+                CommandInput::Argv(argv) => argv,
+                CommandInput::Shell(cmd) => cmd.split_whitespace().map(str::to_string).collect(),
+            },
+            Self::Unix(sh) => vec![
+                sh.to_string_lossy().into_owned(),
+                "-c".to_string(),
+                command.into_shell_string(),
+            ],
+            Self::Powershell => vec![
+                "pwsh".to_string(),
+                "-Command".to_string(),
+                command.into_shell_string(),
+            ],
+            Self::Cmd(shell) => vec![shell.clone(), "/C".to_string(), command.into_shell_string()],
+        }
+    }
+}
+
+/// A tool call's `command` argument, accepted either as a single shell
+/// string or a pre-split argv -- both shapes show up across different
+/// tool-calling models/conventions
+#[derive(Debug, Clone)]
+pub enum CommandInput {
+    /// A single string to be interpreted by a shell
+    Shell(String),
+    /// Already-split argv, executed without further parsing
+    Argv(Vec<String>),
+}
+
+impl CommandInput {
+    /// Parse a tool call's `command` JSON argument, accepting either a
+    /// string or an array of strings
+    pub fn from_value(value: &serde_json::Value) -> Option<Self> {
+        if let Some(s) = value.as_str() {
+            Some(Self::Shell(s.to_string()))
+        } else if let Some(items) = value.as_array() {
+            Some(Self::Argv(
+                items
+                    .iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect(),
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Collapse to a single shell string, joining argv entries with spaces
+    fn into_shell_string(self) -> String {
+        match self {
+            Self::Shell(s) => s,
+            Self::Argv(argv) => argv.join(" "),
+        }
+    }
+
+    /// Render for display (e.g. a TUI's command log), joining argv entries
+    /// with spaces the same way [`Self::into_shell_string`] does
+    pub fn display(&self) -> String {
+        match self {
+            Self::Shell(s) => s.clone(),
+            Self::Argv(argv) => argv.join(" "),
+        }
+    }
+}
+
 impl From<SandboxPolicy> for codex_protocol::config_types::SandboxMode {
     fn from(policy: SandboxPolicy) -> Self {
         match policy {
@@ -150,6 +598,14 @@ impl From<SandboxPolicy> for codex_protocol::config_types::SandboxMode {
 
 /// Approval policy for tool execution
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "schema",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema)
+)]
+#[cfg_attr(
+    all(feature = "audit", not(feature = "schema")),
+    derive(serde::Serialize, serde::Deserialize)
+)]
 pub enum ApprovalPolicy {
     /// Never ask for approval (fully autonomous)
     Never,
@@ -176,3 +632,49 @@ impl From<ApprovalPolicy> for codex_protocol::protocol::AskForApproval {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_for_attempt_doubles_each_attempt_up_to_the_max() {
+        let retry = RetryConfig::builder()
+            .base_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(10))
+            .build();
+
+        assert_eq!(retry.delay_for_attempt(0), Duration::from_millis(100));
+        assert_eq!(retry.delay_for_attempt(1), Duration::from_millis(200));
+        assert_eq!(retry.delay_for_attempt(2), Duration::from_millis(400));
+        assert_eq!(retry.delay_for_attempt(3), Duration::from_millis(800));
+    }
+
+    #[test]
+    fn delay_for_attempt_clamps_to_max_delay() {
+        let retry = RetryConfig::builder()
+            .base_delay(Duration::from_millis(500))
+            .max_delay(Duration::from_secs(1))
+            .build();
+
+        assert_eq!(retry.delay_for_attempt(10), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn delay_for_attempt_does_not_overflow_on_huge_attempt_numbers() {
+        let retry = RetryConfig::builder()
+            .base_delay(Duration::from_millis(500))
+            .max_delay(Duration::from_secs(30))
+            .build();
+
+        assert_eq!(retry.delay_for_attempt(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn default_retry_config_matches_documented_defaults() {
+        let retry = RetryConfig::default();
+        assert_eq!(retry.base_delay, Duration::from_millis(500));
+        assert_eq!(retry.max_delay, Duration::from_secs(30));
+        assert_eq!(retry.max_attempts, 5);
+    }
+}
@@ -0,0 +1,231 @@
+//! Layered, multi-format configuration loading for [`AgentConfig`]
+//!
+//! Mirrors the `config` crate's layered-source approach: a base file, a
+//! profile/environment-specific file, environment variables, and explicit
+//! programmatic overrides are merged in that precedence order (later wins),
+//! before the result is applied onto an [`AgentConfig`]. Source files are
+//! auto-detected by extension -- `.toml`, `.json5`, `.yaml`/`.yml`, `.ron`
+//! are all accepted, so a team can keep shared defaults in whichever format
+//! it already uses elsewhere.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::config::AgentConfig;
+use crate::config::ApprovalPolicy;
+use crate::config::SandboxPolicy;
+use crate::error::AgentError;
+use crate::error::Result;
+
+/// A partial set of [`AgentConfig`] fields, as parsed from one config source
+///
+/// Every field is optional so a profile file only needs to specify what it
+/// overrides, rather than repeating the whole base config.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct ConfigLayer {
+    pub model: Option<String>,
+    pub model_provider: Option<String>,
+    pub working_directory: Option<PathBuf>,
+    pub approval_policy: Option<String>,
+    pub sandbox_policy: Option<String>,
+    pub system_prompt: Option<String>,
+    pub show_raw_reasoning: Option<bool>,
+}
+
+impl ConfigLayer {
+    /// Parse a single config file, dispatching on its extension
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| AgentError::ConfigError(format!("{} has no extension", path.display())))?;
+
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AgentError::ConfigError(format!("reading {}: {e}", path.display())))?;
+
+        match extension {
+            "toml" => toml::from_str(&contents)
+                .map_err(|e| AgentError::ConfigError(format!("parsing {}: {e}", path.display()))),
+            "json5" => json5::from_str(&contents)
+                .map_err(|e| AgentError::ConfigError(format!("parsing {}: {e}", path.display()))),
+            "yaml" | "yml" => serde_yaml::from_str(&contents)
+                .map_err(|e| AgentError::ConfigError(format!("parsing {}: {e}", path.display()))),
+            "ron" => ron::from_str(&contents)
+                .map_err(|e| AgentError::ConfigError(format!("parsing {}: {e}", path.display()))),
+            other => Err(AgentError::ConfigError(format!(
+                "unsupported config extension {other:?} in {}; expected toml, json5, yaml/yml, or ron",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Read `{prefix}__FIELD` environment variables (e.g. `CODEX_AGENT__MODEL`)
+    pub fn from_env(prefix: &str) -> Self {
+        let var = |suffix: &str| std::env::var(format!("{prefix}__{suffix}")).ok();
+        Self {
+            model: var("MODEL"),
+            model_provider: var("MODEL_PROVIDER"),
+            working_directory: var("WORKING_DIRECTORY").map(PathBuf::from),
+            approval_policy: var("APPROVAL_POLICY"),
+            sandbox_policy: var("SANDBOX_POLICY"),
+            system_prompt: var("SYSTEM_PROMPT"),
+            show_raw_reasoning: var("SHOW_RAW_REASONING").and_then(|v| v.parse().ok()),
+        }
+    }
+
+    /// Merge `other` over `self`, with any field `other` sets taking priority
+    fn merge(mut self, other: Self) -> Self {
+        if other.model.is_some() {
+            self.model = other.model;
+        }
+        if other.model_provider.is_some() {
+            self.model_provider = other.model_provider;
+        }
+        if other.working_directory.is_some() {
+            self.working_directory = other.working_directory;
+        }
+        if other.approval_policy.is_some() {
+            self.approval_policy = other.approval_policy;
+        }
+        if other.sandbox_policy.is_some() {
+            self.sandbox_policy = other.sandbox_policy;
+        }
+        if other.system_prompt.is_some() {
+            self.system_prompt = other.system_prompt;
+        }
+        if other.show_raw_reasoning.is_some() {
+            self.show_raw_reasoning = other.show_raw_reasoning;
+        }
+        self
+    }
+
+    /// Apply every set field onto a fresh default [`AgentConfig`], rooted at
+    /// `config_root` so relative paths resolve against the config file's
+    /// directory rather than the process's current directory
+    fn into_config(self, config_root: Option<PathBuf>) -> Result<AgentConfig> {
+        let mut config = AgentConfig::builder().build();
+        config.config_root = config_root;
+
+        if let Some(model) = self.model {
+            config.model = model;
+        }
+        if let Some(model_provider) = self.model_provider {
+            config.model_provider = model_provider;
+        }
+        if let Some(working_directory) = self.working_directory {
+            config.working_directory = working_directory;
+        }
+        if let Some(policy) = self.approval_policy {
+            config.approval_policy = parse_approval_policy(&policy)?;
+        }
+        if let Some(policy) = self.sandbox_policy {
+            config.sandbox_policy = parse_sandbox_policy(&policy)?;
+        }
+        if let Some(prompt) = self.system_prompt {
+            config.system_prompt = Some(prompt);
+        }
+        if let Some(show) = self.show_raw_reasoning {
+            config.show_raw_reasoning = show;
+        }
+
+        Ok(config)
+    }
+}
+
+fn parse_approval_policy(value: &str) -> Result<ApprovalPolicy> {
+    match value {
+        "never" => Ok(ApprovalPolicy::Never),
+        "on_failure" => Ok(ApprovalPolicy::OnFailure),
+        "on_request" => Ok(ApprovalPolicy::OnRequest),
+        "unless_trusted" => Ok(ApprovalPolicy::UnlessTrusted),
+        other => Err(AgentError::ConfigError(format!(
+            "unknown approval_policy {other:?}; expected never, on_failure, on_request, or unless_trusted"
+        ))),
+    }
+}
+
+fn parse_sandbox_policy(value: &str) -> Result<SandboxPolicy> {
+    match value {
+        "danger_full_access" => Ok(SandboxPolicy::DangerFullAccess),
+        "read_only" => Ok(SandboxPolicy::ReadOnly),
+        "workspace_write" => Ok(SandboxPolicy::WorkspaceWrite),
+        other => Err(AgentError::ConfigError(format!(
+            "unknown sandbox_policy {other:?}; expected danger_full_access, read_only, or workspace_write"
+        ))),
+    }
+}
+
+/// Default prefix for environment-variable overrides, e.g. `CODEX_AGENT__MODEL`
+const DEFAULT_ENV_PREFIX: &str = "CODEX_AGENT";
+
+/// Builder that merges config layers in precedence order -- base file,
+/// profile file, environment variables, then explicit programmatic
+/// overrides -- into one [`AgentConfig`]
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoader {
+    base_file: Option<PathBuf>,
+    profile_file: Option<PathBuf>,
+    env_prefix: Option<String>,
+    overrides: ConfigLayer,
+}
+
+impl ConfigLoader {
+    /// Create a loader with no layers configured yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the lowest-precedence layer from `path` (e.g. a team-wide
+    /// `defaults.toml` checked into the repo)
+    pub fn base_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.base_file = Some(path.into());
+        self
+    }
+
+    /// Load a higher-precedence layer from `path` (e.g. a per-environment
+    /// `prod.yaml`), applied after `base_file` but before environment
+    /// variables
+    pub fn profile_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.profile_file = Some(path.into());
+        self
+    }
+
+    /// Override the environment-variable prefix (default [`DEFAULT_ENV_PREFIX`])
+    pub fn env_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.env_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Merge in explicit programmatic overrides, taking priority over every
+    /// file and environment-variable layer
+    pub fn overrides(mut self, overrides: ConfigLayer) -> Self {
+        self.overrides = self.overrides.merge(overrides);
+        self
+    }
+
+    /// Merge every configured layer and build the resulting [`AgentConfig`]
+    ///
+    /// The resulting config's `config_root` is the directory of the
+    /// highest-precedence file layer supplied (`profile_file`, falling back
+    /// to `base_file`), so [`Agent`](crate::Agent) resolves relative paths
+    /// against wherever that file actually lives.
+    pub fn load(self) -> Result<AgentConfig> {
+        let mut merged = ConfigLayer::default();
+        let mut config_root = None;
+
+        if let Some(path) = &self.base_file {
+            merged = merged.merge(ConfigLayer::from_file(path)?);
+            config_root = path.parent().map(Path::to_path_buf);
+        }
+        if let Some(path) = &self.profile_file {
+            merged = merged.merge(ConfigLayer::from_file(path)?);
+            config_root = path.parent().map(Path::to_path_buf);
+        }
+        merged = merged.merge(ConfigLayer::from_env(
+            self.env_prefix.as_deref().unwrap_or(DEFAULT_ENV_PREFIX),
+        ));
+        merged = merged.merge(self.overrides);
+
+        merged.into_config(config_root)
+    }
+}
@@ -0,0 +1,375 @@
+//! Discord connector: run an [`Agent`] as a long-lived chat bot
+//!
+//! Modeled on serenity's design: implement [`EventHandler`] to react to
+//! gateway events, each callback handed a [`Context`] carrying the
+//! conversation's [`AgentController`], a [`ChannelSession`] used to talk
+//! back to the agent, and a [`Cache`] of recently seen channel/user state so
+//! handlers don't have to re-fetch it over the REST API. The actual gateway
+//! connection and REST calls are left to a [`GatewayTransport`]
+//! implementation (e.g. backed by `serenity` or `twilight`), so this crate
+//! stays free of a networking dependency; [`DiscordBot`] owns only the
+//! dispatch loop, sharding, and the bridge to [`Agent::execute`].
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::error;
+use tracing::warn;
+
+use crate::Agent;
+use crate::agent::AgentController;
+use crate::error::AgentError;
+use crate::error::Result;
+use crate::message::InputMessage;
+use crate::message::OutputData;
+
+/// Discord snowflake ID, kept as a string since it exceeds `i32` and callers
+/// never need to do arithmetic on it
+pub type ChannelId = String;
+
+/// Discord snowflake ID for a user
+pub type UserId = String;
+
+/// A single inbound message from the Discord gateway
+#[derive(Debug, Clone)]
+pub struct DiscordMessage {
+    /// Channel the message was posted in
+    pub channel: ChannelId,
+    /// Author's user id
+    pub author: UserId,
+    /// Author's display name, as reported by the gateway
+    pub author_name: String,
+    /// Message text content
+    pub content: String,
+    /// The message's own snowflake ID
+    pub message_id: String,
+}
+
+/// Sends and edits messages on behalf of the bot
+///
+/// This is the only point where a real implementation needs to talk to
+/// Discord's REST API; [`DiscordBot`] never calls the network directly.
+pub trait GatewayTransport: Send + Sync {
+    /// Post a new message to `channel`, returning its message id
+    fn send_message<'a>(
+        &'a self,
+        channel: &'a str,
+        content: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+
+    /// Edit a previously sent message in place, used to stream an in-progress
+    /// agent reply without spamming new messages for every delta
+    fn edit_message<'a>(
+        &'a self,
+        channel: &'a str,
+        message_id: &'a str,
+        content: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+}
+
+/// Reacts to Discord gateway events
+///
+/// Both callbacks default to doing nothing, so implementations only need to
+/// override the ones they care about. `on_message` runs alongside (not
+/// instead of) [`DiscordBot`]'s built-in agent dispatch, for callers that
+/// want additional side effects (logging, moderation, analytics).
+pub trait EventHandler: Send + Sync {
+    /// Called once the bot's gateway connection is ready
+    fn on_ready(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+
+    /// Called for every inbound message, before it is dispatched to the agent
+    fn on_message<'a>(
+        &'a self,
+        ctx: &'a Context,
+        message: &'a DiscordMessage,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        let _ = (ctx, message);
+        Box::pin(async {})
+    }
+}
+
+/// Recently observed channel/user metadata, so handlers and the dispatch
+/// loop avoid a redundant REST round-trip for info the gateway already sent
+#[derive(Clone, Default)]
+pub struct Cache {
+    channel_names: Arc<RwLock<HashMap<ChannelId, String>>>,
+    user_names: Arc<RwLock<HashMap<UserId, String>>>,
+}
+
+impl Cache {
+    /// Look up a previously observed channel name
+    pub async fn channel_name(&self, channel: &str) -> Option<String> {
+        self.channel_names.read().await.get(channel).cloned()
+    }
+
+    /// Record a channel name observed from a gateway event
+    pub async fn record_channel(&self, channel: ChannelId, name: String) {
+        self.channel_names.write().await.insert(channel, name);
+    }
+
+    /// Look up a previously observed user display name
+    pub async fn user_name(&self, user: &str) -> Option<String> {
+        self.user_names.read().await.get(user).cloned()
+    }
+
+    /// Record a user display name observed from a gateway event
+    pub async fn record_user(&self, user: UserId, name: String) {
+        self.user_names.write().await.insert(user, name);
+    }
+}
+
+/// A channel's live conversation with the agent
+#[derive(Clone)]
+pub struct ChannelSession {
+    /// Controller for the agent instance backing this channel
+    pub controller: AgentController,
+    input_tx: mpsc::Sender<InputMessage>,
+}
+
+impl ChannelSession {
+    /// Forward a message to the agent as if the channel's user had sent it
+    pub async fn say(&self, message: impl Into<InputMessage>) -> Result<()> {
+        self.input_tx
+            .send(message.into())
+            .await
+            .map_err(|_| AgentError::ChannelError)
+    }
+}
+
+/// Handed to every [`EventHandler`] callback
+pub struct Context {
+    /// Channel the triggering event occurred in
+    pub channel: ChannelId,
+    /// This channel's conversation with the agent
+    pub session: ChannelSession,
+    /// Recently seen channel/user metadata
+    pub cache: Cache,
+}
+
+/// Handle to a running [`DiscordBot`]
+pub struct DiscordBotHandle {
+    shard_tasks: Vec<JoinHandle<()>>,
+}
+
+impl DiscordBotHandle {
+    /// Wait for every shard to stop (normally only on gateway shutdown)
+    pub async fn join(self) {
+        for task in self.shard_tasks {
+            let _ = task.await;
+        }
+    }
+}
+
+/// Builder and runtime for a Discord bot backed by an [`Agent`]
+///
+/// Each distinct channel gets its own `Agent::execute` conversation, so
+/// guilds don't share context with one another. Inbound events are fanned
+/// out across `shard_count` worker tasks by hashing the channel id, the
+/// same way Discord's own gateway shards guilds across processes; this
+/// keeps one busy channel from starving dispatch for every other guild.
+pub struct DiscordBot {
+    agent: Agent,
+    handler: Arc<dyn EventHandler>,
+    transport: Arc<dyn GatewayTransport>,
+    shard_count: usize,
+}
+
+impl DiscordBot {
+    /// Create a bot that spawns a fresh `agent` conversation per channel and
+    /// talks to Discord through `transport`
+    pub fn new(agent: Agent, transport: Arc<dyn GatewayTransport>) -> Self {
+        Self {
+            agent,
+            handler: Arc::new(NoopEventHandler),
+            transport,
+            shard_count: 1,
+        }
+    }
+
+    /// Register an [`EventHandler`] for gateway-event side effects
+    pub fn handler(mut self, handler: Arc<dyn EventHandler>) -> Self {
+        self.handler = handler;
+        self
+    }
+
+    /// Split inbound dispatch across `count` shard workers (default: 1)
+    pub fn shards(mut self, count: usize) -> Self {
+        self.shard_count = count.max(1);
+        self
+    }
+
+    /// Start the bot: `inbound` is the stream of messages a real gateway
+    /// client feeds in from Discord, sharded across worker tasks that each
+    /// own a subset of channels' [`ChannelSession`]s
+    pub async fn run(self, mut inbound: mpsc::Receiver<DiscordMessage>) -> DiscordBotHandle {
+        self.handler.on_ready().await;
+
+        let mut shard_senders = Vec::with_capacity(self.shard_count);
+        let mut shard_tasks = Vec::with_capacity(self.shard_count);
+        for _ in 0..self.shard_count {
+            let (tx, rx) = mpsc::channel::<DiscordMessage>(256);
+            shard_senders.push(tx);
+            shard_tasks.push(tokio::spawn(Self::run_shard(
+                self.agent.clone(),
+                self.handler.clone(),
+                self.transport.clone(),
+                rx,
+            )));
+        }
+
+        let shard_count = self.shard_count;
+        shard_tasks.push(tokio::spawn(async move {
+            while let Some(message) = inbound.recv().await {
+                let shard = shard_index(&message.channel, shard_count);
+                if shard_senders[shard].send(message).await.is_err() {
+                    warn!("discord: shard {shard} dispatch channel closed");
+                }
+            }
+        }));
+
+        DiscordBotHandle { shard_tasks }
+    }
+
+    /// One shard's worker loop: lazily starts a per-channel `Agent`
+    /// conversation on first message, then dispatches every inbound message
+    /// to its channel's session and streams the reply back as an edited
+    /// Discord message
+    async fn run_shard(
+        agent: Agent,
+        handler: Arc<dyn EventHandler>,
+        transport: Arc<dyn GatewayTransport>,
+        mut inbound: mpsc::Receiver<DiscordMessage>,
+    ) {
+        let cache = Cache::default();
+        let mut sessions: HashMap<ChannelId, ChannelSession> = HashMap::new();
+
+        while let Some(message) = inbound.recv().await {
+            cache
+                .record_user(message.author.clone(), message.author_name.clone())
+                .await;
+
+            let session = match sessions.get(&message.channel) {
+                Some(session) => session.clone(),
+                None => {
+                    match Self::spawn_channel_session(
+                        agent.clone(),
+                        message.channel.clone(),
+                        transport.clone(),
+                    )
+                    .await
+                    {
+                        Ok(session) => {
+                            sessions.insert(message.channel.clone(), session.clone());
+                            session
+                        }
+                        Err(e) => {
+                            error!("discord: failed to start agent for channel '{}': {e}", message.channel);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            let ctx = Context {
+                channel: message.channel.clone(),
+                session: session.clone(),
+                cache: cache.clone(),
+            };
+            handler.on_message(&ctx, &message).await;
+
+            if let Err(e) = session.say(message.content.clone()).await {
+                error!("discord: failed to forward message to agent: {e}");
+            }
+        }
+    }
+
+    /// Start a fresh `Agent::execute` conversation for `channel` and spawn
+    /// the task that streams its output back to Discord as edited messages
+    async fn spawn_channel_session(
+        agent: Agent,
+        channel: ChannelId,
+        transport: Arc<dyn GatewayTransport>,
+    ) -> Result<ChannelSession> {
+        let (input_tx, input_rx) = mpsc::channel(100);
+        let (plan_tx, mut plan_rx) = mpsc::channel(100);
+        let (output_tx, mut output_rx) = mpsc::channel(100);
+
+        let handle = agent.execute(input_rx, plan_tx, output_tx).await?;
+        let controller = handle.controller().clone();
+
+        // Plan updates have no Discord-side representation yet; drained so
+        // the channel never backs up.
+        tokio::spawn(async move { while plan_rx.recv().await.is_some() {} });
+
+        tokio::spawn(async move {
+            let mut reply_message_id: Option<String> = None;
+            let mut reply_text = String::new();
+
+            while let Some(output) = output_rx.recv().await {
+                match output.data {
+                    OutputData::PrimaryDelta(delta) => {
+                        reply_text.push_str(&delta);
+                        let result = match &reply_message_id {
+                            Some(id) => transport.edit_message(&channel, id, &reply_text).await.map(|_| ()),
+                            None => transport
+                                .send_message(&channel, &reply_text)
+                                .await
+                                .map(|id| reply_message_id = Some(id)),
+                        };
+                        if let Err(e) = result {
+                            error!("discord: failed to stream reply to channel '{channel}': {e}");
+                        }
+                    }
+                    OutputData::Primary(text) => {
+                        let result = match &reply_message_id {
+                            Some(id) => transport.edit_message(&channel, id, &text).await.map(|_| ()),
+                            None => transport
+                                .send_message(&channel, &text)
+                                .await
+                                .map(|id| reply_message_id = Some(id)),
+                        };
+                        if let Err(e) = result {
+                            error!("discord: failed to send reply to channel '{channel}': {e}");
+                        }
+                    }
+                    OutputData::Completed => {
+                        reply_message_id = None;
+                        reply_text.clear();
+                    }
+                    OutputData::Error(err) => {
+                        error!("discord: agent error in channel '{channel}': {err}");
+                    }
+                    _ => {}
+                }
+            }
+            // `handle` is held alive for the session's lifetime by this task.
+            let _ = handle.join().await;
+        });
+
+        Ok(ChannelSession {
+            controller,
+            input_tx,
+        })
+    }
+}
+
+/// Default, do-nothing [`EventHandler`] used when none is registered
+struct NoopEventHandler;
+
+impl EventHandler for NoopEventHandler {}
+
+/// Hash a channel id onto one of `shard_count` shards
+fn shard_index(channel: &str, shard_count: usize) -> usize {
+    use std::hash::Hash;
+    use std::hash::Hasher;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    channel.hash(&mut hasher);
+    (hasher.finish() as usize) % shard_count.max(1)
+}
@@ -0,0 +1,291 @@
+//! Matrix connector: let an [`Agent`](crate::Agent) join Matrix rooms and
+//! respond, including rooms with end-to-end encryption enabled
+//!
+//! Bridges the Client-Server API's sync/send loop to an [`AgentSession`] per
+//! room: inbound `m.room.message` events become [`InputMessage`]s, and the
+//! agent's [`OutputData`] stream is sent back as `m.room.message` events.
+//! Encrypted rooms are supported by routing events through a [`RoomCrypto`]
+//! implementation before they reach the agent and after a reply leaves it,
+//! the same way [`super::discord::GatewayTransport`] keeps the Discord
+//! connector free of a networking dependency: this module defines the
+//! sync/send/crypto contracts, a real client (e.g. backed by `matrix-sdk`)
+//! implements them.
+//!
+//! Requires the `session` feature (for [`AgentSession`]) in addition to
+//! `matrix`.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use crate::Agent;
+use crate::error::Result;
+use crate::message::OutputData;
+use crate::session::AgentSession;
+use crate::session::SessionMetrics;
+
+/// A Matrix room id, e.g. `!abc123:example.org`
+pub type RoomId = String;
+
+/// An inbound `m.room.message` event, already decrypted if the room uses
+/// end-to-end encryption
+#[derive(Debug, Clone)]
+pub struct RoomMessage {
+    /// Room the event was sent in
+    pub room: RoomId,
+    /// Sender's Matrix user id, e.g. `@alice:example.org`
+    pub sender: String,
+    /// Plaintext message body
+    pub body: String,
+    /// The event's own id, used as the `m.relates_to` target for streamed
+    /// edits (`m.replace`)
+    pub event_id: String,
+}
+
+/// Encrypts and decrypts room events for end-to-end-encrypted rooms
+///
+/// A plaintext (non-encrypted) room is modeled as the identity
+/// implementation: `decrypt`/`encrypt` just pass the body through.
+pub trait RoomCrypto: Send + Sync {
+    /// Decrypt an inbound event body for `room`; returns the plaintext as-is
+    /// if `room` is not encrypted
+    fn decrypt<'a>(
+        &'a self,
+        room: &'a str,
+        ciphertext: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+
+    /// Encrypt an outbound event body for `room`; returns the plaintext as-is
+    /// if `room` is not encrypted
+    fn encrypt<'a>(
+        &'a self,
+        room: &'a str,
+        plaintext: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+}
+
+/// Client-Server API sync/send operations
+///
+/// Owns the actual homeserver connection; [`MatrixConnector`] never calls
+/// the network directly.
+pub trait MatrixTransport: Send + Sync {
+    /// Send (already encrypted, if applicable) event content as a new
+    /// `m.room.message` in `room`, returning its event id
+    fn send_event<'a>(
+        &'a self,
+        room: &'a str,
+        content: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<String>> + Send + 'a>>;
+
+    /// Send an `m.replace` edit of `event_id`, used to stream an in-progress
+    /// agent reply without posting a new event per delta
+    fn send_edit<'a>(
+        &'a self,
+        room: &'a str,
+        event_id: &'a str,
+        content: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+}
+
+/// A single room's conversation with the agent
+///
+/// Wraps an [`AgentSession`] so history and [`SessionMetrics`] survive a
+/// process restart the same way any other persisted session does — a room
+/// is just a session keyed by `RoomId` instead of a bare session id.
+pub struct MatrixSession {
+    room: RoomId,
+    session: AgentSession,
+}
+
+impl MatrixSession {
+    /// Start a fresh session for `room`
+    pub async fn start(room: RoomId, agent: Agent) -> Result<Self> {
+        let mut session = AgentSession::new(agent);
+        session.start().await?;
+        Ok(Self { room, session })
+    }
+
+    /// Resume this room's conversation from a previously saved snapshot at
+    /// `path` (see [`AgentSession::resume`])
+    pub async fn resume(room: RoomId, path: &std::path::Path, agent: Agent) -> Result<Self> {
+        let session = AgentSession::resume(path, agent).await?;
+        Ok(Self { room, session })
+    }
+
+    /// Forward a decrypted message body to the agent
+    pub async fn send(&mut self, body: String) -> Result<()> {
+        self.session.send(body).await
+    }
+
+    /// This room's accumulated metrics
+    pub async fn metrics(&self) -> SessionMetrics {
+        self.session.get_metrics().await
+    }
+
+    /// Persist this room's conversation to `path`
+    pub async fn save(&self, path: &std::path::Path) -> Result<()> {
+        self.session.save_session(path).await
+    }
+}
+
+/// Bridges a [`MatrixTransport`]'s sync stream to one [`MatrixSession`] per
+/// joined room, decrypting inbound events and encrypting outbound replies
+/// through `crypto`
+pub struct MatrixConnector {
+    agent: Agent,
+    transport: Arc<dyn MatrixTransport>,
+    crypto: Arc<dyn RoomCrypto>,
+}
+
+impl MatrixConnector {
+    /// Create a connector that spawns a fresh agent conversation per room
+    pub fn new(
+        agent: Agent,
+        transport: Arc<dyn MatrixTransport>,
+        crypto: Arc<dyn RoomCrypto>,
+    ) -> Self {
+        Self {
+            agent,
+            transport,
+            crypto,
+        }
+    }
+
+    /// Run the bridge: consume `sync_events` (as produced by a real
+    /// Client-Server `/sync` loop) and stream each room's agent replies back
+    /// as `m.room.message`/`m.replace` events until the channel closes
+    pub async fn run(self, mut sync_events: mpsc::Receiver<RoomMessage>) -> Result<()> {
+        let mut sessions: HashMap<RoomId, mpsc::Sender<String>> = HashMap::new();
+        let mut room_tasks = Vec::new();
+
+        while let Some(event) = sync_events.recv().await {
+            let plaintext = self.crypto.decrypt(&event.room, &event.body).await?;
+
+            if !sessions.contains_key(&event.room) {
+                let (tx, rx) = mpsc::channel::<String>(100);
+                sessions.insert(event.room.clone(), tx);
+                room_tasks.push(tokio::spawn(Self::run_room(
+                    event.room.clone(),
+                    self.agent.clone(),
+                    self.transport.clone(),
+                    self.crypto.clone(),
+                    rx,
+                )));
+            }
+
+            if let Some(tx) = sessions.get(&event.room) {
+                let _ = tx.send(plaintext).await;
+            }
+        }
+
+        for task in room_tasks {
+            let _ = task.await;
+        }
+
+        Ok(())
+    }
+
+    /// One room's lifetime: owns its [`MatrixSession`] and the task that
+    /// streams the agent's replies back through `transport`/`crypto`
+    async fn run_room(
+        room: RoomId,
+        agent: Agent,
+        transport: Arc<dyn MatrixTransport>,
+        crypto: Arc<dyn RoomCrypto>,
+        mut inbound: mpsc::Receiver<String>,
+    ) {
+        let mut session = match MatrixSession::start(room.clone(), agent).await {
+            Ok(session) => session,
+            Err(e) => {
+                tracing::error!("matrix: failed to start agent for room '{room}': {e}");
+                return;
+            }
+        };
+
+        let (output_forward_tx, mut output_forward_rx) = mpsc::channel::<OutputData>(100);
+        session.session.set_output_tap(output_forward_tx);
+
+        let reply_room = room.clone();
+        let reply_transport = transport.clone();
+        let reply_crypto = crypto.clone();
+        tokio::spawn(async move {
+            let mut reply_event_id: Option<String> = None;
+            let mut reply_text = String::new();
+
+            while let Some(data) = output_forward_rx.recv().await {
+                match data {
+                    OutputData::PrimaryDelta(delta) => {
+                        reply_text.push_str(&delta);
+                        Self::publish_reply(
+                            &reply_room,
+                            &reply_text,
+                            &mut reply_event_id,
+                            &reply_transport,
+                            &reply_crypto,
+                        )
+                        .await;
+                    }
+                    OutputData::Primary(text) => {
+                        reply_text = text;
+                        Self::publish_reply(
+                            &reply_room,
+                            &reply_text,
+                            &mut reply_event_id,
+                            &reply_transport,
+                            &reply_crypto,
+                        )
+                        .await;
+                    }
+                    OutputData::Completed => {
+                        reply_event_id = None;
+                        reply_text.clear();
+                    }
+                    OutputData::Error(err) => {
+                        tracing::error!("matrix: agent error in room '{reply_room}': {err}");
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        while let Some(body) = inbound.recv().await {
+            if let Err(e) = session.send(body).await {
+                tracing::error!("matrix: failed to forward message to agent in room '{room}': {e}");
+            }
+        }
+    }
+
+    /// Encrypt (if needed) and publish the agent's current reply text,
+    /// sending a new event the first time and editing it on every
+    /// subsequent delta
+    async fn publish_reply(
+        room: &str,
+        text: &str,
+        reply_event_id: &mut Option<String>,
+        transport: &Arc<dyn MatrixTransport>,
+        crypto: &Arc<dyn RoomCrypto>,
+    ) {
+        let encrypted = match crypto.encrypt(room, text).await {
+            Ok(content) => content,
+            Err(e) => {
+                tracing::error!("matrix: failed to encrypt reply for room '{room}': {e}");
+                return;
+            }
+        };
+
+        let result = match reply_event_id.as_ref() {
+            Some(event_id) => transport.send_edit(room, event_id, &encrypted).await,
+            None => transport
+                .send_event(room, &encrypted)
+                .await
+                .map(|id| *reply_event_id = Some(id)),
+        };
+
+        if let Err(e) = result {
+            tracing::error!("matrix: failed to publish reply to room '{room}': {e}");
+        }
+    }
+}
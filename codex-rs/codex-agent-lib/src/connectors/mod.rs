@@ -0,0 +1,11 @@
+//! Chat-platform connectors that let an [`Agent`](crate::Agent) run as a
+//! long-lived bot instead of a one-shot `execute` call
+//!
+//! Each connector is its own feature-gated submodule so a binary only pays
+//! for the platform(s) it actually embeds.
+
+#[cfg(feature = "discord")]
+pub mod discord;
+
+#[cfg(feature = "matrix")]
+pub mod matrix;
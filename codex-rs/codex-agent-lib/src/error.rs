@@ -30,6 +30,9 @@ pub enum AgentError {
     #[error("MCP server error: {0}")]
     McpError(String),
 
+    #[error("Plan channel error: {0}")]
+    PlanChannelError(String),
+
     #[error("Internal error: {0}")]
     InternalError(String),
 
@@ -41,7 +44,7 @@ pub enum AgentError {
 }
 
 /// Error types that can be sent as output messages
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum OutputError {
     /// Turn limit exceeded
     TurnLimitExceeded,
@@ -95,5 +98,44 @@ impl From<codex_core::error::CodexErr> for OutputError {
     }
 }
 
+impl OutputError {
+    /// Returns true if this error represents a transient condition (a dropped
+    /// connection, a flaky model response) that the agent's run loop should
+    /// retry rather than surface as a terminal `OutputData::Error`.
+    ///
+    /// Fatal errors (e.g. bad configuration, failed authentication) are never
+    /// recoverable since retrying them cannot change the outcome.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(self, Self::ModelError(_) | Self::NetworkError(_))
+    }
+
+    /// Classify a bare `EventMsg::Error` message into an [`OutputError`].
+    ///
+    /// `codex_core::protocol::ErrorEvent` carries only a human-readable
+    /// message, unlike the typed [`codex_core::error::CodexErr`] handled by
+    /// the `From` impl above, so this falls back to sniffing the text for the
+    /// same transient-condition keywords a network/model failure tends to
+    /// produce. Anything that doesn't match stays [`OutputError::Unknown`]
+    /// (and therefore non-recoverable), which is the safe default.
+    pub fn from_event_message(message: String) -> Self {
+        let lower = message.to_lowercase();
+        if lower.contains("network")
+            || lower.contains("connection")
+            || lower.contains("timed out")
+            || lower.contains("timeout")
+            || lower.contains("stream")
+        {
+            Self::NetworkError(message)
+        } else if lower.contains("rate limit")
+            || lower.contains("overloaded")
+            || lower.contains("model")
+        {
+            Self::ModelError(message)
+        } else {
+            Self::Unknown(message)
+        }
+    }
+}
+
 /// Result type for agent operations
 pub type Result<T> = std::result::Result<T, AgentError>;
@@ -0,0 +1,157 @@
+//! Pluggable event-handler registry for [`crate::Agent::execute`]'s event loop
+//!
+//! The event loop behind [`crate::Agent::execute`] matches specific
+//! `codex_core` event variants (plan updates into [`crate::PlanMessage`],
+//! patch/exec begin-end pairs into [`crate::OutputData`]) and drops
+//! everything else into a `debug!` catch-all. An [`EventHandlerRegistry`]
+//! lets a caller register typed handlers keyed by [`EventKind`] instead,
+//! each receiving the decoded event and an [`EventHandlerContext`] it can
+//! use to emit its own [`crate::OutputMessage`]s -- observing reasoning,
+//! tool calls, or token usage without forking the event-loop match arm.
+//!
+//! The default registry is empty, so a caller that never registers a
+//! handler sees exactly today's built-in behavior.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use codex_core::protocol::EventMsg;
+use tokio::sync::mpsc;
+
+use crate::agent::Agent;
+use crate::agent::AgentController;
+use crate::message::OutputData;
+use crate::message::OutputMessage;
+
+/// Coarse category an [`EventMsg`] is classified into for dispatch, so a
+/// handler registers interest in (say) every tool-call event instead of
+/// matching each of `codex_core`'s many `EventMsg` variants itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventKind {
+    /// Assistant message text, streamed or whole
+    AgentMessage,
+    /// Model reasoning output
+    Reasoning,
+    /// MCP tool call lifecycle (arguments, begin, end)
+    ToolCall,
+    /// `apply_patch` lifecycle
+    PatchApply,
+    /// Shell command execution lifecycle
+    Exec,
+    /// Token usage accounting
+    TokenUsage,
+    /// Plan/todo list updates
+    PlanUpdate,
+    /// Turn and session lifecycle (start, complete, aborted)
+    TurnLifecycle,
+    /// Anything not classified above
+    Other,
+}
+
+impl EventKind {
+    /// Classify `event` into the [`EventKind`] handlers register against
+    fn of(event: &EventMsg) -> Self {
+        match event {
+            EventMsg::AgentMessage(_) | EventMsg::AgentMessageDelta(_) => Self::AgentMessage,
+            EventMsg::AgentReasoning(_) => Self::Reasoning,
+            EventMsg::McpToolCallArgumentsDelta(_)
+            | EventMsg::McpToolCallBegin(_)
+            | EventMsg::McpToolCallEnd(_) => Self::ToolCall,
+            EventMsg::PatchApplyBegin(_) | EventMsg::PatchApplyEnd(_) => Self::PatchApply,
+            EventMsg::ExecCommandBegin(_)
+            | EventMsg::ExecCommandOutputDelta(_)
+            | EventMsg::ExecCommandEnd(_) => Self::Exec,
+            EventMsg::TokenCount(_) => Self::TokenUsage,
+            EventMsg::PlanUpdate(_) => Self::PlanUpdate,
+            EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_) | EventMsg::SessionConfigured(_) => {
+                Self::TurnLifecycle
+            }
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Side channel a registered [`EventHandler`] uses to emit its own
+/// [`OutputMessage`]s, the same `output_tx`/broadcast fan-out
+/// [`Agent::publish_output`] uses for the built-in event-loop logic
+#[derive(Clone)]
+pub struct EventHandlerContext {
+    output_tx: mpsc::Sender<OutputMessage>,
+    controller: AgentController,
+    turn_id: u64,
+}
+
+impl EventHandlerContext {
+    pub(crate) fn new(
+        output_tx: mpsc::Sender<OutputMessage>,
+        controller: AgentController,
+        turn_id: u64,
+    ) -> Self {
+        Self {
+            output_tx,
+            controller,
+            turn_id,
+        }
+    }
+
+    /// Publish `data` under the current turn, fanning it out the same way
+    /// the built-in event-loop logic does
+    pub async fn emit(&self, data: OutputData) {
+        Agent::publish_output(
+            &self.output_tx,
+            &self.controller,
+            OutputMessage {
+                turn_id: self.turn_id,
+                data,
+            },
+        )
+        .await;
+    }
+}
+
+/// A handler for one [`EventKind`] of core event, registered with an
+/// [`EventHandlerRegistry`]
+pub trait EventHandler: Send + Sync {
+    /// The [`EventKind`] this handler wants to see
+    fn kind(&self) -> EventKind;
+
+    /// Handle one matching event, optionally emitting output via `ctx`
+    fn handle(
+        &self,
+        event: &EventMsg,
+        ctx: &EventHandlerContext,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+/// Registry of [`EventHandler`]s, keyed by [`EventKind`], that the agent's
+/// event loop dispatches every incoming core event through before its own
+/// built-in plan/todo logic runs
+#[derive(Clone, Default)]
+pub struct EventHandlerRegistry {
+    handlers: HashMap<EventKind, Vec<Arc<dyn EventHandler>>>,
+}
+
+impl EventHandlerRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `handler` to run for every event of its [`EventHandler::kind`]
+    pub fn register(&mut self, handler: Arc<dyn EventHandler>) {
+        self.handlers.entry(handler.kind()).or_default().push(handler);
+    }
+
+    /// Run every handler registered for `event`'s [`EventKind`], in
+    /// registration order
+    pub(crate) async fn dispatch(&self, event: &EventMsg, ctx: &EventHandlerContext) {
+        let Some(handlers) = self.handlers.get(&EventKind::of(event)) else {
+            return;
+        };
+        for handler in handlers {
+            handler.handle(event, ctx).await;
+        }
+    }
+}
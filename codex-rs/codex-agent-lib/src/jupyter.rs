@@ -0,0 +1,515 @@
+//! Persistent Jupyter kernel backend for stateful code execution
+//!
+//! Replaces throwaway `uv run python script.py` invocations with a
+//! long-lived, stateful interpreter: variables and imports persist across
+//! turns, and the kernel can emit rich media (images, markdown, HTML)
+//! instead of plain stdout text. A [`KernelSession`] spawns
+//! `uv run python -m ipykernel_launcher -f <connection.json>`, parses the
+//! connection file the launcher writes, and speaks the Jupyter wire
+//! protocol over it: an `execute_request` on the shell channel, followed by
+//! `stream`/`execute_result`/`display_data`/`error` messages collected from
+//! the iopub channel and keyed by the request's `msg_id`.
+//!
+//! The actual ZeroMQ sockets are left to a [`KernelTransport`]
+//! implementation (mirrors [`crate::connectors::discord::GatewayTransport`]:
+//! the wire-protocol framing/signing lives here, the socket I/O is the
+//! host's responsibility), so this crate doesn't need to depend on a
+//! specific ZeroMQ binding.
+
+#[cfg(feature = "jupyter")]
+use crate::error::AgentError;
+#[cfg(feature = "jupyter")]
+use crate::message::OutputData;
+#[cfg(feature = "jupyter")]
+use crate::Result;
+#[cfg(feature = "jupyter")]
+use base64::Engine;
+#[cfg(feature = "jupyter")]
+use hmac::Hmac;
+#[cfg(feature = "jupyter")]
+use hmac::Mac;
+#[cfg(feature = "jupyter")]
+use serde::Deserialize;
+#[cfg(feature = "jupyter")]
+use serde::Serialize;
+#[cfg(feature = "jupyter")]
+use sha2::Sha256;
+#[cfg(feature = "jupyter")]
+use std::collections::HashMap;
+#[cfg(feature = "jupyter")]
+use std::future::Future;
+#[cfg(feature = "jupyter")]
+use std::pin::Pin;
+#[cfg(feature = "jupyter")]
+use std::sync::atomic::AtomicBool;
+#[cfg(feature = "jupyter")]
+use std::sync::atomic::Ordering;
+#[cfg(feature = "jupyter")]
+use std::sync::Arc;
+#[cfg(feature = "jupyter")]
+use tokio::process::Child;
+#[cfg(feature = "jupyter")]
+use tokio::sync::mpsc;
+
+/// The multipart delimiter separating the (usually empty) ZeroMQ identity
+/// frames from the signed message frames, per the Jupyter wire protocol
+#[cfg(feature = "jupyter")]
+pub const DELIMITER: &[u8] = b"<IDS|MSG>";
+
+/// Connection parameters a kernel launcher writes to its `connection.json`,
+/// read back here to know which ports/key to dial
+#[cfg(feature = "jupyter")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+    pub transport: String,
+    pub ip: String,
+    pub shell_port: u16,
+    pub iopub_port: u16,
+    pub stdin_port: u16,
+    pub control_port: u16,
+    pub hb_port: u16,
+    pub key: String,
+    pub signature_scheme: String,
+}
+
+impl ConnectionInfo {
+    /// A fresh connection file's parameters, bound to `ip` on ports chosen
+    /// by the caller (typically 0 for "ask the OS", then read back after
+    /// the launcher binds -- this crate doesn't allocate ports itself)
+    pub fn new(ip: impl Into<String>, key: impl Into<String>) -> Self {
+        Self {
+            transport: "tcp".to_string(),
+            ip: ip.into(),
+            shell_port: 0,
+            iopub_port: 0,
+            stdin_port: 0,
+            control_port: 0,
+            hb_port: 0,
+            key: key.into(),
+            signature_scheme: "hmac-sha256".to_string(),
+        }
+    }
+
+    /// The `<transport>://<ip>:<port>` address for a given port
+    pub fn endpoint(&self, port: u16) -> String {
+        format!("{}://{}:{}", self.transport, self.ip, port)
+    }
+}
+
+/// Which ZeroMQ channel a message is sent/received on
+#[cfg(feature = "jupyter")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Shell,
+    Iopub,
+    Control,
+    Stdin,
+}
+
+/// Sends and receives raw multipart Jupyter wire-protocol frames
+///
+/// This is the only point where a real implementation needs to talk to
+/// ZeroMQ (a DEALER socket for [`Channel::Shell`]/[`Channel::Control`], a
+/// SUB socket subscribed to everything for [`Channel::Iopub`]);
+/// [`KernelSession`] never touches a socket directly, it only builds and
+/// parses the signed frame lists this trait moves.
+#[cfg(feature = "jupyter")]
+pub trait KernelTransport: Send + Sync {
+    /// Send a multipart message (frames after the identity/delimiter split)
+    /// on `channel`
+    fn send<'a>(
+        &'a self,
+        channel: Channel,
+        frames: Vec<Vec<u8>>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+    /// Block until the next multipart message arrives on `channel`
+    fn recv<'a>(
+        &'a self,
+        channel: Channel,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Vec<u8>>>> + Send + 'a>>;
+}
+
+/// A parsed Jupyter message header
+#[cfg(feature = "jupyter")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageHeader {
+    pub msg_id: String,
+    pub session: String,
+    pub username: String,
+    pub date: String,
+    pub msg_type: String,
+    pub version: String,
+}
+
+/// A single Jupyter protocol message: request, reply, or iopub broadcast
+#[cfg(feature = "jupyter")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KernelMessage {
+    pub header: MessageHeader,
+    #[serde(default)]
+    pub parent_header: serde_json::Value,
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+    pub content: serde_json::Value,
+}
+
+impl KernelMessage {
+    /// Build a fresh request message with an empty `parent_header`
+    fn request(session: &str, msg_type: &str, content: serde_json::Value) -> Self {
+        Self {
+            header: MessageHeader {
+                msg_id: uuid::Uuid::new_v4().to_string(),
+                session: session.to_string(),
+                username: "codex".to_string(),
+                date: "1970-01-01T00:00:00.000000Z".to_string(),
+                msg_type: msg_type.to_string(),
+                version: "5.3".to_string(),
+            },
+            parent_header: serde_json::Value::Object(Default::default()),
+            metadata: serde_json::Value::Object(Default::default()),
+            content,
+        }
+    }
+
+    /// `msg_id` this message's `parent_header` claims to be replying to, if
+    /// it has one (iopub broadcasts always do; requests don't)
+    fn parent_msg_id(&self) -> Option<&str> {
+        self.parent_header.get("msg_id")?.as_str()
+    }
+}
+
+/// HMAC-SHA256-sign the header/parent_header/metadata/content frames and
+/// assemble the `[signature, header, parent_header, metadata, content]`
+/// portion of the wire format (the caller prepends identity frames + the
+/// `<IDS|MSG>` delimiter)
+#[cfg(feature = "jupyter")]
+fn sign_and_frame(key: &[u8], msg: &KernelMessage) -> Result<Vec<Vec<u8>>> {
+    let header = serde_json::to_vec(&msg.header).map_err(to_internal)?;
+    let parent_header = serde_json::to_vec(&msg.parent_header).map_err(to_internal)?;
+    let metadata = serde_json::to_vec(&msg.metadata).map_err(to_internal)?;
+    let content = serde_json::to_vec(&msg.content).map_err(to_internal)?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(to_internal)?;
+    for frame in [&header, &parent_header, &metadata, &content] {
+        mac.update(frame);
+    }
+    let signature = hex::encode(mac.finalize().into_bytes());
+
+    Ok(vec![
+        signature.into_bytes(),
+        header,
+        parent_header,
+        metadata,
+        content,
+    ])
+}
+
+/// Parse a full multipart frame list (identity frames, delimiter, signature,
+/// header, parent_header, metadata, content) back into a [`KernelMessage`],
+/// verifying the signature against `key`
+#[cfg(feature = "jupyter")]
+fn parse_frames(key: &[u8], frames: &[Vec<u8>]) -> Result<KernelMessage> {
+    let delim_idx = frames
+        .iter()
+        .position(|f| f.as_slice() == DELIMITER)
+        .ok_or_else(|| AgentError::ConnectionError("missing <IDS|MSG> delimiter".to_string()))?;
+    let signed = &frames[delim_idx + 1..];
+    if signed.len() < 5 {
+        return Err(AgentError::ConnectionError(
+            "truncated kernel message".to_string(),
+        ));
+    }
+    let (signature, body) = (&signed[0], &signed[1..5]);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(to_internal)?;
+    for frame in body {
+        mac.update(frame);
+    }
+    let expected = hex::encode(mac.finalize().into_bytes());
+    if !constant_time_eq(expected.as_bytes(), signature) {
+        return Err(AgentError::ConnectionError(
+            "kernel message signature mismatch".to_string(),
+        ));
+    }
+
+    Ok(KernelMessage {
+        header: serde_json::from_slice(&body[0]).map_err(to_internal)?,
+        parent_header: serde_json::from_slice(&body[1]).map_err(to_internal)?,
+        metadata: serde_json::from_slice(&body[2]).map_err(to_internal)?,
+        content: serde_json::from_slice(&body[3]).map_err(to_internal)?,
+    })
+}
+
+#[cfg(feature = "jupyter")]
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(feature = "jupyter")]
+fn to_internal<E: std::fmt::Display>(e: E) -> AgentError {
+    AgentError::InternalError(e.to_string())
+}
+
+/// Result of a single `execute_request`, once the kernel has gone back to
+/// idle: whether it raised an `error`, and the execution count reported by
+/// its `execute_result`/`display_data` output, if any
+#[derive(Debug, Clone, Default)]
+#[cfg(feature = "jupyter")]
+pub struct ExecuteSummary {
+    pub execution_count: Option<u64>,
+    pub errored: bool,
+}
+
+/// A running kernel's process handle plus the sockets talking to it
+///
+/// Owns the spawned `ipykernel_launcher` child process and drives the
+/// handshake (`kernel_info_request`), code execution, and interrupt/
+/// shutdown over the shell/iopub/control channels described in the module
+/// docs.
+#[cfg(feature = "jupyter")]
+pub struct KernelSession {
+    transport: Arc<dyn KernelTransport>,
+    key: Vec<u8>,
+    session_id: String,
+    child: Child,
+    is_processing: Arc<AtomicBool>,
+}
+
+#[cfg(feature = "jupyter")]
+impl KernelSession {
+    /// Spawn `uv run python -m ipykernel_launcher -f <connection_file>`,
+    /// wait for it to report ready over `transport`, and complete the
+    /// `kernel_info_request`/`kernel_info_reply` handshake
+    pub async fn spawn(
+        connection_file: &std::path::Path,
+        connection: &ConnectionInfo,
+        transport: Arc<dyn KernelTransport>,
+    ) -> Result<Self> {
+        let json = serde_json::to_vec_pretty(connection).map_err(to_internal)?;
+        tokio::fs::write(connection_file, json)
+            .await
+            .map_err(to_internal)?;
+
+        let child = tokio::process::Command::new("uv")
+            .args([
+                "run",
+                "python",
+                "-m",
+                "ipykernel_launcher",
+                "-f",
+                &connection_file.to_string_lossy(),
+            ])
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(to_internal)?;
+
+        let session = Self {
+            transport,
+            key: connection.key.clone().into_bytes(),
+            session_id: uuid::Uuid::new_v4().to_string(),
+            child,
+            is_processing: Arc::new(AtomicBool::new(false)),
+        };
+
+        session.kernel_info_handshake().await?;
+        Ok(session)
+    }
+
+    /// Whether the kernel is currently busy running an `execute_request`
+    /// (tracked from iopub `busy`/`idle` status messages), for callers that
+    /// want to drive a TUI's "is processing" indicator
+    pub fn is_processing(&self) -> bool {
+        self.is_processing.load(Ordering::SeqCst)
+    }
+
+    async fn kernel_info_handshake(&self) -> Result<()> {
+        let request = KernelMessage::request(
+            &self.session_id,
+            "kernel_info_request",
+            serde_json::Value::Object(Default::default()),
+        );
+        self.send(Channel::Shell, &request).await?;
+        loop {
+            let reply = self.recv(Channel::Shell).await?;
+            if reply.header.msg_type == "kernel_info_reply" {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Run `code`, emitting [`OutputData`] frames to `output` as iopub
+    /// messages for this request's `msg_id` arrive, returning once the
+    /// kernel reports `idle` for it
+    pub async fn execute(
+        &self,
+        code: &str,
+        output: mpsc::Sender<OutputData>,
+    ) -> Result<ExecuteSummary> {
+        let content = serde_json::json!({
+            "code": code,
+            "silent": false,
+            "store_history": true,
+            "user_expressions": {},
+            "allow_stdin": false,
+            "stop_on_error": true,
+        });
+        let request = KernelMessage::request(&self.session_id, "execute_request", content);
+        let msg_id = request.header.msg_id.clone();
+        self.send(Channel::Shell, &request).await?;
+
+        self.is_processing.store(true, Ordering::SeqCst);
+        let mut summary = ExecuteSummary::default();
+
+        loop {
+            let msg = self.recv(Channel::Iopub).await?;
+            if msg.parent_msg_id() != Some(msg_id.as_str()) {
+                // Broadcast for a different request (or none); iopub is
+                // shared across every client attached to this kernel
+                continue;
+            }
+
+            match msg.header.msg_type.as_str() {
+                "status" => {
+                    let busy = msg.content.get("execution_state").and_then(|v| v.as_str())
+                        == Some("busy");
+                    self.is_processing.store(busy, Ordering::SeqCst);
+                    if !busy {
+                        break;
+                    }
+                }
+                "stream" => {
+                    let text = msg
+                        .content
+                        .get("text")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default();
+                    let name = msg
+                        .content
+                        .get("name")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("stdout");
+                    let _ = output
+                        .send(OutputData::ToolOutputDelta {
+                            tool_name: format!("jupyter:{name}"),
+                            chunk: text.to_string(),
+                        })
+                        .await;
+                }
+                "execute_result" | "display_data" => {
+                    if let Some(count) = msg.content.get("execution_count").and_then(|v| v.as_u64())
+                    {
+                        summary.execution_count = Some(count);
+                    }
+                    if let Some(data) = msg.content.get("data").and_then(|v| v.as_object()) {
+                        for (mime, value) in data {
+                            let bytes = mime_bundle_to_bytes(mime, value)?;
+                            let _ = output
+                                .send(OutputData::RichOutput {
+                                    mime: mime.clone(),
+                                    data: bytes,
+                                })
+                                .await;
+                        }
+                    }
+                }
+                "error" => {
+                    summary.errored = true;
+                    let ename = msg
+                        .content
+                        .get("ename")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("Error")
+                        .to_string();
+                    let evalue = msg
+                        .content
+                        .get("evalue")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or_default()
+                        .to_string();
+                    let traceback = msg
+                        .content
+                        .get("traceback")
+                        .and_then(|v| v.as_array())
+                        .map(|lines| {
+                            lines
+                                .iter()
+                                .filter_map(|l| l.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    let _ = output
+                        .send(OutputData::Traceback {
+                            ename,
+                            evalue,
+                            traceback,
+                        })
+                        .await;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Interrupt the kernel's currently running cell via the control
+    /// channel (e.g. when [`crate::AgentController::stop`] is invoked
+    /// mid-execution)
+    pub async fn interrupt(&self) -> Result<()> {
+        let request = KernelMessage::request(
+            &self.session_id,
+            "interrupt_request",
+            serde_json::Value::Object(Default::default()),
+        );
+        self.send(Channel::Control, &request).await
+    }
+
+    /// Ask the kernel to shut down cleanly over the control channel, then
+    /// wait for the child process to exit
+    pub async fn shutdown(mut self) -> Result<()> {
+        let request = KernelMessage::request(
+            &self.session_id,
+            "shutdown_request",
+            serde_json::json!({ "restart": false }),
+        );
+        self.send(Channel::Control, &request).await?;
+        self.child.wait().await.map_err(to_internal)?;
+        Ok(())
+    }
+
+    async fn send(&self, channel: Channel, msg: &KernelMessage) -> Result<()> {
+        let mut frames = vec![DELIMITER.to_vec()];
+        frames.extend(sign_and_frame(&self.key, msg)?);
+        self.transport.send(channel, frames).await
+    }
+
+    async fn recv(&self, channel: Channel) -> Result<KernelMessage> {
+        let frames = self.transport.recv(channel).await?;
+        parse_frames(&self.key, &frames)
+    }
+}
+
+/// Decode a single MIME bundle entry from an `execute_result`/`display_data`
+/// message's `data` dict: binary types (anything starting with `image/`,
+/// plus `application/pdf`) arrive base64-encoded and are decoded here, text types are passed
+/// through as UTF-8 bytes
+#[cfg(feature = "jupyter")]
+fn mime_bundle_to_bytes(mime: &str, value: &serde_json::Value) -> Result<Vec<u8>> {
+    let text = match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(parts) => parts
+            .iter()
+            .filter_map(|p| p.as_str())
+            .collect::<Vec<_>>()
+            .join(""),
+        other => other.to_string(),
+    };
+    if mime.starts_with("image/") || mime == "application/pdf" {
+        base64::engine::general_purpose::STANDARD
+            .decode(text.trim())
+            .map_err(to_internal)
+    } else {
+        Ok(text.into_bytes())
+    }
+}
@@ -43,8 +43,12 @@
 mod agent;
 mod config;
 mod error;
+mod event_handlers;
 mod message;
+mod plan_channel;
+mod project_context;
 mod tool;
+mod wrap;
 
 // Feature-gated modules
 #[cfg(feature = "utils")]
@@ -55,12 +59,57 @@ pub mod utils;
 #[cfg(feature = "templates")]
 pub mod templates;
 
+#[cfg(feature = "config_loader")]
+pub mod config_loader;
+
 #[cfg(feature = "session")]
 pub mod session;
 
+#[cfg(feature = "rag")]
+pub mod rag;
+
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+
+#[cfg(feature = "router")]
+pub mod router;
+
+#[cfg(feature = "orchestrator")]
+pub mod orchestrator;
+
 #[cfg(feature = "tui")]
 pub mod tui;
 
+#[cfg(feature = "webui")]
+pub mod webui;
+
+#[cfg(feature = "repl")]
+pub mod repl;
+
+#[cfg(feature = "transcript")]
+pub mod transcript;
+
+#[cfg(feature = "tpm")]
+pub mod tpm;
+
+#[cfg(feature = "schema")]
+pub mod schema;
+
+#[cfg(feature = "jupyter")]
+pub mod jupyter;
+
+#[cfg(feature = "bench")]
+pub mod bench;
+
+#[cfg(feature = "audit")]
+pub mod audit;
+
+#[cfg(feature = "uv")]
+pub mod uv;
+
+#[cfg(any(feature = "discord", feature = "matrix"))]
+pub mod connectors;
+
 // Prelude for convenient imports
 pub mod prelude;
 
@@ -70,22 +119,50 @@ pub use agent::AgentController;
 pub use agent::AgentExecutionHandle;
 pub use agent::AgentState;
 pub use config::AgentConfig;
+pub use config::AgentProfile;
+pub use config::CommandInput;
 pub use config::McpServerConfig;
+pub use config::ModelProviderKind;
+pub use config::RetryConfig;
 pub use config::SandboxPolicy;
+pub use config::Shell;
 pub use config::ToolConfig;
 pub use error::AgentError;
 pub use error::OutputError;
 pub use error::Result;
+pub use event_handlers::EventHandler;
+pub use event_handlers::EventHandlerContext;
+pub use event_handlers::EventHandlerRegistry;
+pub use event_handlers::EventKind;
+pub use message::ExecutionStatus;
+pub use message::ImageInput;
 pub use message::InputMessage;
 pub use message::OutputData;
 pub use message::OutputMessage;
 pub use message::PlanMessage;
 pub use message::PlanMetadata;
+pub use message::TaskId;
+pub use message::TextChange;
 pub use message::TodoItem;
 pub use message::TodoStatus;
+pub use message::TurnProgress;
+pub use plan_channel::PlanChannelCapacity;
+pub use plan_channel::PlanChannelMetrics;
+pub use project_context::ProjectContext;
+pub use tool::ApprovalHandler;
+pub use tool::DenyAll;
+#[cfg(feature = "rag")]
+pub use tool::ProjectIndexToolHandler;
 pub use tool::Tool;
 pub use tool::ToolCall;
+pub use tool::ToolCallCache;
+pub use tool::ToolChunk;
+pub use tool::ToolRegistry;
 pub use tool::ToolResult;
+pub use tool::execute_tool_calls;
+pub use tool::execute_tool_calls_cached;
+pub use tool::repair_partial_json;
+pub use wrap::WrapMode;
 
 // Re-export commonly used types
 pub use typed_builder::TypedBuilder;
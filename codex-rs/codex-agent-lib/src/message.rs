@@ -35,7 +35,7 @@ impl From<&str> for InputMessage {
 /// Image input for the agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ImageInput {
-    /// Base64 encoded image data
+    /// Base64 encoded image data, as a `data:` URL
     Base64(String),
 
     /// Path to an image file
@@ -45,8 +45,20 @@ pub enum ImageInput {
     Url(String),
 }
 
+impl From<ImageInput> for codex_core::protocol::InputItem {
+    fn from(image: ImageInput) -> Self {
+        match image {
+            ImageInput::Base64(data_url) => Self::Image {
+                image_url: data_url,
+            },
+            ImageInput::Url(url) => Self::Image { image_url: url },
+            ImageInput::Path(path) => Self::LocalImage { path },
+        }
+    }
+}
+
 /// Output message from the agent
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputMessage {
     /// Unique turn ID
     pub turn_id: u64,
@@ -56,7 +68,7 @@ pub struct OutputMessage {
 }
 
 /// Different types of output data from the agent
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum OutputData {
     /// Turn has started
     Start,
@@ -76,37 +88,123 @@ pub enum OutputData {
         arguments: serde_json::Value,
     },
 
+    /// An incremental fragment of a tool call's arguments, emitted as the
+    /// model streams them rather than buffered until the call is complete.
+    /// `id` matches the eventual [`OutputData::ToolStart`]'s call, but
+    /// arrives first and may repeat several times before it; consumers
+    /// accumulate `delta`s per `id` and can best-effort parse the
+    /// in-progress JSON via [`crate::tool::repair_partial_json`] to preview
+    /// the arguments as they're typed.
+    ToolArgsDelta { id: String, delta: String },
+
     /// Tool execution completed
     ToolComplete { tool_name: String, result: String },
 
     /// Tool output streaming (e.g., command output)
     ToolOutput { tool_name: String, output: String },
 
+    /// An incremental chunk of a still-running tool's stdout/stderr,
+    /// analogous to [`OutputData::PrimaryDelta`]: emitted as a child
+    /// process writes to its pipes rather than buffered until it exits, so
+    /// long-running commands (package installs, test runs) can be rendered
+    /// live instead of appearing frozen. Followed by exactly one
+    /// `ToolComplete` carrying the final exit status.
+    ToolOutputDelta { tool_name: String, chunk: String },
+
     /// Reasoning content (for models that support reasoning)
     Reasoning(String),
 
     /// Todo list update
     TodoUpdate { todos: Vec<TodoItem> },
 
+    /// A single MIME-typed output from a [`crate::jupyter`] kernel execution
+    /// (e.g. `execute_result`/`display_data`'s `text/plain`, `image/png`, or
+    /// `text/markdown` entries); `data` is the raw bytes, already decoded
+    /// from base64 for binary MIME types
+    RichOutput { mime: String, data: Vec<u8> },
+
+    /// An error traceback from a [`crate::jupyter`] kernel execution
+    /// (`ename`/`evalue`/`traceback`, the latter still carrying ANSI color
+    /// codes as the kernel emitted them)
+    Traceback {
+        ename: String,
+        evalue: String,
+        traceback: Vec<String>,
+    },
+
+    /// An image the agent wants to show back to the user (a plot, a
+    /// diagram, a screenshot), analogous to [`OutputData::RichOutput`] but
+    /// with an optional caption for surfaces that render it inline
+    Image {
+        /// MIME type of `data`, e.g. `"image/png"` or `"image/jpeg"`
+        mime: String,
+        /// Raw, already-decoded image bytes
+        data: Vec<u8>,
+        /// Optional alt text, shown by text-only consumers and as a
+        /// fallback caption alongside the rendered image
+        alt: Option<String>,
+    },
+
+    /// A file edit applied via Codex's apply-patch tool, expressed as a
+    /// range+content delta rather than the whole before/after file so a
+    /// host editor can splice it directly into an open buffer
+    FileEdit(TextChange),
+
     /// Turn completed successfully
     Completed,
 
     /// An error occurred
     Error(crate::error::OutputError),
+
+    /// Machine-readable progress for the current turn as a whole (plan
+    /// steps completed, tokens used against the context window), so
+    /// editors/CLIs can render a progress bar without re-parsing
+    /// `TodoUpdate`/token-usage events themselves. Distinct from
+    /// [`ExecutionStatus`], which tracks one task within `scheduler`'s
+    /// dependency graph rather than the turn.
+    Progress(TurnProgress),
 }
 
+/// A single range+content edit to a file, analogous to how
+/// collaborative-editing clients represent changes: `start`/`end` are a byte
+/// range in the file's prior contents, replaced in full by `content`. An
+/// empty `content` is a deletion of that range; `start == end` is a pure
+/// insertion at that offset.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextChange {
+    /// Path of the edited file
+    pub path: String,
+    /// Start byte offset of the replaced range in the file's prior contents
+    pub start: usize,
+    /// End byte offset (exclusive) of the replaced range
+    pub end: usize,
+    /// Replacement content for the range
+    pub content: String,
+}
+
+/// Identifier for a single task within a [`PlanMessage`]'s todo list
+pub type TaskId = String;
+
 /// Represents a todo item
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TodoItem {
+    /// Stable identifier for this task, used by `depends_on` to reference it
+    #[serde(default)]
+    pub id: TaskId,
+
     /// The task description
     pub content: String,
 
     /// The task status
     pub status: TodoStatus,
+
+    /// Tasks that must reach `Completed` before this one becomes ready to run
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub depends_on: Vec<TaskId>,
 }
 
 /// Todo item status
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum TodoStatus {
     /// Task is pending
@@ -123,7 +221,7 @@ pub enum TodoStatus {
 }
 
 /// Plan update message sent through the dedicated plan channel
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlanMessage {
     /// The updated todo list
     pub todos: Vec<TodoItem>,
@@ -133,7 +231,7 @@ pub struct PlanMessage {
 }
 
 /// Metadata about a plan update
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlanMetadata {
     /// The turn ID when this plan was updated
     pub turn_id: u64,
@@ -142,6 +240,58 @@ pub struct PlanMetadata {
     pub description: Option<String>,
 }
 
+/// Progress of a single task within a scheduled plan, emitted alongside
+/// [`PlanMessage`] updates so consumers can render progress bars
+#[derive(Debug, Clone)]
+pub enum ExecutionStatus {
+    /// The task started running
+    InProgress {
+        /// ID of the task that started running
+        task: TaskId,
+        /// How many tasks have reached a terminal state so far
+        current: usize,
+        /// Total number of tasks in the plan
+        total: usize,
+        /// Unit label for display (e.g. "tasks", "files")
+        unit: String,
+    },
+
+    /// The task completed successfully
+    Complete {
+        /// ID of the task that completed
+        task: TaskId,
+    },
+
+    /// The task failed and will not be retried
+    Failed {
+        /// ID of the task that failed
+        task: TaskId,
+        /// Human-readable failure reason
+        reason: String,
+    },
+}
+
+/// A uniform progress signal for a turn, derived in `process_events` from
+/// plan updates and token-usage events so consumers get one shape to render
+/// regardless of which underlying event produced it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TurnProgress {
+    /// The turn has advanced `current` out of `total` `unit`s (e.g. `"steps"`
+    /// completed out of a plan's total, or `"tokens"` used out of the
+    /// model's context window)
+    InProgress {
+        current: u64,
+        total: u64,
+        unit: String,
+    },
+
+    /// The turn reached a successful terminal state
+    Complete,
+
+    /// The turn failed; carries a human-readable reason
+    Failed(String),
+}
+
 impl OutputData {
     /// Check if this is a terminal state
     pub fn is_terminal(&self) -> bool {
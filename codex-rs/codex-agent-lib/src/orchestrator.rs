@@ -0,0 +1,243 @@
+//! Dependency-graph orchestrator for running multiple agents as a DAG
+//!
+//! Unlike [`crate::scheduler::DagScheduler`], which runs the todos of a
+//! single agent's plan, `AgentOrchestrator` runs a set of *named agents*
+//! where one agent's prompt consumes another's final answer - e.g. a
+//! "researcher" node whose output is substituted into a "writer" node's
+//! prompt template. It is modeled after a build executor: targets are
+//! declared up front with their dependencies, a topological check rejects
+//! cycles before anything runs, and every node whose dependencies are
+//! satisfied is dispatched concurrently via `join_all`.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+
+use futures::future::join_all;
+use tokio::sync::mpsc;
+
+use crate::Agent;
+use crate::error::AgentError;
+use crate::error::Result;
+
+/// One node in the orchestration graph
+struct AgentNode {
+    /// Prompt template; `{{name}}` is replaced with the collected response
+    /// of each dependency named in `depends_on` before the node is launched
+    prompt_template: String,
+    /// Names of nodes that must complete before this one becomes ready
+    depends_on: Vec<String>,
+    /// The agent that runs this node's prompt
+    agent: Agent,
+    /// Collected response, populated once the node completes
+    output: Option<String>,
+}
+
+/// Progress update for one node in an [`AgentOrchestrator`] run
+#[derive(Debug, Clone)]
+pub struct ExecutionStatusMsg {
+    /// Name of the node this status concerns
+    pub name: String,
+    /// The node's current status
+    pub status: NodeStatus,
+}
+
+/// Status of a single orchestrated node
+#[derive(Debug, Clone)]
+pub enum NodeStatus {
+    /// The node's dependencies are all satisfied and it has been dispatched
+    Running,
+    /// The node finished and produced a response
+    Completed(String),
+    /// The node's `Agent::query` call returned an error
+    Failed(String),
+}
+
+/// Builder and runtime for a dependency graph of named agents
+pub struct AgentOrchestrator {
+    nodes: BTreeMap<String, AgentNode>,
+}
+
+impl AgentOrchestrator {
+    /// Create an empty orchestrator
+    pub fn new() -> Self {
+        Self {
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    /// Register a node under `name`, with `prompt_template` run once every
+    /// name in `depends_on` has completed
+    pub fn add_node(
+        mut self,
+        name: impl Into<String>,
+        prompt_template: impl Into<String>,
+        depends_on: impl IntoIterator<Item = String>,
+        agent: Agent,
+    ) -> Self {
+        self.nodes.insert(
+            name.into(),
+            AgentNode {
+                prompt_template: prompt_template.into(),
+                depends_on: depends_on.into_iter().collect(),
+                agent,
+                output: None,
+            },
+        );
+        self
+    }
+
+    /// Run every node to completion, reporting progress on `status_tx`
+    ///
+    /// Returns the collected responses keyed by node name. Fails the whole
+    /// run with [`AgentError::ConfigError`] if a dependency cycle is
+    /// detected during the initial topological check, or with
+    /// [`AgentError::InternalError`] naming the failed node if any node's
+    /// `Agent::query` returns an error.
+    pub async fn run(
+        mut self,
+        status_tx: mpsc::Sender<ExecutionStatusMsg>,
+    ) -> Result<BTreeMap<String, String>> {
+        self.check_acyclic()?;
+
+        let mut completed: HashSet<String> = HashSet::new();
+
+        while completed.len() < self.nodes.len() {
+            let ready: Vec<String> = self
+                .nodes
+                .iter()
+                .filter(|(name, node)| {
+                    !completed.contains(*name)
+                        && node.depends_on.iter().all(|dep| completed.contains(dep))
+                })
+                .map(|(name, _)| name.clone())
+                .collect();
+
+            // `check_acyclic` guarantees this can't happen, but a defensive
+            // check keeps the loop from spinning forever if that invariant
+            // is ever violated.
+            if ready.is_empty() {
+                return Err(AgentError::InternalError(
+                    "orchestrator: no ready nodes but the graph is not fully completed".into(),
+                ));
+            }
+
+            for name in &ready {
+                let _ = status_tx
+                    .send(ExecutionStatusMsg {
+                        name: name.clone(),
+                        status: NodeStatus::Running,
+                    })
+                    .await;
+            }
+
+            let futures = ready.iter().map(|name| {
+                let node = self.nodes.get(name).expect("ready node exists");
+                let prompt = Self::substitute(&node.prompt_template, &self.nodes);
+                let mut agent = node.agent.clone();
+                let name = name.clone();
+                async move { (name, agent.query(&prompt).await) }
+            });
+
+            for (name, result) in join_all(futures).await {
+                match result {
+                    Ok(response) => {
+                        let _ = status_tx
+                            .send(ExecutionStatusMsg {
+                                name: name.clone(),
+                                status: NodeStatus::Completed(response.clone()),
+                            })
+                            .await;
+                        if let Some(node) = self.nodes.get_mut(&name) {
+                            node.output = Some(response);
+                        }
+                        completed.insert(name);
+                    }
+                    Err(e) => {
+                        let _ = status_tx
+                            .send(ExecutionStatusMsg {
+                                name: name.clone(),
+                                status: NodeStatus::Failed(e.to_string()),
+                            })
+                            .await;
+                        return Err(AgentError::InternalError(format!(
+                            "orchestrator: node '{name}' failed: {e}"
+                        )));
+                    }
+                }
+            }
+        }
+
+        Ok(self
+            .nodes
+            .into_iter()
+            .map(|(name, node)| (name, node.output.unwrap_or_default()))
+            .collect())
+    }
+
+    /// Substitute every `{{dep}}` placeholder in `template` with `dep`'s
+    /// collected output, leaving unresolved placeholders (a dependency that
+    /// hasn't run yet) untouched
+    fn substitute(template: &str, nodes: &BTreeMap<String, AgentNode>) -> String {
+        let mut prompt = template.to_string();
+        for (name, node) in nodes {
+            if let Some(output) = &node.output {
+                prompt = prompt.replace(&format!("{{{{{name}}}}}"), output);
+            }
+        }
+        prompt
+    }
+
+    /// Reject the graph if it contains a cycle or references an unknown
+    /// dependency, via Kahn's algorithm over the declared `depends_on` edges
+    fn check_acyclic(&self) -> Result<()> {
+        for node in self.nodes.values() {
+            for dep in &node.depends_on {
+                if !self.nodes.contains_key(dep) {
+                    return Err(AgentError::ConfigError(format!(
+                        "orchestrator: unknown dependency '{dep}'"
+                    )));
+                }
+            }
+        }
+
+        let mut in_degree: BTreeMap<&str, usize> = self
+            .nodes
+            .iter()
+            .map(|(name, node)| (name.as_str(), node.depends_on.len()))
+            .collect();
+
+        let mut queue: Vec<&str> = in_degree
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(name, _)| *name)
+            .collect();
+        let mut visited = 0usize;
+
+        while let Some(name) = queue.pop() {
+            visited += 1;
+            for (other_name, other_node) in &self.nodes {
+                if other_node.depends_on.iter().any(|dep| dep == name) {
+                    let degree = in_degree.get_mut(other_name.as_str()).expect("tracked node");
+                    *degree -= 1;
+                    if *degree == 0 {
+                        queue.push(other_name.as_str());
+                    }
+                }
+            }
+        }
+
+        if visited != self.nodes.len() {
+            return Err(AgentError::ConfigError(
+                "orchestrator: dependency cycle detected among agent nodes".into(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for AgentOrchestrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,205 @@
+//! Internal buffering and throughput metrics for plan/todo updates
+//!
+//! The event loop used to call `plan_tx.send(..).await` directly and
+//! discard the result with `let _ = ..`: a slow consumer blocked turn
+//! processing with no visibility into it, and a closed receiver just
+//! vanished the update. [`PlanChannel`] decouples production from
+//! consumption with its own internal buffer (sized per
+//! [`PlanChannelCapacity`]) drained by a dedicated forwarding task, so a
+//! full buffer surfaces as a reportable [`AgentError`] instead of blocking
+//! or being swallowed, and [`PlanChannelMetrics`] exposes what happened.
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+
+use tokio::sync::mpsc;
+
+use crate::agent::AgentController;
+use crate::error::AgentError;
+use crate::error::Result;
+use crate::message::PlanMessage;
+
+/// Default capacity for a [`PlanChannelCapacity::Bounded`] buffer
+pub const DEFAULT_PLAN_CHANNEL_CAPACITY: usize = 64;
+
+/// Capacity of a [`PlanChannel`]'s internal buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "schema",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema)
+)]
+pub enum PlanChannelCapacity {
+    /// At most `n` unconsumed plan updates may be buffered; a `send`
+    /// beyond that is dropped (counted in [`PlanChannelMetrics::dropped`])
+    /// rather than blocking the event loop on a slow consumer
+    Bounded(usize),
+    /// Buffer every update regardless of how far behind the consumer falls
+    Unbounded,
+}
+
+impl Default for PlanChannelCapacity {
+    fn default() -> Self {
+        Self::Bounded(DEFAULT_PLAN_CHANNEL_CAPACITY)
+    }
+}
+
+/// Throughput counters for a [`PlanChannel`], safe to read from another
+/// task (e.g. a metrics exporter) while updates are in flight
+#[derive(Debug, Default)]
+pub struct PlanChannelMetrics {
+    sent: AtomicU64,
+    dropped: AtomicU64,
+    queue_depth: AtomicUsize,
+    total_latency_micros: AtomicU64,
+}
+
+impl PlanChannelMetrics {
+    /// Plan updates successfully forwarded to the consumer
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Plan updates dropped because the buffer was full; see
+    /// [`PlanChannelCapacity::Bounded`]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Plan updates currently buffered, awaiting the forwarding task
+    pub fn queue_depth(&self) -> usize {
+        self.queue_depth.load(Ordering::Relaxed)
+    }
+
+    /// Average time between a plan update entering the buffer and being
+    /// forwarded to the consumer
+    pub fn average_latency(&self) -> Duration {
+        let sent = self.sent();
+        if sent == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_micros(self.total_latency_micros.load(Ordering::Relaxed) / sent)
+    }
+}
+
+enum BufferSender {
+    Bounded(mpsc::Sender<(PlanMessage, Instant)>),
+    Unbounded(mpsc::UnboundedSender<(PlanMessage, Instant)>),
+}
+
+/// Buffers [`PlanMessage`]s between the event loop and the `plan_tx`
+/// channel [`crate::Agent::execute`]'s caller supplies
+pub(crate) struct PlanChannel {
+    buffer_tx: BufferSender,
+    metrics: Arc<PlanChannelMetrics>,
+}
+
+impl PlanChannel {
+    /// Spawn the forwarding task and return a handle producers send
+    /// through; `downstream` is the caller-supplied `plan_tx`, `controller`
+    /// is used to keep fanning buffered updates out to
+    /// [`AgentController::subscribe_plan`] the same way the event loop did
+    /// before this buffer existed
+    pub(crate) fn spawn(
+        capacity: PlanChannelCapacity,
+        downstream: mpsc::Sender<PlanMessage>,
+        controller: AgentController,
+    ) -> Self {
+        let metrics = Arc::new(PlanChannelMetrics::default());
+
+        let buffer_tx = match capacity {
+            PlanChannelCapacity::Bounded(capacity) => {
+                let (tx, rx) = mpsc::channel(capacity.max(1));
+                tokio::spawn(Self::forward_bounded(
+                    rx,
+                    downstream,
+                    controller,
+                    metrics.clone(),
+                ));
+                BufferSender::Bounded(tx)
+            }
+            PlanChannelCapacity::Unbounded => {
+                let (tx, rx) = mpsc::unbounded_channel();
+                tokio::spawn(Self::forward_unbounded(
+                    rx,
+                    downstream,
+                    controller,
+                    metrics.clone(),
+                ));
+                BufferSender::Unbounded(tx)
+            }
+        };
+
+        Self { buffer_tx, metrics }
+    }
+
+    /// Buffer `message`, returning a recoverable [`AgentError`] instead of
+    /// blocking or silently discarding it if the buffer is full (bounded)
+    /// or the forwarding task has stopped
+    pub(crate) async fn send(&self, message: PlanMessage) -> Result<()> {
+        let queued_at = Instant::now();
+        match &self.buffer_tx {
+            BufferSender::Bounded(tx) => tx.try_send((message, queued_at)).map_err(|err| {
+                match err {
+                    mpsc::error::TrySendError::Full(_) => {
+                        self.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                    mpsc::error::TrySendError::Closed(_) => {}
+                }
+                AgentError::PlanChannelError(err.to_string())
+            })?,
+            BufferSender::Unbounded(tx) => tx
+                .send((message, queued_at))
+                .map_err(|err| AgentError::PlanChannelError(err.to_string()))?,
+        }
+        self.metrics.queue_depth.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Snapshot handle for this channel's throughput counters
+    pub(crate) fn metrics(&self) -> Arc<PlanChannelMetrics> {
+        self.metrics.clone()
+    }
+
+    async fn forward_bounded(
+        mut rx: mpsc::Receiver<(PlanMessage, Instant)>,
+        downstream: mpsc::Sender<PlanMessage>,
+        controller: AgentController,
+        metrics: Arc<PlanChannelMetrics>,
+    ) {
+        while let Some((message, queued_at)) = rx.recv().await {
+            Self::forward_one(message, queued_at, &downstream, &controller, &metrics).await;
+        }
+    }
+
+    async fn forward_unbounded(
+        mut rx: mpsc::UnboundedReceiver<(PlanMessage, Instant)>,
+        downstream: mpsc::Sender<PlanMessage>,
+        controller: AgentController,
+        metrics: Arc<PlanChannelMetrics>,
+    ) {
+        while let Some((message, queued_at)) = rx.recv().await {
+            Self::forward_one(message, queued_at, &downstream, &controller, &metrics).await;
+        }
+    }
+
+    async fn forward_one(
+        message: PlanMessage,
+        queued_at: Instant,
+        downstream: &mpsc::Sender<PlanMessage>,
+        controller: &AgentController,
+        metrics: &PlanChannelMetrics,
+    ) {
+        metrics.queue_depth.fetch_sub(1, Ordering::Relaxed);
+        controller.publish_plan_broadcast(&message);
+        if downstream.send(message).await.is_ok() {
+            metrics.sent.fetch_add(1, Ordering::Relaxed);
+            metrics
+                .total_latency_micros
+                .fetch_add(queued_at.elapsed().as_micros() as u64, Ordering::Relaxed);
+        }
+    }
+}
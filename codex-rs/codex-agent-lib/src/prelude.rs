@@ -8,37 +8,122 @@ pub use crate::AgentState;
 
 // Configuration
 pub use crate::AgentConfig;
+pub use crate::AgentProfile;
+pub use crate::CommandInput;
 pub use crate::McpServerConfig;
+pub use crate::ModelProviderKind;
+pub use crate::RetryConfig;
 pub use crate::SandboxPolicy;
+pub use crate::Shell;
+pub use crate::ProjectContext;
+pub use crate::ApprovalHandler;
+pub use crate::DenyAll;
+pub use crate::ToolCallCache;
 pub use crate::ToolConfig;
+pub use crate::ToolRegistry;
+pub use crate::execute_tool_calls;
+pub use crate::execute_tool_calls_cached;
+
+// TPM-sealed secret storage (if enabled)
+#[cfg(feature = "tpm")]
+pub use crate::tpm::SealPolicy;
+#[cfg(feature = "tpm")]
+pub use crate::tpm::SealedSecret;
+#[cfg(feature = "tpm")]
+pub use crate::tpm::SecretLocation;
+#[cfg(feature = "tpm")]
+pub use crate::tpm::SecretStore;
+#[cfg(feature = "tpm")]
+pub use crate::tpm::TpmDevice;
 
 // Messages
+pub use crate::ExecutionStatus;
+pub use crate::ImageInput;
 pub use crate::InputMessage;
 pub use crate::OutputData;
 pub use crate::OutputMessage;
 pub use crate::PlanMessage;
+pub use crate::TaskId;
+pub use crate::PlanChannelCapacity;
+pub use crate::PlanChannelMetrics;
+pub use crate::TextChange;
 pub use crate::TodoItem;
 pub use crate::TodoStatus;
+pub use crate::TurnProgress;
 
 // Error handling
 pub use crate::AgentError;
 pub use crate::OutputError;
 pub use crate::Result;
 
+// Pluggable event-handler registry
+pub use crate::EventHandler;
+pub use crate::EventHandlerContext;
+pub use crate::EventHandlerRegistry;
+pub use crate::EventKind;
+
 // Templates (if enabled)
 #[cfg(feature = "templates")]
+pub use crate::templates::TemplateFile;
+#[cfg(feature = "templates")]
+pub use crate::templates::TemplateRegistry;
+#[cfg(feature = "templates")]
 pub use crate::templates::templates;
 
+// Layered, multi-format config loading (if enabled)
+#[cfg(feature = "config_loader")]
+pub use crate::config_loader::ConfigLayer;
+#[cfg(feature = "config_loader")]
+pub use crate::config_loader::ConfigLoader;
+
 // Processing (if enabled)
 #[cfg(feature = "utils")]
 pub use crate::processing::MessageProcessor;
 #[cfg(feature = "utils")]
 pub use crate::processing::MessageProcessorBuilder;
+#[cfg(feature = "utils")]
+pub use crate::processing::ThrottlePolicy;
 
 // Utils (if enabled)
 #[cfg(feature = "utils")]
 pub use crate::utils::output;
 
+// Dependency-graph scheduler (if enabled)
+#[cfg(feature = "scheduler")]
+pub use crate::scheduler::DagScheduler;
+
+// Multi-agent router (if enabled)
+#[cfg(feature = "router")]
+pub use crate::router::AgentRouter;
+#[cfg(feature = "router")]
+pub use crate::router::AgentRouterHandle;
+#[cfg(feature = "router")]
+pub use crate::router::RoutedInput;
+#[cfg(feature = "router")]
+pub use crate::router::RoutedOutput;
+
+// Dependency-graph agent orchestrator (if enabled)
+#[cfg(feature = "orchestrator")]
+pub use crate::orchestrator::AgentOrchestrator;
+#[cfg(feature = "orchestrator")]
+pub use crate::orchestrator::ExecutionStatusMsg;
+#[cfg(feature = "orchestrator")]
+pub use crate::orchestrator::NodeStatus;
+
+// Retrieval-augmented context injection (if enabled)
+#[cfg(feature = "rag")]
+pub use crate::rag::DocumentChunk;
+#[cfg(feature = "rag")]
+pub use crate::rag::Embedder;
+#[cfg(feature = "rag")]
+pub use crate::rag::RagConfig;
+#[cfg(feature = "rag")]
+pub use crate::rag::RagIndex;
+#[cfg(feature = "rag")]
+pub use crate::rag::Reranker;
+#[cfg(feature = "rag")]
+pub use crate::ProjectIndexToolHandler;
+
 // Session management (if enabled)
 #[cfg(feature = "session")]
 pub use crate::session::AgentSession;
@@ -55,6 +140,125 @@ pub use crate::tui::AgentTui;
 #[cfg(feature = "tui")]
 pub use crate::tui::AppState;
 #[cfg(feature = "tui")]
+pub use crate::tui::BusyBehavior;
+#[cfg(feature = "tui")]
 pub use crate::tui::Message;
 #[cfg(feature = "tui")]
-pub use crate::tui::MessageRole;
\ No newline at end of file
+pub use crate::tui::MessageRole;
+#[cfg(feature = "tui")]
+pub use crate::tui::JsonFileSessionStore;
+#[cfg(feature = "tui")]
+pub use crate::tui::SessionSnapshot;
+#[cfg(feature = "tui")]
+pub use crate::tui::SessionStore;
+#[cfg(feature = "tui")]
+pub use crate::tui::TestHarness;
+
+// Browser-based web UI (if enabled)
+#[cfg(feature = "webui")]
+pub use crate::webui::AgentWebUi;
+#[cfg(feature = "webui")]
+pub use crate::webui::AgentWebUiHandle;
+#[cfg(feature = "webui")]
+pub use crate::webui::BrowserSender;
+#[cfg(feature = "webui")]
+pub use crate::webui::WebEvent;
+#[cfg(feature = "webui")]
+pub use crate::webui::WebTransport;
+#[cfg(feature = "webui")]
+pub use crate::webui::WebUiMessage;
+#[cfg(feature = "webui")]
+pub use crate::webui::WebUiState;
+
+// REPL (if enabled)
+#[cfg(feature = "repl")]
+pub use crate::repl::Repl;
+#[cfg(feature = "repl")]
+pub use crate::repl::run_repl;
+
+// Transcript persistence (if enabled)
+#[cfg(feature = "transcript")]
+pub use crate::transcript::RotationPolicy;
+#[cfg(feature = "transcript")]
+pub use crate::transcript::TranscriptConfig;
+#[cfg(feature = "transcript")]
+pub use crate::transcript::TranscriptReader;
+#[cfg(feature = "transcript")]
+pub use crate::transcript::TranscriptRecord;
+#[cfg(feature = "transcript")]
+pub use crate::transcript::TranscriptWriter;
+
+// Discord connector (if enabled)
+#[cfg(feature = "discord")]
+pub use crate::connectors::discord::Context as DiscordContext;
+#[cfg(feature = "discord")]
+pub use crate::connectors::discord::DiscordBot;
+#[cfg(feature = "discord")]
+pub use crate::connectors::discord::DiscordMessage;
+#[cfg(feature = "discord")]
+pub use crate::connectors::discord::EventHandler as DiscordEventHandler;
+#[cfg(feature = "discord")]
+pub use crate::connectors::discord::GatewayTransport as DiscordGatewayTransport;
+
+// Config JSON Schema (if enabled): exposes `config::schema()` plus the
+// dedicated serde-aligned mirror types it's built from
+#[cfg(feature = "schema")]
+pub use crate::config;
+#[cfg(feature = "schema")]
+pub use crate::schema::AgentConfigSchema;
+#[cfg(feature = "schema")]
+pub use crate::schema::ToolConfigSchema;
+
+// uv environment manager (if enabled)
+#[cfg(feature = "uv")]
+pub use crate::uv::UvConfig;
+#[cfg(feature = "uv")]
+pub use crate::uv::UvEnvironment;
+
+// Jupyter kernel backend (if enabled)
+#[cfg(feature = "jupyter")]
+pub use crate::jupyter::ConnectionInfo;
+#[cfg(feature = "jupyter")]
+pub use crate::jupyter::ExecuteSummary;
+#[cfg(feature = "jupyter")]
+pub use crate::jupyter::KernelSession;
+#[cfg(feature = "jupyter")]
+pub use crate::jupyter::KernelTransport;
+
+// Workload-file benchmark harness (if enabled)
+#[cfg(feature = "bench")]
+pub use crate::bench::BenchmarkReport;
+#[cfg(feature = "bench")]
+pub use crate::bench::BenchmarkRunner;
+#[cfg(feature = "bench")]
+pub use crate::bench::ResultsSink;
+#[cfg(feature = "bench")]
+pub use crate::bench::ScenarioResult;
+#[cfg(feature = "bench")]
+pub use crate::bench::ScenarioSpec;
+#[cfg(feature = "bench")]
+pub use crate::bench::TurnMetrics;
+#[cfg(feature = "bench")]
+pub use crate::bench::WorkloadFile;
+
+// Structured tool-call audit log (if enabled)
+#[cfg(feature = "audit")]
+pub use crate::audit::AuditEvent;
+#[cfg(feature = "audit")]
+pub use crate::audit::AuditRingBuffer;
+#[cfg(feature = "audit")]
+pub use crate::audit::AuditSink;
+#[cfg(feature = "audit")]
+pub use crate::audit::AuditWriter;
+
+// Matrix connector (if enabled)
+#[cfg(feature = "matrix")]
+pub use crate::connectors::matrix::MatrixConnector;
+#[cfg(feature = "matrix")]
+pub use crate::connectors::matrix::MatrixSession;
+#[cfg(feature = "matrix")]
+pub use crate::connectors::matrix::MatrixTransport;
+#[cfg(feature = "matrix")]
+pub use crate::connectors::matrix::RoomCrypto;
+#[cfg(feature = "matrix")]
+pub use crate::connectors::matrix::RoomMessage;
\ No newline at end of file
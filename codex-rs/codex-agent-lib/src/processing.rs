@@ -1,5 +1,8 @@
 //! Message processing pipeline for filtering, transforming, and aggregating messages
 
+use std::time::Duration;
+use std::time::Instant;
+
 use crate::message::OutputData;
 use crate::message::OutputMessage;
 
@@ -19,9 +22,23 @@ pub trait MessageTransformer: Send + Sync {
 pub trait MessageAggregator: Send + Sync {
     /// Process a message and potentially return an aggregated result
     fn process(&mut self, msg: OutputMessage) -> Option<OutputMessage>;
-    
+
     /// Flush any remaining messages
     fn flush(&mut self) -> Vec<OutputMessage>;
+
+    /// Serialize this aggregator's in-flight state (e.g. a partially
+    /// buffered delta) for persistence across a restart
+    ///
+    /// The default implementation persists nothing; stateless aggregators
+    /// don't need to override it.
+    fn save_state(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Restore in-flight state previously produced by `save_state`
+    ///
+    /// The default implementation is a no-op.
+    fn load_state(&mut self, _state: serde_json::Value) {}
 }
 
 /// Message processor that applies filters, transformers, and aggregators
@@ -82,14 +99,48 @@ impl MessageProcessor {
         }
         results
     }
+
+    /// Snapshot every aggregator's in-flight state, in stage order, so it
+    /// can be persisted and later restored via `load_aggregator_states`
+    pub fn save_aggregator_states(&self) -> Vec<serde_json::Value> {
+        self.aggregators.iter().map(|a| a.save_state()).collect()
+    }
+
+    /// Restore aggregator state previously produced by
+    /// `save_aggregator_states`, matched back up by stage order
+    ///
+    /// Extra or missing entries (the pipeline's shape changed since the
+    /// snapshot was taken) are ignored rather than treated as an error.
+    pub fn load_aggregator_states(&mut self, states: &[serde_json::Value]) {
+        for (aggregator, state) in self.aggregators.iter_mut().zip(states) {
+            aggregator.load_state(state.clone());
+        }
+    }
 }
 
 /// Builder for MessageProcessor
-#[derive(Default)]
 pub struct MessageProcessorBuilder {
     filters: Vec<Box<dyn MessageFilter>>,
     transformers: Vec<Box<dyn MessageTransformer>>,
     aggregators: Vec<Box<dyn MessageAggregator>>,
+    backlog: usize,
+    capacity: usize,
+    timeout_ms: u64,
+    throttle_ms: u64,
+}
+
+impl Default for MessageProcessorBuilder {
+    fn default() -> Self {
+        Self {
+            filters: Vec::new(),
+            transformers: Vec::new(),
+            aggregators: Vec::new(),
+            backlog: 256,
+            capacity: 100,
+            timeout_ms: 1000,
+            throttle_ms: 100,
+        }
+    }
 }
 
 impl MessageProcessorBuilder {
@@ -151,7 +202,83 @@ impl MessageProcessorBuilder {
         self.aggregators.push(Box::new(DeltaAggregator::new()));
         self
     }
-    
+
+    /// Set the maximum buffered content (in bytes) a throttle stage holds
+    /// before applying its drop-or-block policy
+    pub fn backlog(mut self, backlog: usize) -> Self {
+        self.backlog = backlog;
+        self
+    }
+
+    /// Record a token-bucket capacity (max burst of immediate flushes); no
+    /// stage reads this value, since [`Self::throttle`] and
+    /// [`Self::throttle_with_policy`] take their own `capacity` argument and
+    /// overwrite it
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Record a force-flush timeout, in milliseconds; no stage reads this
+    /// value, since [`Self::throttle`] and [`Self::throttle_with_policy`]
+    /// take their own `timeout` argument and overwrite it
+    pub fn timeout_ms(mut self, timeout_ms: u64) -> Self {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    /// Record a coalescing window, in milliseconds; no stage reads this
+    /// value, since [`Self::throttle`] and [`Self::throttle_with_policy`]
+    /// take their own `interval` argument and overwrite it
+    pub fn throttle_ms(mut self, throttle_ms: u64) -> Self {
+        self.throttle_ms = throttle_ms;
+        self
+    }
+
+    /// Add a throttling stage: like `aggregate_deltas`, but time-bounded and
+    /// backpressured. `PrimaryDelta` messages arriving within `interval` are
+    /// coalesced into a single `Primary`, a token bucket of `capacity`
+    /// permits governs how many flushes can happen back-to-back, and once
+    /// the buffered backlog would exceed the `backlog` knob (as set by
+    /// [`Self::backlog`] before this call) the stage force-flushes early
+    /// ([`ThrottlePolicy::Block`]).
+    pub fn throttle(mut self, capacity: usize, interval: Duration, timeout: Duration) -> Self {
+        self.capacity = capacity;
+        self.throttle_ms = interval.as_millis() as u64;
+        self.timeout_ms = timeout.as_millis() as u64;
+        self.aggregators.push(Box::new(ThrottleAggregator::new(
+            capacity,
+            interval,
+            timeout,
+            self.backlog,
+            ThrottlePolicy::default(),
+        )));
+        self
+    }
+
+    /// Like [`Self::throttle`], but applies `policy` once the backlog is
+    /// full ([`ThrottlePolicy::Drop`] drops excess deltas instead of
+    /// force-flushing)
+    pub fn throttle_with_policy(
+        mut self,
+        capacity: usize,
+        interval: Duration,
+        timeout: Duration,
+        policy: ThrottlePolicy,
+    ) -> Self {
+        self.capacity = capacity;
+        self.throttle_ms = interval.as_millis() as u64;
+        self.timeout_ms = timeout.as_millis() as u64;
+        self.aggregators.push(Box::new(ThrottleAggregator::new(
+            capacity,
+            interval,
+            timeout,
+            self.backlog,
+            policy,
+        )));
+        self
+    }
+
     /// Remove duplicate consecutive messages
     pub fn remove_duplicates(mut self) -> Self {
         self.aggregators.push(Box::new(DuplicateRemover::new()));
@@ -176,7 +303,9 @@ impl MessageFilter for ToolOutputFilter {
     fn should_keep(&self, msg: &OutputMessage) -> bool {
         !matches!(
             msg.data,
-            OutputData::ToolOutput { .. } | OutputData::ToolStart { .. }
+            OutputData::ToolOutput { .. }
+                | OutputData::ToolOutputDelta { .. }
+                | OutputData::ToolStart { .. }
         )
     }
 }
@@ -200,6 +329,7 @@ impl MessageFilter for TypeFilter {
             OutputData::PrimaryDelta(_) => "delta",
             OutputData::ToolStart { .. } => "tool_start",
             OutputData::ToolOutput { .. } => "tool_output",
+            OutputData::ToolOutputDelta { .. } => "tool_output_delta",
             OutputData::ToolComplete { .. } => "tool_complete",
             OutputData::Completed => "completed",
             OutputData::Error(_) => "error",
@@ -228,9 +358,12 @@ impl MessageTransformer for AnsiStripper {
             OutputData::ToolOutput { output, .. } => {
                 *output = clean_ansi(output);
             }
+            OutputData::ToolOutputDelta { chunk, .. } => {
+                *chunk = clean_ansi(chunk);
+            }
             _ => {}
         }
-        
+
         msg
     }
 }
@@ -248,6 +381,9 @@ impl MessageTransformer for LineTruncator {
             OutputData::ToolOutput { output, .. } => {
                 *output = truncate_lines(output, self.max_length);
             }
+            OutputData::ToolOutputDelta { chunk, .. } => {
+                *chunk = truncate_lines(chunk, self.max_length);
+            }
             _ => {}
         }
         
@@ -313,6 +449,16 @@ impl MessageAggregator for DeltaAggregator {
             Vec::new()
         }
     }
+
+    fn save_state(&self) -> serde_json::Value {
+        serde_json::json!({ "buffer": self.buffer })
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) {
+        if let Some(buffer) = state.get("buffer").and_then(|v| v.as_str()) {
+            self.buffer = buffer.to_string();
+        }
+    }
 }
 
 struct DuplicateRemover {
@@ -337,7 +483,7 @@ impl DuplicateRemover {
 impl MessageAggregator for DuplicateRemover {
     fn process(&mut self, msg: OutputMessage) -> Option<OutputMessage> {
         let content = Self::message_content(&msg);
-        
+
         if let Some(ref current) = content {
             if self.last_message.as_ref() == Some(current) {
                 // Duplicate, skip it
@@ -345,11 +491,205 @@ impl MessageAggregator for DuplicateRemover {
             }
             self.last_message = Some(current.clone());
         }
-        
+
         Some(msg)
     }
-    
+
     fn flush(&mut self) -> Vec<OutputMessage> {
         Vec::new()
     }
+
+    fn save_state(&self) -> serde_json::Value {
+        serde_json::json!({ "last_message": self.last_message })
+    }
+
+    fn load_state(&mut self, state: serde_json::Value) {
+        self.last_message = state
+            .get("last_message")
+            .and_then(|v| v.as_str().map(str::to_string));
+    }
+}
+
+/// Policy applied when a throttle stage's backlog is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThrottlePolicy {
+    /// Force an early flush of the buffered content to make room, keeping
+    /// the new delta
+    #[default]
+    Block,
+
+    /// Drop the incoming delta, keeping whatever is already buffered
+    Drop,
+}
+
+/// Time-bounded, backpressured delta coalescer
+///
+/// Behaves like [`DeltaAggregator`], but instead of waiting for a non-delta
+/// message to flush, it flushes as soon as `interval` has elapsed since the
+/// current buffer started (rate-limited by a token bucket of `capacity`
+/// permits), caps how much it will buffer at `backlog` bytes (a single
+/// delta larger than that is still buffered whole), and forces a
+/// flush once a buffer has been held back for `timeout` regardless of the
+/// token bucket, so an exhausted bucket can delay a burst but never stall
+/// it indefinitely.
+struct ThrottleAggregator {
+    capacity: usize,
+    interval: Duration,
+    timeout: Duration,
+    backlog: usize,
+    policy: ThrottlePolicy,
+    tokens: usize,
+    last_refill: Instant,
+    window_start: Option<Instant>,
+    buffer: String,
+}
+
+impl ThrottleAggregator {
+    fn new(
+        capacity: usize,
+        interval: Duration,
+        timeout: Duration,
+        backlog: usize,
+        policy: ThrottlePolicy,
+    ) -> Self {
+        Self {
+            capacity,
+            interval,
+            timeout,
+            backlog,
+            policy,
+            tokens: capacity,
+            last_refill: Instant::now(),
+            window_start: None,
+            buffer: String::new(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let interval_nanos = self.interval.as_nanos().max(1);
+        let elapsed = self.last_refill.elapsed().as_nanos();
+        let gained = (elapsed / interval_nanos) as usize;
+        if gained > 0 {
+            self.tokens = (self.tokens + gained).min(self.capacity);
+            self.last_refill = Instant::now();
+        }
+    }
+
+    fn take_buffer(&mut self, turn_id: u64) -> OutputMessage {
+        self.window_start = None;
+        OutputMessage {
+            turn_id,
+            data: OutputData::Primary(std::mem::take(&mut self.buffer)),
+        }
+    }
+}
+
+impl MessageAggregator for ThrottleAggregator {
+    fn process(&mut self, msg: OutputMessage) -> Option<OutputMessage> {
+        match msg.data {
+            OutputData::PrimaryDelta(delta) => {
+                self.refill();
+
+                if self.buffer.len() + delta.len() > self.backlog {
+                    match self.policy {
+                        ThrottlePolicy::Drop => return None,
+                        ThrottlePolicy::Block => {
+                            // Force-flush now so the backlog never grows past
+                            // its cap (unless this one delta is itself larger),
+                            // then start a fresh window with the delta that
+                            // triggered the overflow.
+                            let flushed = if self.buffer.is_empty() {
+                                None
+                            } else {
+                                Some(self.take_buffer(msg.turn_id))
+                            };
+                            self.window_start = Some(Instant::now());
+                            self.buffer.push_str(&delta);
+                            return flushed;
+                        }
+                    }
+                }
+
+                if self.buffer.is_empty() {
+                    self.window_start = Some(Instant::now());
+                }
+                self.buffer.push_str(&delta);
+
+                let elapsed = self.window_start.map(|t| t.elapsed()).unwrap_or_default();
+                if elapsed >= self.interval && self.tokens > 0 {
+                    self.tokens -= 1;
+                    Some(self.take_buffer(msg.turn_id))
+                } else if elapsed >= self.timeout {
+                    // The token bucket is exhausted, but the buffer has now
+                    // been held back longer than `timeout` tolerates --
+                    // force the flush so a busy burst can't stall it
+                    // indefinitely.
+                    Some(self.take_buffer(msg.turn_id))
+                } else {
+                    None
+                }
+            }
+            _ => {
+                if !self.buffer.is_empty() {
+                    Some(self.take_buffer(msg.turn_id))
+                } else {
+                    Some(msg)
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Vec<OutputMessage> {
+        if !self.buffer.is_empty() {
+            vec![self.take_buffer(0)]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(turn_id: u64, text: &str) -> OutputMessage {
+        OutputMessage {
+            turn_id,
+            data: OutputData::PrimaryDelta(text.to_string()),
+        }
+    }
+
+    #[test]
+    fn forces_a_flush_once_timeout_elapses_even_with_an_exhausted_token_bucket() {
+        // `capacity: 0` means the token bucket never has a token to spend,
+        // so only the `timeout` force-flush (not the rate-limited path) can
+        // ever emit anything here.
+        let mut aggregator =
+            ThrottleAggregator::new(0, Duration::from_millis(5), Duration::from_millis(20), 4096, ThrottlePolicy::Block);
+
+        assert!(aggregator.process(delta(1, "a")).is_none());
+        std::thread::sleep(Duration::from_millis(30));
+
+        let flushed = aggregator
+            .process(delta(1, "b"))
+            .expect("timeout should force a flush despite the exhausted token bucket");
+        match flushed.data {
+            OutputData::Primary(text) => assert_eq!(text, "ab"),
+            other => panic!("expected a coalesced Primary, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn does_not_flush_before_the_timeout_when_the_token_bucket_is_exhausted() {
+        let mut aggregator = ThrottleAggregator::new(
+            0,
+            Duration::from_millis(5),
+            Duration::from_secs(60),
+            4096,
+            ThrottlePolicy::Block,
+        );
+
+        assert!(aggregator.process(delta(1, "a")).is_none());
+        assert!(aggregator.process(delta(1, "b")).is_none());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,173 @@
+//! Shared per-turn project state accumulated by tool calls
+//!
+//! Without this, every context-gathering tool (a file read, a directory
+//! listing, a search) reports its findings independently into the
+//! transcript, so two tools that touch the same file produce two redundant,
+//! unstructured blobs. Tools instead mutate a [`ProjectContext`] handle
+//! passed into [`crate::tool::ToolHandler::execute`], and the accumulated
+//! state is rendered once per turn into a single deduplicated system block
+//! via [`ProjectContext::take_rendered`] -- [`crate::tool::ToolResult`]
+//! still carries the human-visible output, only the model-visible context
+//! flows through here.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// A file a tool has opened or referenced during a turn
+#[derive(Debug, Clone)]
+struct OpenedFile {
+    /// Short description of how the file was touched (e.g. "read",
+    /// "modified lines 10-40"); a later registration for the same path
+    /// replaces this rather than appending a duplicate entry
+    summary: String,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ProjectContextState {
+    files: BTreeMap<PathBuf, OpenedFile>,
+    worktree_summaries: Vec<String>,
+    diagnostics: Vec<String>,
+}
+
+impl ProjectContextState {
+    fn is_empty(&self) -> bool {
+        self.files.is_empty() && self.worktree_summaries.is_empty() && self.diagnostics.is_empty()
+    }
+}
+
+/// Shared, cheaply cloned handle that tools mutate through
+/// [`crate::tool::ToolHandler::execute`] as they gather context over the
+/// course of a turn
+#[derive(Debug, Clone, Default)]
+pub struct ProjectContext {
+    state: Arc<Mutex<ProjectContextState>>,
+}
+
+impl ProjectContext {
+    /// Create an empty context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `path` was opened/referenced this turn
+    pub async fn register_file(&self, path: impl Into<PathBuf>, summary: impl Into<String>) {
+        let mut state = self.state.lock().await;
+        state.files.insert(
+            path.into(),
+            OpenedFile {
+                summary: summary.into(),
+            },
+        );
+    }
+
+    /// Record a free-form worktree summary (e.g. a directory listing or a
+    /// `git status` digest)
+    pub async fn register_worktree_summary(&self, summary: impl Into<String>) {
+        self.state.lock().await.worktree_summaries.push(summary.into());
+    }
+
+    /// Record a diagnostic (e.g. a compiler warning or lint finding)
+    pub async fn register_diagnostic(&self, diagnostic: impl Into<String>) {
+        self.state.lock().await.diagnostics.push(diagnostic.into());
+    }
+
+    /// Whether anything has been recorded yet this turn
+    pub async fn is_empty(&self) -> bool {
+        self.state.lock().await.is_empty()
+    }
+
+    /// Render the accumulated state into a single deduplicated system
+    /// block, trimmed to roughly `token_budget` tokens (a chars/4 estimate,
+    /// good enough for a budget check, not for billing), then clear it so
+    /// the next turn starts fresh. Returns `None` if nothing was recorded.
+    pub async fn take_rendered(&self, token_budget: usize) -> Option<String> {
+        let mut state = self.state.lock().await;
+        if state.is_empty() {
+            return None;
+        }
+
+        let mut block = String::from("## Project context gathered this turn\n");
+        for (path, file) in &state.files {
+            block.push_str(&format!("- {}: {}\n", path.display(), file.summary));
+        }
+        for summary in dedup_preserving_order(&state.worktree_summaries) {
+            block.push_str(&format!("- {summary}\n"));
+        }
+        for diagnostic in dedup_preserving_order(&state.diagnostics) {
+            block.push_str(&format!("- {diagnostic}\n"));
+        }
+
+        *state = ProjectContextState::default();
+
+        let budget_chars = token_budget.saturating_mul(4);
+        if block.len() > budget_chars {
+            block.truncate(budget_chars);
+            block.push_str("\n...(truncated)");
+        }
+
+        Some(block)
+    }
+}
+
+/// Keep only the first occurrence of each string, preserving order
+fn dedup_preserving_order(items: &[String]) -> Vec<&String> {
+    let mut seen = HashSet::new();
+    items.iter().filter(|item| seen.insert(item.as_str())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn take_rendered_collapses_duplicate_file_entries() {
+        let context = ProjectContext::new();
+        context.register_file("src/lib.rs", "read").await;
+        context.register_file("src/lib.rs", "modified lines 1-10").await;
+
+        let rendered = context.take_rendered(1000).await.unwrap();
+
+        assert_eq!(rendered.matches("src/lib.rs").count(), 1);
+        assert!(rendered.contains("modified lines 1-10"));
+    }
+
+    #[tokio::test]
+    async fn take_rendered_dedupes_repeated_worktree_summaries() {
+        let context = ProjectContext::new();
+        context.register_worktree_summary("3 files changed").await;
+        context.register_worktree_summary("3 files changed").await;
+
+        let rendered = context.take_rendered(1000).await.unwrap();
+
+        assert_eq!(rendered.matches("3 files changed").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn take_rendered_returns_none_when_nothing_was_recorded() {
+        let context = ProjectContext::new();
+        assert!(context.take_rendered(1000).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn take_rendered_clears_state_for_the_next_turn() {
+        let context = ProjectContext::new();
+        context.register_diagnostic("unused import").await;
+
+        context.take_rendered(1000).await;
+
+        assert!(context.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn take_rendered_truncates_to_the_token_budget() {
+        let context = ProjectContext::new();
+        context.register_diagnostic("x".repeat(1000)).await;
+
+        let rendered = context.take_rendered(1).await.unwrap();
+
+        assert!(rendered.contains("...(truncated)"));
+    }
+}
@@ -0,0 +1,257 @@
+//! Retrieval-augmented context injection for agent sessions
+//!
+//! Mirrors aichat's `Rag`: a caller adds documents to an in-memory vector
+//! index, and [`AgentSession::send`](crate::session::AgentSession::send)
+//! grounds each user turn in the most relevant chunks before it reaches the
+//! model.
+
+#[cfg(feature = "rag")]
+use crate::error::AgentError;
+#[cfg(feature = "rag")]
+use serde::Deserialize;
+#[cfg(feature = "rag")]
+use serde::Serialize;
+#[cfg(feature = "rag")]
+use std::future::Future;
+#[cfg(feature = "rag")]
+use std::path::Path;
+#[cfg(feature = "rag")]
+use std::pin::Pin;
+#[cfg(feature = "rag")]
+use std::sync::Arc;
+#[cfg(feature = "rag")]
+use typed_builder::TypedBuilder;
+
+/// Embeds text into a dense vector representation
+///
+/// Implementations typically call the same provider the agent uses for
+/// chat completions (e.g. OpenAI's `/embeddings` endpoint), so a single
+/// API key and base URL cover both.
+#[cfg(feature = "rag")]
+pub trait Embedder: Send + Sync {
+    /// Embed a single piece of text
+    fn embed(&self, text: &str) -> Pin<Box<dyn Future<Output = Result<Vec<f32>, AgentError>> + Send>>;
+}
+
+/// Scores how well a chunk answers a query, for an optional second-pass
+/// rerank after the initial similarity search
+#[cfg(feature = "rag")]
+pub trait Reranker: Send + Sync {
+    /// Score a single query/chunk pair; higher is more relevant
+    fn score(
+        &self,
+        query: &str,
+        chunk: &str,
+    ) -> Pin<Box<dyn Future<Output = Result<f32, AgentError>> + Send>>;
+}
+
+/// A chunk of a source document stored in the index, along with its
+/// embedding so the index can be reloaded without re-embedding on restore
+#[cfg(feature = "rag")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentChunk {
+    /// ID of the document this chunk was split from
+    pub doc_id: String,
+    /// The chunk's text
+    pub text: String,
+    /// The chunk's embedding vector
+    pub embedding: Vec<f32>,
+}
+
+/// Knobs controlling chunking, retrieval, and reranking
+#[cfg(feature = "rag")]
+#[derive(Debug, Clone, TypedBuilder)]
+pub struct RagConfig {
+    /// Target size, in characters, of each chunk a document is split into
+    #[builder(default = 800)]
+    pub chunk_size: usize,
+
+    /// How many trailing characters of a chunk are repeated at the start
+    /// of the next chunk, so a fact split across a boundary is still
+    /// findable from either side
+    #[builder(default = 200)]
+    pub chunk_overlap: usize,
+
+    /// Number of chunks to retrieve per query
+    #[builder(default = 5)]
+    pub top_k: usize,
+
+    /// Minimum cosine similarity a chunk must reach to be retrieved
+    #[builder(default = 0.0)]
+    pub similarity_threshold: f32,
+
+    /// Whether retrieval runs automatically on every `send`; the setting is
+    /// fixed once the index is built and has no per-message override
+    #[builder(default = true)]
+    pub retrieval_enabled: bool,
+}
+
+#[cfg(feature = "rag")]
+impl Default for RagConfig {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+/// In-memory vector index over a document corpus, used to ground agent
+/// answers in user-supplied material
+#[cfg(feature = "rag")]
+pub struct RagIndex {
+    config: RagConfig,
+    embedder: Arc<dyn Embedder>,
+    reranker: Option<Arc<dyn Reranker>>,
+    chunks: Vec<DocumentChunk>,
+}
+
+#[cfg(feature = "rag")]
+impl RagIndex {
+    /// Create an empty index backed by the given embedder
+    pub fn new(config: RagConfig, embedder: Arc<dyn Embedder>) -> Self {
+        Self {
+            config,
+            embedder,
+            reranker: None,
+            chunks: Vec::new(),
+        }
+    }
+
+    /// Enable a second-pass rerank of retrieved chunks
+    pub fn with_reranker(mut self, reranker: Arc<dyn Reranker>) -> Self {
+        self.reranker = Some(reranker);
+        self
+    }
+
+    /// Whether retrieval should run automatically for each message
+    pub fn retrieval_enabled(&self) -> bool {
+        self.config.retrieval_enabled
+    }
+
+    /// Chunk, embed, and add a document to the index
+    pub async fn add_document(&mut self, doc_id: impl Into<String>, text: &str) -> Result<(), AgentError> {
+        let doc_id = doc_id.into();
+        for chunk_text in split_into_chunks(text, self.config.chunk_size, self.config.chunk_overlap) {
+            let embedding = self.embedder.embed(&chunk_text).await?;
+            self.chunks.push(DocumentChunk {
+                doc_id: doc_id.clone(),
+                text: chunk_text,
+                embedding,
+            });
+        }
+        Ok(())
+    }
+
+    /// Retrieve the most relevant chunks for `query`, applying the
+    /// similarity threshold, `top_k` cutoff, and rerank pass (if configured)
+    pub async fn retrieve(&self, query: &str) -> Result<Vec<DocumentChunk>, AgentError> {
+        if self.chunks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let query_embedding = self.embedder.embed(query).await?;
+        let mut scored: Vec<(f32, &DocumentChunk)> = self
+            .chunks
+            .iter()
+            .map(|chunk| (cosine_similarity(&query_embedding, &chunk.embedding), chunk))
+            .filter(|(score, _)| *score >= self.config.similarity_threshold)
+            .collect();
+        scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(self.config.top_k);
+
+        let mut top: Vec<DocumentChunk> = scored.into_iter().map(|(_, chunk)| chunk.clone()).collect();
+
+        if let Some(reranker) = &self.reranker {
+            let mut rescored = Vec::with_capacity(top.len());
+            for chunk in top {
+                let score = reranker.score(query, &chunk.text).await?;
+                rescored.push((score, chunk));
+            }
+            rescored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+            top = rescored.into_iter().map(|(_, chunk)| chunk).collect();
+        }
+
+        Ok(top)
+    }
+
+    /// Render retrieved chunks as a single context block to prepend before
+    /// a user turn
+    pub fn render_context(chunks: &[DocumentChunk]) -> String {
+        chunks
+            .iter()
+            .map(|chunk| format!("[source: {}]\n{}", chunk.doc_id, chunk.text))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Persist the index's chunks and embeddings to disk
+    pub async fn save(&self, path: &Path) -> Result<(), AgentError> {
+        let json = serde_json::to_string_pretty(&self.chunks)
+            .map_err(|e| AgentError::InternalError(e.to_string()))?;
+        tokio::fs::write(path, json)
+            .await
+            .map_err(|e| AgentError::InternalError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Restore a previously saved index; chunks are loaded with their
+    /// existing embeddings, so nothing is re-embedded
+    pub async fn load(
+        path: &Path,
+        config: RagConfig,
+        embedder: Arc<dyn Embedder>,
+    ) -> Result<Self, AgentError> {
+        let json = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| AgentError::InternalError(e.to_string()))?;
+        let chunks: Vec<DocumentChunk> =
+            serde_json::from_str(&json).map_err(|e| AgentError::InternalError(e.to_string()))?;
+        Ok(Self {
+            config,
+            embedder,
+            reranker: None,
+            chunks,
+        })
+    }
+}
+
+/// Split `text` into overlapping chunks of roughly `size` characters
+#[cfg(feature = "rag")]
+fn split_into_chunks(text: &str, size: usize, overlap: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.is_empty() {
+        return Vec::new();
+    }
+    let size = size.max(1);
+    let overlap = overlap.min(size.saturating_sub(1));
+    let step = size - overlap;
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let end = (start + size).min(chars.len());
+        chunks.push(chars[start..end].iter().collect());
+        if end == chars.len() {
+            break;
+        }
+        start += step;
+    }
+    chunks
+}
+
+/// Cosine similarity between two equal-length vectors; `0.0` if either is
+/// zero-length or the dimensions don't match
+///
+/// `pub(crate)` rather than private so [`crate::tool::ProjectIndexToolHandler`]
+/// can score its own embeddings the same way this index does.
+#[cfg(feature = "rag")]
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
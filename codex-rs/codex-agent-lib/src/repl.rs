@@ -0,0 +1,277 @@
+//! Lightweight line-oriented REPL front-end, decoupled from the `tui` feature
+//!
+//! Mirrors [`crate::tui::AgentTui`]'s shape over the same core [`Agent`] /
+//! [`crate::session::AgentSession`], but talks to the agent over stdin/stdout
+//! instead of pulling in the ratatui stack, so `cargo run --features repl`
+//! stays a fast build while iterating on agent behavior. `repl` and `tui` are
+//! independent cargo feature layers; a binary only pays for the one it needs.
+//!
+//! When the `session` feature is also enabled, the REPL wraps the agent in
+//! an [`crate::session::AgentSession`] and gains slash-commands to inspect
+//! [`crate::session::SessionState`], dump [`crate::session::MessageHistory`],
+//! and reset context; without it, the REPL falls back to a raw
+//! [`Agent::execute`] loop and keeps only a local transcript.
+
+#[cfg(feature = "repl")]
+use crate::Agent;
+#[cfg(feature = "repl")]
+use crate::Result;
+#[cfg(feature = "repl")]
+use crate::error::AgentError;
+#[cfg(feature = "repl")]
+use crate::message::OutputData;
+#[cfg(feature = "repl")]
+use std::io::Write;
+#[cfg(feature = "repl")]
+use tokio::io::AsyncBufReadExt;
+#[cfg(feature = "repl")]
+use tokio::io::BufReader;
+#[cfg(feature = "repl")]
+use tokio::sync::mpsc;
+
+/// Interactive line-oriented front-end for an [`Agent`]
+#[cfg(feature = "repl")]
+pub struct Repl {
+    prompt: String,
+}
+
+#[cfg(feature = "repl")]
+impl Repl {
+    /// Create a new REPL with the default `"> "` prompt
+    pub fn new() -> Self {
+        Self {
+            prompt: "> ".to_string(),
+        }
+    }
+
+    /// Override the prompt string shown before each input line
+    pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Run the REPL against `agent` until the user sends `/exit` or EOF
+    pub async fn run(&mut self, agent: Agent) -> Result<()> {
+        #[cfg(feature = "session")]
+        {
+            self.run_with_session(agent).await
+        }
+        #[cfg(not(feature = "session"))]
+        {
+            self.run_without_session(agent).await
+        }
+    }
+
+    /// Fallback loop used when the `session` feature isn't enabled: talks to
+    /// the agent directly via [`Agent::execute`] and keeps only a local,
+    /// in-memory transcript for `/history` and `/reset`
+    #[cfg(not(feature = "session"))]
+    async fn run_without_session(&mut self, agent: Agent) -> Result<()> {
+        let mut transcript: Vec<(String, String)> = Vec::new();
+        let mut stdin = BufReader::new(tokio::io::stdin()).lines();
+
+        loop {
+            print_prompt(&self.prompt);
+
+            let Some(line) = read_line(&mut stdin).await? else {
+                break;
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            match line {
+                "/exit" | "/quit" => break,
+                "/history" => {
+                    print_transcript(&transcript);
+                    continue;
+                }
+                "/reset" => {
+                    transcript.clear();
+                    println!("(local transcript cleared; model context is not tracked without the session feature)");
+                    continue;
+                }
+                _ => {}
+            }
+
+            transcript.push(("user".to_string(), line.to_string()));
+
+            let (input_tx, input_rx) = mpsc::channel(1);
+            let (plan_tx, _plan_rx) = mpsc::channel(1);
+            let (output_tx, mut output_rx) = mpsc::channel(100);
+
+            let handle = agent.clone().execute(input_rx, plan_tx, output_tx).await?;
+            input_tx
+                .send(line.into())
+                .await
+                .map_err(|_| AgentError::ChannelError)?;
+            drop(input_tx);
+
+            let mut reply = String::new();
+            while let Some(output) = output_rx.recv().await {
+                if print_output(&output.data) {
+                    break;
+                }
+                if let OutputData::Primary(text) | OutputData::PrimaryDelta(text) = &output.data {
+                    reply.push_str(text);
+                }
+            }
+            transcript.push(("assistant".to_string(), reply));
+
+            handle.controller().stop().await;
+            let _ = handle.join().await;
+        }
+
+        Ok(())
+    }
+
+    /// Main loop used when the `session` feature is enabled: wraps `agent`
+    /// in an [`crate::session::AgentSession`] so slash-commands can inspect
+    /// real session state instead of a REPL-local copy
+    #[cfg(feature = "session")]
+    async fn run_with_session(&mut self, agent: Agent) -> Result<()> {
+        use crate::session::AgentSession;
+
+        let mut session = AgentSession::new(agent.clone());
+        let (tap_tx, mut tap_rx) = mpsc::channel::<OutputData>(100);
+        session.set_output_tap(tap_tx);
+        session.start().await?;
+
+        let mut stdin = BufReader::new(tokio::io::stdin()).lines();
+
+        loop {
+            print_prompt(&self.prompt);
+
+            let Some(line) = read_line(&mut stdin).await? else {
+                break;
+            };
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            match line {
+                "/exit" | "/quit" => break,
+                "/history" => {
+                    let history = session.get_history().await;
+                    for msg in history {
+                        println!("{}: {}", msg.role, msg.content);
+                    }
+                    continue;
+                }
+                "/state" => {
+                    let state = session.state_snapshot().await;
+                    match serde_json::to_string_pretty(&state) {
+                        Ok(json) => println!("{json}"),
+                        Err(e) => println!("failed to serialize session state: {e}"),
+                    }
+                    continue;
+                }
+                "/reset" => {
+                    session.stop().await?;
+                    session = AgentSession::new(agent.clone());
+                    let (tap_tx, new_tap_rx) = mpsc::channel::<OutputData>(100);
+                    session.set_output_tap(tap_tx);
+                    tap_rx = new_tap_rx;
+                    session.start().await?;
+                    println!("(context reset)");
+                    continue;
+                }
+                _ => {}
+            }
+
+            session.send(line.to_string()).await?;
+
+            while let Some(data) = tap_rx.recv().await {
+                if print_output(&data) {
+                    break;
+                }
+            }
+        }
+
+        session.stop().await?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "repl")]
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "repl")]
+fn print_prompt(prompt: &str) {
+    print!("{prompt}");
+    let _ = std::io::stdout().flush();
+}
+
+#[cfg(feature = "repl")]
+async fn read_line(
+    lines: &mut tokio::io::Lines<BufReader<tokio::io::Stdin>>,
+) -> Result<Option<String>> {
+    lines
+        .next_line()
+        .await
+        .map_err(|e| AgentError::InternalError(e.to_string()))
+}
+
+#[cfg(feature = "repl")]
+fn print_transcript(transcript: &[(String, String)]) {
+    for (role, content) in transcript {
+        println!("{role}: {content}");
+    }
+}
+
+/// Print a single `OutputData` event to stdout; returns `true` once a
+/// terminal event (`Completed`/`Error`) has been printed, so the caller
+/// knows to stop draining for this turn
+#[cfg(feature = "repl")]
+fn print_output(data: &OutputData) -> bool {
+    match data {
+        OutputData::Primary(text) => {
+            println!("{text}");
+            false
+        }
+        OutputData::PrimaryDelta(delta) => {
+            print!("{delta}");
+            let _ = std::io::stdout().flush();
+            false
+        }
+        OutputData::ToolStart { tool_name, .. } => {
+            println!("[tool] {tool_name} started");
+            false
+        }
+        OutputData::ToolOutput { output, .. } => {
+            print!("{output}");
+            let _ = std::io::stdout().flush();
+            false
+        }
+        OutputData::ToolOutputDelta { chunk, .. } => {
+            print!("{chunk}");
+            let _ = std::io::stdout().flush();
+            false
+        }
+        OutputData::ToolComplete { tool_name, .. } => {
+            println!("[tool] {tool_name} completed");
+            false
+        }
+        OutputData::Error(err) => {
+            println!("error: {err}");
+            true
+        }
+        OutputData::Completed => {
+            println!();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Run `agent` as a REPL on stdin/stdout with the default prompt
+#[cfg(feature = "repl")]
+pub async fn run_repl(agent: Agent) -> Result<()> {
+    Repl::new().run(agent).await
+}
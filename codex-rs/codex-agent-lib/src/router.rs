@@ -0,0 +1,301 @@
+//! Multi-agent router that fans a single input stream to several agents and
+//! merges their outputs
+//!
+//! `AgentRouter` sits on top of the existing channel-based `Agent::execute`
+//! API: it holds N named agents, routes each `InputMessage` to the agents
+//! linked to its "room", tags every `OutputMessage` with the id of the agent
+//! that produced it, and merges everything onto one combined output channel.
+//! A supervisor watches each agent's join handle and restarts it if it
+//! crashes, buffering any input that arrives during the restart window so it
+//! is not lost.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::Agent;
+use crate::message::InputMessage;
+use crate::message::OutputMessage;
+
+/// Identifies one agent registered with an [`AgentRouter`]
+pub type AgentId = String;
+
+/// An input message addressed to a "room", which the router's link-map
+/// resolves to a set of agent ids
+#[derive(Debug, Clone)]
+pub struct RoutedInput {
+    /// Room name this message targets
+    pub room: String,
+    /// The message to deliver to every agent linked to `room`
+    pub message: InputMessage,
+}
+
+/// An output message tagged with the agent that produced it
+#[derive(Debug, Clone)]
+pub struct RoutedOutput {
+    /// Id of the agent that produced `message`
+    pub source: AgentId,
+    /// The agent's output
+    pub message: OutputMessage,
+}
+
+/// Handle to a running [`AgentRouter`]
+pub struct AgentRouterHandle {
+    should_stop: Arc<AtomicBool>,
+    router_task: JoinHandle<()>,
+    supervisor_tasks: Vec<JoinHandle<()>>,
+}
+
+impl AgentRouterHandle {
+    /// Stop routing input and tear down every supervised agent
+    pub async fn stop(self) {
+        self.should_stop.store(true, Ordering::SeqCst);
+        let _ = self.router_task.await;
+        for task in self.supervisor_tasks {
+            let _ = task.await;
+        }
+    }
+}
+
+/// Builder and runtime for a multi-agent router
+pub struct AgentRouter {
+    agents: HashMap<AgentId, Agent>,
+    link_map: HashMap<String, Vec<AgentId>>,
+}
+
+impl AgentRouter {
+    /// Create an empty router
+    pub fn new() -> Self {
+        Self {
+            agents: HashMap::new(),
+            link_map: HashMap::new(),
+        }
+    }
+
+    /// Register an agent under `id`
+    pub fn register(mut self, id: impl Into<AgentId>, agent: Agent) -> Self {
+        self.agents.insert(id.into(), agent);
+        self
+    }
+
+    /// Route messages sent to `room` to the given set of agent ids
+    ///
+    /// A room with no link is simply never delivered; agents can share a
+    /// room (e.g. a planner and an executor both linked to "main") to model
+    /// them participating in the same conversation.
+    pub fn link(mut self, room: impl Into<String>, agent_ids: impl IntoIterator<Item = AgentId>) -> Self {
+        self.link_map
+            .insert(room.into(), agent_ids.into_iter().collect());
+        self
+    }
+
+    /// Start routing: every registered agent is launched under its own
+    /// supervisor, `input_rx` is fanned out per the link-map, and every
+    /// agent's output is tagged with its id and merged onto `output_tx`.
+    pub async fn run(
+        self,
+        mut input_rx: mpsc::Receiver<RoutedInput>,
+        output_tx: mpsc::Sender<RoutedOutput>,
+    ) -> AgentRouterHandle {
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let mut senders: HashMap<AgentId, mpsc::Sender<InputMessage>> = HashMap::new();
+        let mut supervisor_tasks = Vec::new();
+
+        for (id, agent) in self.agents {
+            let (agent_input_tx, agent_input_rx) = mpsc::channel(100);
+            senders.insert(id.clone(), agent_input_tx);
+
+            let output_tx = output_tx.clone();
+            let should_stop = should_stop.clone();
+            supervisor_tasks.push(tokio::spawn(Self::supervise(
+                id,
+                agent,
+                agent_input_rx,
+                output_tx,
+                should_stop,
+            )));
+        }
+
+        let link_map = self.link_map;
+        let router_should_stop = should_stop.clone();
+        let router_task = tokio::spawn(async move {
+            while let Some(routed) = input_rx.recv().await {
+                if router_should_stop.load(Ordering::SeqCst) {
+                    break;
+                }
+
+                let Some(targets) = link_map.get(&routed.room) else {
+                    warn!("no agents linked to room '{}'", routed.room);
+                    continue;
+                };
+
+                for target in targets {
+                    if let Some(tx) = senders.get(target) {
+                        let _ = tx.send(routed.message.clone()).await;
+                    } else {
+                        warn!("room '{}' links to unknown agent '{}'", routed.room, target);
+                    }
+                }
+            }
+        });
+
+        AgentRouterHandle {
+            should_stop,
+            router_task,
+            supervisor_tasks,
+        }
+    }
+
+    /// Run one agent, restarting it with a fresh conversation whenever its
+    /// execution task actually crashes, and forwarding its output (tagged
+    /// with `id`) onto `output_tx` until the router is stopped.
+    ///
+    /// `AgentExecutionHandle::join` resolving `Ok(())` covers every
+    /// *controlled* termination `run_agent_loop` reports -- the router's own
+    /// `stop`/cancellation, or the agent hitting its own `max_turns` cap --
+    /// none of which are a crash. Restarting on those would silently start a
+    /// fresh, history-less conversation every time, masking normal shutdown
+    /// as a failure. Only `Err(_)` (the task genuinely failed or panicked)
+    /// triggers a restart; on a controlled `Ok(())` this agent's supervision
+    /// simply ends.
+    async fn supervise(
+        id: AgentId,
+        agent: Agent,
+        mut input_rx: mpsc::Receiver<InputMessage>,
+        output_tx: mpsc::Sender<RoutedOutput>,
+        should_stop: Arc<AtomicBool>,
+    ) {
+        // Messages that arrived while the agent was between generations
+        // (e.g. a send raced a crash) are replayed to the next generation
+        // instead of being dropped.
+        let mut pending: VecDeque<InputMessage> = VecDeque::new();
+
+        'generations: while !should_stop.load(Ordering::SeqCst) {
+            let (gen_input_tx, gen_input_rx) = mpsc::channel(100);
+            let (plan_tx, mut plan_rx) = mpsc::channel(100);
+            let (gen_output_tx, mut gen_output_rx) = mpsc::channel(100);
+
+            let handle = match agent.clone().execute(gen_input_rx, plan_tx, gen_output_tx).await {
+                Ok(handle) => handle,
+                Err(e) => {
+                    error!("router: agent '{id}' failed to start, retrying: {e}");
+                    tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+
+            // This router surfaces only tagged OutputMessages; plan updates
+            // are drained so the channel never backs up.
+            tokio::spawn(async move { while plan_rx.recv().await.is_some() {} });
+
+            while let Some(msg) = pending.pop_front() {
+                let _ = gen_input_tx.send(msg).await;
+            }
+
+            let forward_id = id.clone();
+            let forward_output_tx = output_tx.clone();
+            let forward_task = tokio::spawn(async move {
+                while let Some(message) = gen_output_rx.recv().await {
+                    let _ = forward_output_tx
+                        .send(RoutedOutput {
+                            source: forward_id.clone(),
+                            message,
+                        })
+                        .await;
+                }
+            });
+
+            let mut join_fut = Box::pin(handle.join());
+            loop {
+                tokio::select! {
+                    joined = &mut join_fut => {
+                        let crashed = Self::log_generation_end(&id, joined);
+                        let _ = forward_task.await;
+                        if crashed { break; } else { break 'generations; }
+                    }
+                    maybe_msg = input_rx.recv() => {
+                        match maybe_msg {
+                            Some(msg) => {
+                                if let Err(e) = gen_input_tx.send(msg).await {
+                                    // The agent's task already exited; find
+                                    // out whether that was a crash before
+                                    // deciding whether to replay `msg` into a
+                                    // fresh generation.
+                                    let joined = join_fut.as_mut().await;
+                                    let crashed = Self::log_generation_end(&id, joined);
+                                    let _ = forward_task.await;
+                                    if crashed {
+                                        pending.push_back(e.0);
+                                        break;
+                                    } else {
+                                        break 'generations;
+                                    }
+                                }
+                            }
+                            None => {
+                                should_stop.store(true, Ordering::SeqCst);
+                                let _ = join_fut.as_mut().await;
+                                let _ = forward_task.await;
+                                break 'generations;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Log how a generation's execution task ended and report whether it
+    /// was an actual crash (`true`) as opposed to a controlled termination
+    /// (`false`) -- see [`Self::supervise`]'s doc comment for why that
+    /// distinction decides whether to restart.
+    fn log_generation_end(id: &AgentId, joined: crate::error::Result<()>) -> bool {
+        match joined {
+            Err(e) => {
+                error!("router: agent '{id}' crashed, restarting: {e}");
+                true
+            }
+            Ok(()) => {
+                info!("router: agent '{id}' stopped normally, not restarting");
+                false
+            }
+        }
+    }
+}
+
+impl Default for AgentRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AgentError;
+
+    #[test]
+    fn a_crash_is_reported_and_asks_for_a_restart() {
+        let id: AgentId = "worker".to_string();
+        let crashed = AgentRouter::log_generation_end(
+            &id,
+            Err(AgentError::InternalError("boom".to_string())),
+        );
+        assert!(crashed);
+    }
+
+    #[test]
+    fn a_controlled_termination_does_not_ask_for_a_restart() {
+        let id: AgentId = "worker".to_string();
+        let crashed = AgentRouter::log_generation_end(&id, Ok(()));
+        assert!(!crashed);
+    }
+}
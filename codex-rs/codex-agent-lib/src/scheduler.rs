@@ -0,0 +1,260 @@
+//! Dependency-graph executor for a plan's todo list
+//!
+//! `PlanMessage`/`TodoItem` model a flat list by default, but a todo can
+//! declare `depends_on` to require other todos to complete first. This
+//! module turns that into a DAG and runs ready tasks concurrently, up to a
+//! configurable parallelism limit.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::future::Future;
+
+use tokio::sync::mpsc;
+
+use crate::message::ExecutionStatus;
+use crate::message::TaskId;
+use crate::message::TodoItem;
+use crate::message::TodoStatus;
+
+/// Runs a plan's todos as a dependency graph
+///
+/// Tasks with no unmet dependencies are executed concurrently, bounded by
+/// `parallelism`. As each task completes, its dependents' unmet-dependency
+/// counts are decremented and any that reach zero are enqueued. If the
+/// ready queue empties while tasks remain (a cycle, or a dependency on a
+/// task that failed), the remainder is marked `Blocked`.
+pub struct DagScheduler {
+    parallelism: usize,
+}
+
+impl DagScheduler {
+    /// Create a scheduler that runs at most `parallelism` tasks concurrently
+    pub fn new(parallelism: usize) -> Self {
+        Self {
+            parallelism: parallelism.max(1),
+        }
+    }
+
+    /// Execute `todos` via `run_task`, reporting progress on `status_tx`
+    ///
+    /// Returns the todos with their final status (`Completed` or `Blocked`)
+    /// applied. `run_task` is called once per task that becomes ready.
+    pub async fn run<F, Fut>(
+        &self,
+        todos: Vec<TodoItem>,
+        status_tx: mpsc::Sender<ExecutionStatus>,
+        run_task: F,
+    ) -> Vec<TodoItem>
+    where
+        F: Fn(TodoItem) -> Fut + Send + Sync,
+        Fut: Future<Output = Result<(), String>> + Send,
+    {
+        let total = todos.len();
+        let mut by_id: HashMap<TaskId, TodoItem> =
+            todos.into_iter().map(|t| (t.id.clone(), t)).collect();
+
+        // unmet[id] = number of not-yet-completed dependencies of `id`
+        let mut unmet: HashMap<TaskId, usize> = HashMap::new();
+        // dependents[id] = tasks that become one step closer to ready when `id` completes
+        let mut dependents: HashMap<TaskId, Vec<TaskId>> = HashMap::new();
+        for todo in by_id.values() {
+            unmet.insert(todo.id.clone(), todo.depends_on.len());
+            for dep in &todo.depends_on {
+                dependents.entry(dep.clone()).or_default().push(todo.id.clone());
+            }
+        }
+
+        let mut ready: VecDeque<TaskId> = unmet
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let mut done_count = 0usize;
+
+        while !ready.is_empty() {
+            // Take this wave's frontier, bounded by the parallelism limit.
+            let wave_size = self.parallelism.min(ready.len());
+            let wave: Vec<TaskId> = (0..wave_size).filter_map(|_| ready.pop_front()).collect();
+
+            for id in &wave {
+                if let Some(todo) = by_id.get_mut(id) {
+                    todo.status = TodoStatus::InProgress;
+                }
+                let _ = status_tx
+                    .send(ExecutionStatus::InProgress {
+                        task: id.clone(),
+                        current: done_count,
+                        total,
+                        unit: "tasks".to_string(),
+                    })
+                    .await;
+            }
+
+            let run_task = &run_task;
+            let frontier = wave.iter().map(|id| {
+                let todo = by_id.get(id).expect("task queued for the current wave exists").clone();
+                let id = id.clone();
+                async move { (id, run_task(todo).await) }
+            });
+
+            for (id, result) in futures::future::join_all(frontier).await {
+                match result {
+                    Ok(()) => {
+                        if let Some(todo) = by_id.get_mut(&id) {
+                            todo.status = TodoStatus::Completed;
+                        }
+                        done_count += 1;
+                        let _ = status_tx.send(ExecutionStatus::Complete { task: id.clone() }).await;
+
+                        if let Some(deps) = dependents.get(&id) {
+                            for dependent in deps {
+                                if let Some(count) = unmet.get_mut(dependent) {
+                                    *count = count.saturating_sub(1);
+                                    if *count == 0 {
+                                        ready.push_back(dependent.clone());
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Err(reason) => {
+                        if let Some(todo) = by_id.get_mut(&id) {
+                            todo.status = TodoStatus::Blocked;
+                        }
+                        let _ = status_tx
+                            .send(ExecutionStatus::Failed {
+                                task: id.clone(),
+                                reason,
+                            })
+                            .await;
+                    }
+                }
+            }
+        }
+
+        // Anything still carrying unmet dependencies never drained: either a
+        // cycle, or a dependency on a task that failed. Mark it Blocked.
+        for (id, count) in unmet {
+            if count > 0
+                && let Some(todo) = by_id.get_mut(&id)
+                && todo.status != TodoStatus::Completed
+            {
+                todo.status = TodoStatus::Blocked;
+            }
+        }
+
+        by_id.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    fn todo(id: &str, depends_on: &[&str]) -> TodoItem {
+        TodoItem {
+            id: id.to_string(),
+            content: id.to_string(),
+            status: TodoStatus::Pending,
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+        }
+    }
+
+    fn status_of<'a>(todos: &'a [TodoItem], id: &str) -> &'a TodoStatus {
+        &todos.iter().find(|t| t.id == id).expect("task present").status
+    }
+
+    #[tokio::test]
+    async fn runs_a_chain_in_dependency_order() {
+        let todos = vec![todo("a", &[]), todo("b", &["a"]), todo("c", &["b"])];
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let (tx, _rx) = mpsc::channel(16);
+
+        let scheduler = DagScheduler::new(4);
+        let finished = scheduler
+            .run(todos, tx, {
+                let order = order.clone();
+                move |task| {
+                    let order = order.clone();
+                    async move {
+                        order.lock().unwrap().push(task.id.clone());
+                        Ok(())
+                    }
+                }
+            })
+            .await;
+
+        assert_eq!(*order.lock().unwrap(), vec!["a", "b", "c"]);
+        assert_eq!(*status_of(&finished, "a"), TodoStatus::Completed);
+        assert_eq!(*status_of(&finished, "b"), TodoStatus::Completed);
+        assert_eq!(*status_of(&finished, "c"), TodoStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn runs_independent_tasks_concurrently_up_to_the_parallelism_limit() {
+        let todos = vec![todo("a", &[]), todo("b", &[]), todo("c", &[]), todo("d", &[])];
+        let concurrent = Arc::new(AtomicUsize::new(0));
+        let max_concurrent = Arc::new(AtomicUsize::new(0));
+        let (tx, _rx) = mpsc::channel(16);
+
+        let scheduler = DagScheduler::new(2);
+        let finished = scheduler
+            .run(todos, tx, {
+                let concurrent = concurrent.clone();
+                let max_concurrent = max_concurrent.clone();
+                move |_task| {
+                    let concurrent = concurrent.clone();
+                    let max_concurrent = max_concurrent.clone();
+                    async move {
+                        let now = concurrent.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_concurrent.fetch_max(now, Ordering::SeqCst);
+                        tokio::task::yield_now().await;
+                        concurrent.fetch_sub(1, Ordering::SeqCst);
+                        Ok(())
+                    }
+                }
+            })
+            .await;
+
+        assert!(max_concurrent.load(Ordering::SeqCst) <= 2);
+        assert!(finished.iter().all(|t| t.status == TodoStatus::Completed));
+    }
+
+    #[tokio::test]
+    async fn a_failed_task_blocks_its_dependents() {
+        let todos = vec![todo("a", &[]), todo("b", &["a"])];
+        let (tx, _rx) = mpsc::channel(16);
+
+        let scheduler = DagScheduler::new(4);
+        let finished = scheduler
+            .run(todos, tx, |task| async move {
+                if task.id == "a" {
+                    Err("boom".to_string())
+                } else {
+                    Ok(())
+                }
+            })
+            .await;
+
+        assert_eq!(*status_of(&finished, "a"), TodoStatus::Blocked);
+        assert_eq!(*status_of(&finished, "b"), TodoStatus::Blocked);
+    }
+
+    #[tokio::test]
+    async fn a_dependency_cycle_never_becomes_ready_and_is_marked_blocked() {
+        let todos = vec![todo("a", &["b"]), todo("b", &["a"])];
+        let (tx, _rx) = mpsc::channel(16);
+
+        let scheduler = DagScheduler::new(4);
+        let finished = scheduler
+            .run(todos, tx, |_task| async move { Ok(()) })
+            .await;
+
+        assert_eq!(*status_of(&finished, "a"), TodoStatus::Blocked);
+        assert_eq!(*status_of(&finished, "b"), TodoStatus::Blocked);
+    }
+}
@@ -0,0 +1,261 @@
+//! JSON Schema documents for the config surface
+//!
+//! [`ModelProviderKind`], [`McpServerConfig`], [`SandboxPolicy`],
+//! [`ApprovalPolicy`], [`AgentProfile`], and [`PlanChannelCapacity`] derive
+//! [`schemars::JsonSchema`] directly (see their `#[cfg_attr(feature =
+//! "schema", ...)]` attributes) since none of their fields resist serde.
+//! [`AgentConfig`] and [`ToolConfig`] can't: `ToolConfig::Custom` carries a
+//! `CustomToolHandler` function pointer, and `AgentConfig::event_handlers` /
+//! `AgentConfig::audit_sink` carry an `Arc<dyn EventHandler>` registry and
+//! an `Arc<dyn AuditWriter>` sink respectively -- none of those are
+//! serializable or representable in JSON Schema. Rather than threading
+//! those exceptions through the types hosts actually construct agents
+//! with, this module mirrors the two into a dedicated, serde-aligned
+//! schema surface -- the same split cargo itself uses, extracting its
+//! manifest types into a `cargo-util-schemas` crate so editors can
+//! validate `Cargo.toml` without linking all of cargo. [`AgentConfigSchema`]
+//! omits `event_handlers` and `audit_sink` outright, the same way
+//! [`ToolConfigSchema::Custom`] omits `handler` -- a host can still attach
+//! them to the real [`AgentConfig`] in code after validating the rest of a
+//! config file against this schema.
+//!
+//! Hosts that want to validate a user-supplied config file before
+//! constructing an [`crate::Agent`] should deserialize against
+//! [`AgentConfigSchema`] first (surfacing a precise [`crate::AgentError`]
+//! on mismatch), then build the real [`crate::AgentConfig`] from the
+//! validated fields. [`AgentConfigSchema`] denies unknown fields so a
+//! typo'd key fails validation instead of being silently ignored.
+
+use crate::config::AgentProfile;
+use crate::config::ApprovalPolicy;
+use crate::config::McpServerConfig;
+use crate::config::ModelProviderKind;
+use crate::config::SandboxPolicy;
+use crate::config::Shell;
+use crate::plan_channel::PlanChannelCapacity;
+use schemars::JsonSchema;
+use schemars::schema_for;
+use schemars::schema::RootSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Serde-aligned mirror of [`crate::ToolConfig`], omitting the
+/// non-serializable `handler` carried by `Custom`
+///
+/// A `Custom` tool can still be described in a config file -- name,
+/// description, and parameter schema are all data -- but attaching it to a
+/// running [`crate::Agent`] requires a handler supplied in Rust, so
+/// deserializing this variant only gets a host as far as knowing a custom
+/// tool was requested, not how to run it.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolConfigSchema {
+    /// Built-in bash/shell tool
+    Bash {
+        /// Whether to allow network access
+        allow_network: bool,
+    },
+    /// Built-in web search tool
+    WebSearch,
+    /// Built-in file reading tool
+    FileRead,
+    /// Built-in file writing tool
+    FileWrite,
+    /// Built-in apply patch tool
+    ApplyPatch,
+    /// Custom tool description; the handler itself isn't representable in
+    /// config and must be attached in code
+    Custom {
+        name: String,
+        description: String,
+        parameters: serde_json::Value,
+    },
+}
+
+/// Serde-aligned mirror of [`crate::AgentConfig`], for validating
+/// user-supplied config files before constructing the real thing
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AgentConfigSchema {
+    /// Model to use (e.g., "gpt-5-mini", "o3")
+    #[serde(default = "default_model")]
+    pub model: String,
+
+    /// API key for authentication
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// Model provider (e.g., "openai", "azure", "ollama")
+    #[serde(default = "default_model_provider")]
+    pub model_provider: String,
+
+    /// Known provider family backing `model_provider`
+    #[serde(default)]
+    pub provider_kind: ModelProviderKind,
+
+    /// Override the provider's default API base URL
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// System prompt for the agent
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+
+    /// Base instructions for the agent
+    #[serde(default)]
+    pub base_instructions: Option<String>,
+
+    /// Tools available to the agent
+    #[serde(default)]
+    pub tools: Vec<ToolConfigSchema>,
+
+    /// MCP servers to connect to
+    #[serde(default)]
+    pub mcp_servers: Vec<McpServerConfig>,
+
+    /// Maximum number of turns before stopping
+    #[serde(default = "default_max_turns")]
+    pub max_turns: usize,
+
+    /// Working directory for the agent
+    #[serde(default = "default_working_directory")]
+    pub working_directory: std::path::PathBuf,
+
+    /// Enable reasoning mode (for supported models)
+    #[serde(default)]
+    pub enable_reasoning: bool,
+
+    /// Sandbox policy for tool execution
+    #[serde(default)]
+    pub sandbox_policy: SandboxPolicy,
+
+    /// Approval policy for tool execution
+    #[serde(default)]
+    pub approval_policy: ApprovalPolicy,
+
+    /// Custom Codex home directory
+    #[serde(default)]
+    pub codex_home: Option<std::path::PathBuf>,
+
+    /// Name of the active profile, looked up in `profiles`
+    #[serde(default)]
+    pub profile: Option<String>,
+
+    /// Named profiles overriding a subset of this config's fields when
+    /// selected via `profile`
+    #[serde(default)]
+    pub profiles: std::collections::HashMap<String, AgentProfile>,
+
+    /// Directory a loaded config file's relative paths are resolved
+    /// against; see [`crate::AgentConfig::config_root`]
+    #[serde(default)]
+    pub config_root: Option<std::path::PathBuf>,
+
+    /// Shell used to wrap a tool call's `command` argument
+    #[serde(default = "Shell::detect")]
+    pub shell: Shell,
+
+    /// Disable response storage (for zero data retention)
+    #[serde(default)]
+    pub disable_response_storage: bool,
+
+    /// Show raw agent reasoning (for supported models)
+    #[serde(default)]
+    pub show_raw_reasoning: bool,
+
+    /// Delay before the first retry attempt, in milliseconds
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub retry_base_delay_ms: u64,
+
+    /// Upper bound on the exponential backoff delay between retries, in
+    /// milliseconds
+    #[serde(default = "default_retry_max_delay_ms")]
+    pub retry_max_delay_ms: u64,
+
+    /// Maximum number of retries before a recoverable error becomes
+    /// terminal
+    #[serde(default = "default_retry_max_attempts")]
+    pub retry_max_attempts: u32,
+
+    /// Startup delay before the first turn is submitted, in milliseconds
+    #[serde(default)]
+    pub bootstrap_ms: u64,
+
+    /// Maximum number of independent tool calls run concurrently
+    #[serde(default = "default_tool_concurrency")]
+    pub tool_concurrency: usize,
+
+    /// Capacity of the internal plan/todo update buffer
+    #[serde(default)]
+    pub plan_channel_capacity: PlanChannelCapacity,
+}
+
+impl Default for ModelProviderKind {
+    fn default() -> Self {
+        Self::OpenAi
+    }
+}
+
+impl Default for SandboxPolicy {
+    fn default() -> Self {
+        Self::WorkspaceWrite
+    }
+}
+
+impl Default for ApprovalPolicy {
+    fn default() -> Self {
+        Self::Never
+    }
+}
+
+fn default_model() -> String {
+    "gpt-5-mini".to_string()
+}
+
+fn default_model_provider() -> String {
+    "openai".to_string()
+}
+
+fn default_max_turns() -> usize {
+    100
+}
+
+fn default_working_directory() -> std::path::PathBuf {
+    std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."))
+}
+
+fn default_retry_base_delay_ms() -> u64 {
+    500
+}
+
+fn default_retry_max_delay_ms() -> u64 {
+    30_000
+}
+
+fn default_retry_max_attempts() -> u32 {
+    5
+}
+
+fn default_tool_concurrency() -> usize {
+    num_cpus::get().min(crate::tool::DEFAULT_TOOL_CONCURRENCY)
+}
+
+/// JSON Schema document for [`AgentConfigSchema`]
+pub fn agent_config_schema() -> RootSchema {
+    schema_for!(AgentConfigSchema)
+}
+
+/// JSON Schema document for [`ToolConfigSchema`]
+pub fn tool_config_schema() -> RootSchema {
+    schema_for!(ToolConfigSchema)
+}
+
+/// JSON Schema document for [`crate::McpServerConfig`]
+pub fn mcp_server_config_schema() -> RootSchema {
+    schema_for!(McpServerConfig)
+}
+
+/// JSON Schema document for [`crate::SandboxPolicy`]
+pub fn sandbox_policy_schema() -> RootSchema {
+    schema_for!(SandboxPolicy)
+}
@@ -9,6 +9,8 @@ use crate::error::AgentError;
 #[cfg(feature = "session")]
 use crate::message::InputMessage;
 #[cfg(feature = "session")]
+use crate::message::OutputData;
+#[cfg(feature = "session")]
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "session")]
 use std::collections::VecDeque;
@@ -21,16 +23,80 @@ use tokio::sync::RwLock;
 #[cfg(feature = "session")]
 use tokio::sync::mpsc;
 
+/// Current on-disk schema version for [`SessionState`]
+///
+/// Bump this and append a `vN -> vN+1` function to [`MIGRATIONS`] whenever a
+/// field is added or changed in a way that breaks deserialization of older
+/// snapshots.
+#[cfg(feature = "session")]
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// A `vN -> vN+1` migration: backfills whatever v(N+1) added, in place, on
+/// the raw JSON value so it deserializes cleanly into the current
+/// `SessionState`
+#[cfg(feature = "session")]
+type Migration = fn(serde_json::Value) -> serde_json::Value;
+
+/// Registered migrations, indexed by `from_version - 1`
+#[cfg(feature = "session")]
+const MIGRATIONS: &[Migration] = &[migrate_v1_to_v2];
+
+/// v1 snapshots predate `schema_version`, `agent_state`, `pending_input`,
+/// and `aggregator_states`; backfill them with resume-safe defaults.
+#[cfg(feature = "session")]
+fn migrate_v1_to_v2(mut value: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.entry("schema_version").or_insert(serde_json::json!(2));
+        obj.entry("agent_state")
+            .or_insert(serde_json::json!("initialized"));
+        obj.entry("pending_input").or_insert(serde_json::json!([]));
+        obj.entry("aggregator_states")
+            .or_insert(serde_json::json!([]));
+    }
+    value
+}
+
+/// Apply every registered migration from `from_version` up to
+/// [`SCHEMA_VERSION`], in order
+#[cfg(feature = "session")]
+fn migrate_forward(mut value: serde_json::Value, from_version: u32) -> serde_json::Value {
+    let mut version = from_version.max(1);
+    while (version as usize) <= MIGRATIONS.len() {
+        value = MIGRATIONS[(version - 1) as usize](value);
+        version += 1;
+    }
+    value
+}
+
 /// Session state that can be saved and loaded
 #[cfg(feature = "session")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionState {
+    /// Schema version this snapshot was written with
+    #[serde(default)]
+    pub schema_version: u32,
     /// Message history
     pub messages: Vec<SerializedMessage>,
     /// Current turn count
     pub turn_count: u64,
     /// Session metadata
     pub metadata: SessionMetadata,
+    /// The agent's lifecycle state at the time of the snapshot
+    #[serde(default)]
+    pub agent_state: crate::AgentState,
+    /// Messages submitted via [`AgentSession::send`] whose turn hadn't
+    /// reached `OutputData::Completed` yet when the snapshot was taken, in
+    /// submission order; replayed by [`AgentSession::resume`] so a restart
+    /// mid-turn doesn't silently drop the user's in-flight input
+    #[serde(default)]
+    pub pending_input: Vec<String>,
+    /// Per-stage `MessageAggregator::save_state()` output, in pipeline order
+    #[serde(default)]
+    pub aggregator_states: Vec<serde_json::Value>,
+    /// Memoized tool call results, so a resumed session doesn't need to
+    /// re-run cacheable tools it already called
+    #[serde(default)]
+    pub tool_cache: crate::tool::ToolCallCache,
 }
 
 /// Serializable message format
@@ -40,8 +106,24 @@ pub struct SerializedMessage {
     pub role: String,
     pub content: String,
     pub timestamp: u64,
+    /// Set for synthetic `role: "summary"` messages produced by context
+    /// compression, so a reload doesn't try to re-summarize them
+    #[serde(default)]
+    pub is_summary: bool,
+}
+
+/// Rough chars-per-token heuristic used to decide when a session needs
+/// context compression; good enough for a threshold check, not for billing.
+#[cfg(feature = "session")]
+fn estimate_tokens(text: &str) -> u64 {
+    (text.len() as u64 / 4).max(1)
 }
 
+/// Token budget [`AgentSession::augment_with_project_context`] trims the
+/// accumulated [`crate::ProjectContext`] to before injecting it
+#[cfg(feature = "session")]
+const PROJECT_CONTEXT_TOKEN_BUDGET: usize = 2000;
+
 /// Session metadata
 #[cfg(feature = "session")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,28 +170,38 @@ impl MessageHistory {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_secs(),
+            is_summary: false,
         });
     }
-    
+
     /// Get all messages
     pub fn get_all(&self) -> Vec<SerializedMessage> {
         self.messages.iter().cloned().collect()
     }
-    
+
     /// Clear the history
     pub fn clear(&mut self) {
         self.messages.clear();
     }
-    
+
     /// Get the number of messages
     pub fn len(&self) -> usize {
         self.messages.len()
     }
-    
+
     /// Check if the history is empty
     pub fn is_empty(&self) -> bool {
         self.messages.is_empty()
     }
+
+    /// Collapse the oldest `count` messages into a single synthetic
+    /// message, used by [`AgentSession`]'s rolling context compression
+    pub fn replace_span(&mut self, count: usize, replacement: SerializedMessage) {
+        for _ in 0..count.min(self.messages.len()) {
+            self.messages.pop_front();
+        }
+        self.messages.push_front(replacement);
+    }
 }
 
 /// Session metrics
@@ -128,6 +220,13 @@ pub struct SessionMetrics {
     pub errors: u64,
     /// Session duration in seconds
     pub duration_secs: u64,
+    /// Total messages collapsed into summary turns by context compression
+    pub messages_compressed: u64,
+    /// Tool calls served from [`AgentSession::tool_cache`] instead of being
+    /// re-executed
+    pub tool_cache_hits: u64,
+    /// Tool calls that missed the cache (not cacheable, or not seen before)
+    pub tool_cache_misses: u64,
 }
 
 /// Agent session with state management
@@ -139,6 +238,31 @@ pub struct AgentSession {
     metrics: Arc<RwLock<SessionMetrics>>,
     input_tx: Option<mpsc::Sender<InputMessage>>,
     handle: Option<crate::agent::AgentExecutionHandle>,
+    /// Background task draining the error-reporting channel, spawned by
+    /// `start`/`start_with_error_sink`
+    reporter_handle: Option<tokio::task::JoinHandle<()>>,
+    /// Token threshold above which `send` triggers rolling summarization;
+    /// `None` (the default) disables context compression entirely
+    max_context_tokens: Option<u64>,
+    /// How many of the most recent messages are never eligible for
+    /// compression, to keep short-term coherence intact
+    pinned_messages: usize,
+    /// Document corpus grounding this session's answers, if retrieval is
+    /// enabled
+    #[cfg(feature = "rag")]
+    rag: Option<Arc<RwLock<crate::rag::RagIndex>>>,
+    /// Memoization cache for tool calls made through [`Self::call_tool_cached`]
+    tool_cache: Arc<RwLock<crate::tool::ToolCallCache>>,
+    /// Shared handle tools mutate through [`Self::call_tool_cached`] as they
+    /// gather context over the course of a turn; folded into a single
+    /// system block and injected ahead of the message on the next
+    /// [`Self::send`]
+    project_context: crate::ProjectContext,
+    /// Optional sink every `OutputData` is mirrored to as it's produced, for
+    /// callers (e.g. a chat-platform connector) that need to observe the
+    /// live stream in addition to the history/metrics this session already
+    /// tracks internally
+    output_tap: Option<mpsc::Sender<OutputData>>,
 }
 
 #[cfg(feature = "session")]
@@ -152,6 +276,7 @@ impl AgentSession {
             .as_secs();
         
         let state = SessionState {
+            schema_version: SCHEMA_VERSION,
             messages: Vec::new(),
             turn_count: 0,
             metadata: SessionMetadata {
@@ -161,8 +286,12 @@ impl AgentSession {
                 model: agent.config.model.clone(),
                 custom: serde_json::Value::Object(serde_json::Map::new()),
             },
+            agent_state: crate::AgentState::default(),
+            pending_input: Vec::new(),
+            aggregator_states: Vec::new(),
+            tool_cache: crate::tool::ToolCallCache::new(),
         };
-        
+
         Self {
             agent,
             state: Arc::new(RwLock::new(state)),
@@ -170,35 +299,217 @@ impl AgentSession {
             metrics: Arc::new(RwLock::new(SessionMetrics::default())),
             input_tx: None,
             handle: None,
+            reporter_handle: None,
+            max_context_tokens: None,
+            pinned_messages: 16,
+            #[cfg(feature = "rag")]
+            rag: None,
+            tool_cache: Arc::new(RwLock::new(crate::tool::ToolCallCache::new())),
+            project_context: crate::ProjectContext::new(),
+            output_tap: None,
         }
     }
-    
+
+    /// Mirror every `OutputData` this session produces to `tap` as it's
+    /// received, alongside the history/metrics bookkeeping `start` already
+    /// does; used by chat-platform connectors that bridge the live stream
+    /// to their own protocol instead of polling [`Self::get_history`]
+    pub fn set_output_tap(&mut self, tap: mpsc::Sender<OutputData>) {
+        self.output_tap = Some(tap);
+    }
+
+    /// Enable rolling context compression: once the estimated token count
+    /// across `state.messages` exceeds `max_tokens`, `send` summarizes the
+    /// oldest eligible messages into a single `role: "summary"` message,
+    /// keeping the most recent `pinned_messages` messages verbatim
+    pub fn with_context_compression(mut self, max_tokens: u64, pinned_messages: usize) -> Self {
+        self.max_context_tokens = Some(max_tokens);
+        self.pinned_messages = pinned_messages.max(1);
+        self
+    }
+
+    /// Ground this session's answers in a document corpus: each `send`
+    /// retrieves the most relevant chunks from `rag` and prepends them as
+    /// a context block before the user's turn
+    #[cfg(feature = "rag")]
+    pub fn with_rag(mut self, rag: crate::rag::RagIndex) -> Self {
+        self.rag = Some(Arc::new(RwLock::new(rag)));
+        self
+    }
+
+    /// Opt a tool into per-session memoization; only pure/read-only tools
+    /// should be marked cacheable, since a cache hit skips re-execution
+    /// entirely
+    ///
+    /// This only affects tools invoked through [`Self::call_tool_cached`],
+    /// i.e. tools implementing this crate's own [`crate::tool::ToolHandler`]
+    /// extension point. Tool calls dispatched through `codex_core`'s MCP/bash
+    /// event loop are executed before this crate observes them and can't be
+    /// intercepted here.
+    pub async fn mark_tool_cacheable(&self, tool_name: impl Into<String>) {
+        self.tool_cache.write().await.mark_cacheable(tool_name);
+    }
+
+    /// The shared [`crate::ProjectContext`] handle this session passes to
+    /// every [`crate::tool::ToolHandler::execute`] call made through
+    /// [`Self::call_tool_cached`] -- useful for a host wiring up its own
+    /// [`crate::tool::execute_tool_calls`] batch so tools share the same
+    /// per-turn context instead of each getting a fresh, disconnected one
+    pub fn project_context(&self) -> &crate::ProjectContext {
+        &self.project_context
+    }
+
+    /// Execute `tool_call` through `handler`, reusing a memoized result if
+    /// the tool was previously marked cacheable with
+    /// [`Self::mark_tool_cacheable`] and has been called before with the
+    /// same (canonicalized) arguments
+    pub async fn call_tool_cached(
+        &self,
+        tool_call: &crate::tool::ToolCall,
+        handler: &dyn crate::tool::ToolHandler,
+    ) -> Result<crate::tool::ToolResult> {
+        if let Some(cached) = self
+            .tool_cache
+            .read()
+            .await
+            .get(&tool_call.tool_name, &tool_call.arguments)
+        {
+            let mut metrics = self.metrics.write().await;
+            metrics.tool_cache_hits += 1;
+            return Ok(cached.clone());
+        }
+
+        let result = handler
+            .execute(tool_call.arguments.clone(), &self.project_context)
+            .await?;
+
+        {
+            let mut metrics = self.metrics.write().await;
+            metrics.tool_cache_misses += 1;
+        }
+        self.tool_cache.write().await.insert(
+            &tool_call.tool_name,
+            &tool_call.arguments,
+            result.clone(),
+        );
+
+        Ok(result)
+    }
+
+    /// Run a batch of tool calls concurrently through `registry`, sharing
+    /// this session's [`Self::project_context`] across every call -- see
+    /// [`crate::tool::execute_tool_calls`] for the concurrency/approval/
+    /// timeout semantics. A caller that owns its own [`crate::tool::ToolRegistry`]
+    /// (e.g. a host wiring up several [`crate::config::ToolConfig::Custom`]
+    /// handlers) uses this instead of dispatching each call one at a time
+    /// via [`Self::call_tool_cached`].
+    ///
+    /// `approver` is consulted for any call gated by `registry`'s
+    /// `requires_approval`, per this session's own
+    /// [`crate::config::AgentConfig::approval_policy`] (profile overrides
+    /// included, same resolution [`crate::Agent`] itself uses).
+    pub async fn run_tool_batch(
+        &self,
+        registry: &crate::tool::ToolRegistry,
+        calls: Vec<crate::tool::ToolCall>,
+        concurrency: usize,
+        timeout: std::time::Duration,
+        stop_flag: &std::sync::atomic::AtomicBool,
+        approver: &dyn crate::tool::ApprovalHandler,
+    ) -> Vec<crate::tool::ToolResult> {
+        let (_, approval_policy, _) = self.agent.config.profile_resolved();
+        crate::tool::execute_tool_calls(
+            registry,
+            calls,
+            concurrency,
+            timeout,
+            stop_flag,
+            &self.project_context,
+            approval_policy,
+            approver,
+        )
+        .await
+    }
+
+    /// [`Self::run_tool_batch`], but reusing [`Self::tool_cache`]'s memoized
+    /// results for any call whose tool was marked cacheable via
+    /// [`Self::mark_tool_cacheable`] -- the batch counterpart to
+    /// [`Self::call_tool_cached`], for a caller that wants a
+    /// [`crate::tool::ToolRegistry`] of cacheable tools dispatched
+    /// concurrently instead of one at a time.
+    pub async fn run_tool_batch_cached(
+        &self,
+        registry: &crate::tool::ToolRegistry,
+        calls: Vec<crate::tool::ToolCall>,
+        concurrency: usize,
+        timeout: std::time::Duration,
+        stop_flag: &std::sync::atomic::AtomicBool,
+        approver: &dyn crate::tool::ApprovalHandler,
+    ) -> Vec<crate::tool::ToolResult> {
+        let (_, approval_policy, _) = self.agent.config.profile_resolved();
+        let mut cache = self.tool_cache.write().await;
+        crate::tool::execute_tool_calls_cached(
+            registry,
+            calls,
+            concurrency,
+            timeout,
+            stop_flag,
+            &self.project_context,
+            &mut cache,
+            approval_policy,
+            approver,
+        )
+        .await
+    }
+
     /// Start the session
     pub async fn start(&mut self) -> Result<()> {
+        self.start_with_error_sink(None).await
+    }
+
+    /// Start the session, forwarding any `OutputData::Error` produced while
+    /// it runs to `error_sink`
+    ///
+    /// Delivery is resilient rather than fire-and-forget: a dedicated
+    /// reporter task drains the internal error channel and, for each error,
+    /// tries delivery into `error_sink` up to 3 times (see
+    /// `run_error_reporter`) with exponential backoff if the sink is momentarily
+    /// full, logging (and giving up on) deliveries that never land. The
+    /// reporter shuts down cleanly when [`Self::stop`] is called.
+    pub async fn start_with_error_sink(
+        &mut self,
+        error_sink: Option<mpsc::Sender<crate::error::OutputError>>,
+    ) -> Result<()> {
         if self.handle.is_some() {
             return Err(AgentError::AlreadyRunning);
         }
-        
+
         let (input_tx, input_rx) = mpsc::channel(100);
         let (plan_tx, mut plan_rx) = mpsc::channel(100);
         let (output_tx, mut output_rx) = mpsc::channel::<crate::message::OutputMessage>(100);
-        
+        let (error_tx, error_rx) = mpsc::channel::<crate::error::OutputError>(100);
+
         // Clone for handlers
         let history = self.history.clone();
         let metrics = self.metrics.clone();
         let state = self.state.clone();
-        
+        let output_tap = self.output_tap.clone();
+
         // Spawn output handler
         tokio::spawn(async move {
             while let Some(output) = output_rx.recv().await {
+                if let Some(tap) = &output_tap {
+                    let _ = tap.send(output.data.clone()).await;
+                }
+
                 let mut metrics = metrics.write().await;
                 metrics.messages_received += 1;
-                
+
                 match &output.data {
                     crate::message::OutputData::Primary(text) => {
                         let mut history = history.write().await;
                         history.add("assistant".to_string(), text.clone());
-                        
+
                         let mut state = state.write().await;
                         state.messages.push(SerializedMessage {
                             role: "assistant".to_string(),
@@ -207,36 +518,51 @@ impl AgentSession {
                                 .duration_since(std::time::UNIX_EPOCH)
                                 .unwrap_or_default()
                                 .as_secs(),
+                            is_summary: false,
                         });
                     }
                     crate::message::OutputData::ToolStart { .. } => {
                         metrics.tool_calls += 1;
                     }
-                    crate::message::OutputData::Error(_) => {
+                    crate::message::OutputData::Error(err) => {
                         metrics.errors += 1;
+                        let _ = error_tx.send(err.clone()).await;
+                    }
+                    crate::message::OutputData::Completed => {
+                        // The oldest still-pending send is the one whose
+                        // turn just finished, since `AgentSession::send`
+                        // pushes in submission order and turns settle in
+                        // the order they were submitted.
+                        let mut state = state.write().await;
+                        if !state.pending_input.is_empty() {
+                            state.pending_input.remove(0);
+                        }
                     }
                     _ => {}
                 }
             }
         });
-        
+
         // Spawn plan handler (just consume for now)
         tokio::spawn(async move {
             while let Some(_plan) = plan_rx.recv().await {
                 // Could store plan state here if needed
             }
         });
-        
+
+        let reporter_handle = tokio::spawn(run_error_reporter(error_rx, error_sink));
+
         let handle = self.agent.clone().execute(input_rx, plan_tx, output_tx).await?;
         self.input_tx = Some(input_tx);
         self.handle = Some(handle);
-        
+        self.reporter_handle = Some(reporter_handle);
+
         Ok(())
     }
-    
+
     /// Send a message to the agent
     pub async fn send(&mut self, message: String) -> Result<()> {
-        if let Some(tx) = &self.input_tx {
+        if self.input_tx.is_some() {
             // Update history
             {
                 let mut history = self.history.write().await;
@@ -259,22 +585,165 @@ impl AgentSession {
                         .duration_since(std::time::UNIX_EPOCH)
                         .unwrap_or_default()
                         .as_secs(),
+                    is_summary: false,
                 });
                 state.turn_count += 1;
                 state.metadata.updated_at = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap_or_default()
                     .as_secs();
+                state.pending_input.push(message.clone());
             }
-            
-            tx.send(message.into()).await
+
+            let with_project_context = self.augment_with_project_context(&message).await;
+            let augmented = self.augment_with_rag_context(&with_project_context).await?;
+
+            let tx = self.input_tx.as_ref().expect("checked by outer if let");
+            tx.send(augmented.into()).await
                 .map_err(|_| AgentError::ChannelError)?;
+
+            self.maybe_compress().await?;
+
             Ok(())
         } else {
             Err(AgentError::NotRunning)
         }
     }
-    
+
+    /// Fold this turn's accumulated [`crate::ProjectContext`] -- file,
+    /// worktree, and diagnostic entries tools registered through
+    /// [`Self::call_tool_cached`] -- into a single deduplicated system
+    /// block prepended before `message`, then clear it so the next turn
+    /// starts fresh. Returns `message` unchanged if nothing was recorded.
+    async fn augment_with_project_context(&self, message: &str) -> String {
+        match self
+            .project_context
+            .take_rendered(PROJECT_CONTEXT_TOKEN_BUDGET)
+            .await
+        {
+            Some(context) => format!("{context}\n\n{message}"),
+            None => message.to_string(),
+        }
+    }
+
+    /// Retrieve relevant chunks from the session's `rag` index (if any) and
+    /// prepend them as a context block before `message`; returns `message`
+    /// unchanged when no index is attached or retrieval is disabled
+    #[cfg(feature = "rag")]
+    async fn augment_with_rag_context(&self, message: &str) -> Result<String> {
+        let Some(rag) = &self.rag else {
+            return Ok(message.to_string());
+        };
+        let rag = rag.read().await;
+        if !rag.retrieval_enabled() {
+            return Ok(message.to_string());
+        }
+        let chunks = rag.retrieve(message).await?;
+        if chunks.is_empty() {
+            return Ok(message.to_string());
+        }
+        let context = crate::rag::RagIndex::render_context(&chunks);
+        Ok(format!("{context}\n\n{message}"))
+    }
+
+    #[cfg(not(feature = "rag"))]
+    async fn augment_with_rag_context(&self, message: &str) -> Result<String> {
+        Ok(message.to_string())
+    }
+
+    /// Collapse the oldest unpinned messages into a single synthetic
+    /// summary turn when the running token estimate crosses
+    /// `max_context_tokens`
+    ///
+    /// Mirrors aichat's rolling session compaction: messages older than
+    /// the last `pinned_messages` entries are eligible, summarized by the
+    /// same model via [`Agent::query`], and replaced in both
+    /// `state.messages` and the `MessageHistory` buffer with one
+    /// `role: "summary"` message tagged `is_summary` so it round-trips
+    /// through `save_session`/`load_session` without being re-summarized.
+    async fn maybe_compress(&mut self) -> Result<()> {
+        let Some(max_tokens) = self.max_context_tokens else {
+            return Ok(());
+        };
+
+        let messages = self.state.read().await.messages.clone();
+        if messages.len() <= self.pinned_messages {
+            return Ok(());
+        }
+
+        let total_tokens: u64 = messages.iter().map(|m| estimate_tokens(&m.content)).sum();
+        if total_tokens <= max_tokens {
+            return Ok(());
+        }
+
+        let pinned_at = messages.len() - self.pinned_messages;
+        let target_tokens = (max_tokens / 2).max(1);
+        let mut collapsed_tokens = 0u64;
+        let mut split = 0usize;
+        for message in &messages[..pinned_at] {
+            collapsed_tokens += estimate_tokens(&message.content);
+            split += 1;
+            if collapsed_tokens >= target_tokens {
+                break;
+            }
+        }
+        if split == 0 {
+            return Ok(());
+        }
+
+        let transcript = messages[..split]
+            .iter()
+            .map(|m| format!("{}: {}", m.role, m.content))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let prompt = format!(
+            "Summarize the following conversation span concisely, preserving any facts, decisions, or commitments a later turn might need:\n\n{transcript}"
+        );
+
+        // Built from scratch rather than `self.agent.clone()`: the live
+        // agent's `system_prompt`/`tools`/`mcp_servers` are whatever persona
+        // the conversation is actually running, which has no business
+        // influencing a plain-text summarization call (and could make it
+        // spawn tool calls instead of returning one). Only the connection
+        // details (model, provider, auth) carry over.
+        let mut summarizer_config = self.agent.config.clone();
+        summarizer_config.system_prompt = Some(
+            "You summarize conversation transcripts concisely and factually. \
+             Respond with the summary only, no tool calls."
+                .to_string(),
+        );
+        summarizer_config.base_instructions = None;
+        summarizer_config.tools = Vec::new();
+        summarizer_config.mcp_servers = Vec::new();
+        let mut summarizer = Agent::new(summarizer_config)?;
+        let summary_text = summarizer.query(&prompt).await?;
+
+        let summary = SerializedMessage {
+            role: "summary".to_string(),
+            content: summary_text,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            is_summary: true,
+        };
+
+        {
+            let mut state = self.state.write().await;
+            state.messages.splice(..split, std::iter::once(summary.clone()));
+        }
+        {
+            let mut history = self.history.write().await;
+            history.replace_span(split, summary);
+        }
+        {
+            let mut metrics = self.metrics.write().await;
+            metrics.messages_compressed += split as u64;
+        }
+
+        Ok(())
+    }
+
     /// Get the message history
     pub async fn get_history(&self) -> Vec<SerializedMessage> {
         self.history.read().await.get_all()
@@ -284,32 +753,58 @@ impl AgentSession {
     pub async fn get_metrics(&self) -> SessionMetrics {
         self.metrics.read().await.clone()
     }
+
+    /// Snapshot the current [`SessionState`], for callers (e.g. the `repl`
+    /// feature's inspection commands) that want to look at it without going
+    /// through [`Self::save_session`]
+    pub async fn state_snapshot(&self) -> SessionState {
+        let mut state = self.state.read().await.clone();
+        state.tool_cache = self.tool_cache.read().await.clone();
+        state
+    }
     
     /// Save the session to a file
     pub async fn save_session(&self, path: &Path) -> Result<()> {
-        let state = self.state.read().await.clone();
+        let mut state = self.state.read().await.clone();
+        state.schema_version = SCHEMA_VERSION;
+        state.tool_cache = self.tool_cache.read().await.clone();
         let json = serde_json::to_string_pretty(&state)
             .map_err(|e| AgentError::InternalError(e.to_string()))?;
-        
+
         tokio::fs::write(path, json).await
             .map_err(|e| AgentError::InternalError(e.to_string()))?;
-        
+
         Ok(())
     }
-    
+
     /// Load a session from a file
+    ///
+    /// Snapshots written by an older version of this crate are forward
+    /// migrated (see [`MIGRATIONS`]) before being deserialized, so resuming
+    /// a session never requires the on-disk format to be up to date.
     pub async fn load_session(path: &Path, agent: Agent) -> Result<Self> {
         let json = tokio::fs::read_to_string(path).await
             .map_err(|e| AgentError::InternalError(e.to_string()))?;
-        
-        let state: SessionState = serde_json::from_str(&json)
+
+        let raw: serde_json::Value = serde_json::from_str(&json)
             .map_err(|e| AgentError::InternalError(e.to_string()))?;
-        
+        let from_version = raw
+            .get("schema_version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1) as u32;
+        let migrated = migrate_forward(raw, from_version);
+
+        let state: SessionState = serde_json::from_value(migrated)
+            .map_err(|e| AgentError::InternalError(e.to_string()))?;
+
         let mut history = MessageHistory::new(1000);
         for msg in &state.messages {
-            history.add(msg.role.clone(), msg.content.clone());
+            // Push the message as-is (not via `add`) so summary messages
+            // keep their `is_summary` tag and original timestamp.
+            history.messages.push_back(msg.clone());
         }
-        
+        let tool_cache = state.tool_cache.clone();
+
         Ok(Self {
             agent,
             state: Arc::new(RwLock::new(state)),
@@ -317,16 +812,215 @@ impl AgentSession {
             metrics: Arc::new(RwLock::new(SessionMetrics::default())),
             input_tx: None,
             handle: None,
+            reporter_handle: None,
+            max_context_tokens: None,
+            pinned_messages: 16,
+            #[cfg(feature = "rag")]
+            rag: None,
+            tool_cache: Arc::new(RwLock::new(tool_cache)),
+            project_context: crate::ProjectContext::new(),
+            output_tap: None,
         })
     }
-    
+
+    /// Load a session from a file and resume it: starts the agent, replays
+    /// any `pending_input` left over from before the restart, and seeds the
+    /// new [`AgentExecutionHandle`]'s turn counter from the saved state so
+    /// `max_turns` accounting carries over across the restart.
+    pub async fn resume(path: &Path, agent: Agent) -> Result<Self> {
+        let mut session = Self::load_session(path, agent).await?;
+        session.start().await?;
+
+        // Take the snapshot's `pending_input` and drop it from state before
+        // resubmitting: `Self::send` re-pushes each message as it's replayed,
+        // and the `OutputData::Completed` handler retires that fresh entry
+        // once its turn actually finishes. Clearing here (instead of after
+        // the loop) avoids leaving the stale on-disk copies stranded
+        // alongside the replay's own entries, which nothing would ever pop.
+        let pending = {
+            let mut state = session.state.write().await;
+            std::mem::take(&mut state.pending_input)
+        };
+        for message in pending {
+            session.send(message).await?;
+        }
+
+        if let Some(handle) = &session.handle {
+            let turn_count = session.state.read().await.turn_count;
+            handle.controller().set_turn_count(turn_count);
+        }
+
+        Ok(session)
+    }
+
+    /// Snapshot the saved `MessageAggregator` states from a previous session
+    ///
+    /// Lets a caller who separately owns a `MessageProcessor` (feature
+    /// `utils`) pull its pipeline state back out for persistence, without
+    /// this module depending on the `utils` feature directly.
+    pub async fn aggregator_states(&self) -> Vec<serde_json::Value> {
+        self.state.read().await.aggregator_states.clone()
+    }
+
+    /// Restore `MessageAggregator` states into this session ahead of a
+    /// save, mirroring [`Self::aggregator_states`]
+    pub async fn set_aggregator_states(&self, states: Vec<serde_json::Value>) {
+        self.state.write().await.aggregator_states = states;
+    }
+
     /// Stop the session
+    ///
+    /// Joins the agent's execution handle first, which drops its
+    /// `output_tx` and lets the output handler task exit; that in turn
+    /// drops the error channel's sender, so the error reporter task drains
+    /// whatever is left and shuts down cleanly rather than being aborted.
     pub async fn stop(&mut self) -> Result<()> {
         if let Some(handle) = self.handle.take() {
             handle.controller().stop().await;
             let _ = handle.join().await;
         }
         self.input_tx = None;
+        if let Some(reporter_handle) = self.reporter_handle.take() {
+            let _ = reporter_handle.await;
+        }
         Ok(())
     }
+}
+
+/// Background task that drains `error_rx` and, for each error, attempts
+/// delivery into `error_sink` (if any) with bounded retries and
+/// exponential backoff when the sink is momentarily full
+///
+/// Ported from the unki `ErrChan` background reporter pattern: errors
+/// produced deep inside the spawned output handler would otherwise only
+/// ever surface as a `SessionMetrics::errors` increment.
+#[cfg(feature = "session")]
+async fn run_error_reporter(
+    mut error_rx: mpsc::Receiver<crate::error::OutputError>,
+    error_sink: Option<mpsc::Sender<crate::error::OutputError>>,
+) {
+    let retry = crate::RetryConfig::builder().max_attempts(3).build();
+
+    while let Some(error) = error_rx.recv().await {
+        let Some(sink) = &error_sink else {
+            continue;
+        };
+
+        let mut attempt = 0u32;
+        loop {
+            match sink.try_send(error.clone()) {
+                Ok(()) => break,
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    tracing::warn!("error sink closed; dropping error: {error:?}");
+                    break;
+                }
+                Err(mpsc::error::TrySendError::Full(_)) if attempt + 1 < retry.max_attempts => {
+                    tracing::warn!(
+                        "error sink full on attempt {}/{}, retrying: {error:?}",
+                        attempt + 1,
+                        retry.max_attempts
+                    );
+                    tokio::time::sleep(retry.delay_for_attempt(attempt)).await;
+                    attempt += 1;
+                }
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    tracing::warn!(
+                        "giving up delivering error to sink after {} attempts: {error:?}",
+                        retry.max_attempts
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "session")]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_v1_to_v2_backfills_new_fields_with_resume_safe_defaults() {
+        let v1 = serde_json::json!({
+            "messages": [],
+            "turn_count": 3,
+            "metadata": {},
+        });
+
+        let migrated = migrate_v1_to_v2(v1);
+
+        assert_eq!(migrated["schema_version"], 2);
+        assert_eq!(migrated["agent_state"], "initialized");
+        assert_eq!(migrated["pending_input"], serde_json::json!([]));
+        assert_eq!(migrated["aggregator_states"], serde_json::json!([]));
+        assert_eq!(migrated["turn_count"], 3);
+    }
+
+    #[test]
+    fn migrate_v1_to_v2_does_not_overwrite_fields_already_present() {
+        let already_v2 = serde_json::json!({
+            "schema_version": 2,
+            "agent_state": "running",
+            "pending_input": ["draft"],
+            "aggregator_states": [{"buffered": 1}],
+        });
+
+        let migrated = migrate_v1_to_v2(already_v2);
+
+        assert_eq!(migrated["agent_state"], "running");
+        assert_eq!(migrated["pending_input"], serde_json::json!(["draft"]));
+        assert_eq!(
+            migrated["aggregator_states"],
+            serde_json::json!([{"buffered": 1}])
+        );
+    }
+
+    #[test]
+    fn migrate_forward_from_v1_applies_every_registered_migration() {
+        let v1 = serde_json::json!({
+            "messages": [],
+            "turn_count": 0,
+            "metadata": {},
+        });
+
+        let migrated = migrate_forward(v1, 1);
+
+        assert_eq!(migrated["schema_version"], SCHEMA_VERSION);
+        assert_eq!(migrated["agent_state"], "initialized");
+    }
+
+    #[test]
+    fn migrate_forward_from_current_version_is_a_no_op() {
+        let current = serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "agent_state": "running",
+            "pending_input": [],
+            "aggregator_states": [],
+            "messages": [],
+            "turn_count": 7,
+            "metadata": {},
+        });
+
+        let migrated = migrate_forward(current.clone(), SCHEMA_VERSION);
+
+        assert_eq!(migrated, current);
+    }
+
+    #[test]
+    fn migrate_forward_treats_a_missing_from_version_as_v1() {
+        // `schema_version` was introduced by the v1->v2 migration itself, so
+        // a snapshot predating it has no `schema_version` field at all;
+        // callers default `from_version` to 1 in that case (see
+        // `AgentSession::load_session`).
+        let no_version = serde_json::json!({
+            "messages": [],
+            "turn_count": 0,
+            "metadata": {},
+        });
+
+        let migrated = migrate_forward(no_version, 1);
+
+        assert_eq!(migrated["schema_version"], SCHEMA_VERSION);
+    }
 }
\ No newline at end of file
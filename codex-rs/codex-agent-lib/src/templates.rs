@@ -4,6 +4,300 @@
 use crate::config::AgentConfig;
 #[cfg(feature = "templates")]
 use crate::config::SandboxPolicy;
+#[cfg(feature = "templates")]
+use crate::config::ToolConfig;
+#[cfg(feature = "templates")]
+use crate::error::AgentError;
+#[cfg(feature = "templates")]
+use std::collections::HashMap;
+#[cfg(feature = "templates")]
+use std::path::Path;
+
+/// On-disk shape of a template file (TOML or YAML), covering the knobs a
+/// team is most likely to want to override per-persona
+#[cfg(feature = "templates")]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TemplateFile {
+    /// Model to use (e.g., "gpt-5-mini", "o3")
+    #[serde(default = "default_model")]
+    pub model: String,
+
+    /// System prompt for the agent
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+
+    /// Sandbox policy: "danger_full_access", "read_only", or "workspace_write"
+    #[serde(default = "default_sandbox_policy")]
+    pub sandbox_policy: String,
+
+    /// Maximum number of turns before stopping
+    #[serde(default = "default_max_turns")]
+    pub max_turns: usize,
+
+    /// Name of the environment variable the API key should be read from
+    /// (e.g. `"OPENAI_API_KEY"`); left unset if the agent authenticates
+    /// another way
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+
+    /// Built-in tools to enable, by name: `"bash"`, `"web_search"`,
+    /// `"file_read"`, `"file_write"`, `"apply_patch"`. A declarative file
+    /// can only turn on built-ins -- a [`ToolConfig::Custom`] handler is a
+    /// closure and can't be represented as data.
+    #[serde(default)]
+    pub tools: Vec<String>,
+
+    /// Arbitrary team-defined metadata, not interpreted by this crate
+    #[serde(default)]
+    pub metadata: serde_json::Value,
+}
+
+#[cfg(feature = "templates")]
+fn default_model() -> String {
+    "gpt-5-mini".to_string()
+}
+
+#[cfg(feature = "templates")]
+fn default_sandbox_policy() -> String {
+    "workspace_write".to_string()
+}
+
+#[cfg(feature = "templates")]
+fn default_max_turns() -> usize {
+    100
+}
+
+#[cfg(feature = "templates")]
+impl TemplateFile {
+    /// Parse a single `*.toml`/`*.yaml`/`*.yml` file into a [`TemplateFile`],
+    /// dispatching on its extension the same way [`TemplateRegistry::load_dir`] does
+    pub fn load(path: &Path) -> Result<Self, AgentError> {
+        let extension = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| AgentError::ConfigError(format!("{} has no extension", path.display())))?;
+
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| AgentError::ConfigError(format!("reading {}: {e}", path.display())))?;
+
+        match extension {
+            "toml" => toml::from_str(&contents)
+                .map_err(|e| AgentError::ConfigError(format!("parsing {}: {e}", path.display()))),
+            "yaml" | "yml" => serde_yaml::from_str(&contents)
+                .map_err(|e| AgentError::ConfigError(format!("parsing {}: {e}", path.display()))),
+            other => Err(AgentError::ConfigError(format!(
+                "unsupported template extension {other:?} in {}",
+                path.display()
+            ))),
+        }
+    }
+
+    fn into_config(self) -> Result<AgentConfig, AgentError> {
+        self.into_config_with_context(&HashMap::new())
+    }
+
+    /// Convert this template into an [`AgentConfig`], substituting
+    /// `{{variable}}` placeholders in the system prompt from `context`
+    /// before anything else is resolved
+    pub fn into_config_with_context(
+        self,
+        context: &HashMap<String, String>,
+    ) -> Result<AgentConfig, AgentError> {
+        let sandbox_policy = match self.sandbox_policy.as_str() {
+            "danger_full_access" => SandboxPolicy::DangerFullAccess,
+            "read_only" => SandboxPolicy::ReadOnly,
+            "workspace_write" => SandboxPolicy::WorkspaceWrite,
+            other => {
+                return Err(AgentError::ConfigError(format!(
+                    "unknown sandbox_policy {other:?}; expected danger_full_access, read_only, or workspace_write"
+                )));
+            }
+        };
+
+        let system_prompt = self
+            .system_prompt
+            .map(|prompt| substitute_template(&prompt, context));
+
+        let api_key = match self.api_key_env {
+            Some(var) => Some(std::env::var(&var).map_err(|_| {
+                AgentError::ConfigError(format!("environment variable {var:?} is not set"))
+            })?),
+            None => None,
+        };
+
+        let tools = self
+            .tools
+            .iter()
+            .map(|name| match name.as_str() {
+                "bash" => Ok(ToolConfig::Bash {
+                    allow_network: false,
+                }),
+                "web_search" => Ok(ToolConfig::WebSearch),
+                "file_read" => Ok(ToolConfig::FileRead),
+                "file_write" => Ok(ToolConfig::FileWrite),
+                "apply_patch" => Ok(ToolConfig::ApplyPatch),
+                other => Err(AgentError::ConfigError(format!(
+                    "unknown tool {other:?}; expected bash, web_search, file_read, file_write, or apply_patch"
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(AgentConfig::builder()
+            .model(self.model)
+            .api_key(api_key)
+            .system_prompt(system_prompt)
+            .sandbox_policy(sandbox_policy)
+            .max_turns(self.max_turns)
+            .tools(tools)
+            .build())
+    }
+}
+
+/// Replace every `{{key}}` placeholder in `text` with `context[key]`,
+/// leaving unmatched placeholders untouched so a missing context entry
+/// fails loudly in the rendered prompt rather than silently
+#[cfg(feature = "templates")]
+fn substitute_template(text: &str, context: &HashMap<String, String>) -> String {
+    let mut result = text.to_string();
+    for (key, value) in context {
+        result = result.replace(&format!("{{{{{key}}}}}"), value);
+    }
+    result
+}
+
+/// Loads [`AgentConfig`] templates from a directory of TOML/YAML files,
+/// falling back to the built-in [`templates`] functions when a name isn't
+/// found on disk
+///
+/// This gives the `templates` feature a real extension point: a team can
+/// ship its own persona library (or override a built-in persona, e.g. swap
+/// the `python_developer` model) without recompiling this crate.
+#[cfg(feature = "templates")]
+#[derive(Debug, Clone, Default)]
+pub struct TemplateRegistry {
+    files: HashMap<String, TemplateFile>,
+}
+
+#[cfg(feature = "templates")]
+impl TemplateRegistry {
+    /// Create an empty registry backed only by the built-in templates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load every `*.toml`/`*.yaml`/`*.yml` file in `dir` into the registry,
+    /// keyed by file stem (e.g. `python_developer.toml` -> `"python_developer"`)
+    pub fn load_dir(dir: &Path) -> Result<Self, AgentError> {
+        let mut registry = Self::new();
+
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| AgentError::ConfigError(format!("reading {}: {e}", dir.display())))?;
+
+        for entry in entries {
+            let entry =
+                entry.map_err(|e| AgentError::ConfigError(format!("reading {}: {e}", dir.display())))?;
+            let path = entry.path();
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let Some(extension) = path.extension().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| AgentError::ConfigError(format!("reading {}: {e}", path.display())))?;
+
+            let template: TemplateFile = match extension {
+                "toml" => toml::from_str(&contents)
+                    .map_err(|e| AgentError::ConfigError(format!("parsing {}: {e}", path.display())))?,
+                "yaml" | "yml" => serde_yaml::from_str(&contents)
+                    .map_err(|e| AgentError::ConfigError(format!("parsing {}: {e}", path.display())))?,
+                _ => continue,
+            };
+
+            registry.files.insert(name.to_string(), template);
+        }
+
+        Ok(registry)
+    }
+
+    /// Register a template programmatically, overriding any file-backed or
+    /// built-in template with the same name
+    pub fn register(&mut self, name: impl Into<String>, config: AgentConfig) {
+        self.files.insert(
+            name.into(),
+            TemplateFile {
+                model: config.model,
+                system_prompt: config.system_prompt,
+                sandbox_policy: match config.sandbox_policy {
+                    SandboxPolicy::DangerFullAccess => "danger_full_access".to_string(),
+                    SandboxPolicy::ReadOnly => "read_only".to_string(),
+                    SandboxPolicy::WorkspaceWrite => "workspace_write".to_string(),
+                },
+                max_turns: config.max_turns,
+                api_key_env: None,
+                tools: Vec::new(),
+                metadata: serde_json::Value::Null,
+            },
+        );
+    }
+
+    /// List every template name known to this registry, file-backed and
+    /// built-in, with no duplicates
+    pub fn list(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.files.keys().cloned().collect();
+        for builtin in BUILTIN_TEMPLATE_NAMES {
+            if !names.iter().any(|n| n == builtin) {
+                names.push(builtin.to_string());
+            }
+        }
+        names.sort();
+        names
+    }
+
+    /// Resolve a template by name: a file-backed or programmatically
+    /// registered template takes priority, falling back to the matching
+    /// built-in template function
+    pub fn get(&self, name: &str) -> Result<AgentConfig, AgentError> {
+        self.get_with_context(name, &HashMap::new())
+    }
+
+    /// Resolve a template by name like [`Self::get`], substituting
+    /// `{{variable}}` placeholders in its system prompt from `context`
+    pub fn get_with_context(
+        &self,
+        name: &str,
+        context: &HashMap<String, String>,
+    ) -> Result<AgentConfig, AgentError> {
+        if let Some(file) = self.files.get(name) {
+            return file.clone().into_config_with_context(context);
+        }
+
+        match name {
+            "python_developer" => Ok(templates::python_developer()),
+            "code_reviewer" => Ok(templates::code_reviewer()),
+            "documentation_writer" => Ok(templates::documentation_writer()),
+            "data_analyst" => Ok(templates::data_analyst()),
+            "devops_engineer" => Ok(templates::devops_engineer()),
+            "web_developer" => Ok(templates::web_developer()),
+            "security_analyst" => Ok(templates::security_analyst()),
+            "test_engineer" => Ok(templates::test_engineer()),
+            other => Err(AgentError::ConfigError(format!("unknown template {other:?}"))),
+        }
+    }
+}
+
+#[cfg(feature = "templates")]
+const BUILTIN_TEMPLATE_NAMES: &[&str] = &[
+    "python_developer",
+    "code_reviewer",
+    "documentation_writer",
+    "data_analyst",
+    "devops_engineer",
+    "web_developer",
+    "security_analyst",
+    "test_engineer",
+];
 
 /// Pre-configured agent templates
 #[cfg(feature = "templates")]
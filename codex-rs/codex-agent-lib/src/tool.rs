@@ -4,6 +4,7 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::future::Future;
 use std::pin::Pin;
+use tokio::sync::mpsc;
 
 /// Represents a tool that can be called by the agent
 #[derive(Debug, Clone)]
@@ -68,6 +69,127 @@ impl ToolResult {
     }
 }
 
+/// A single frame of a streaming tool invocation
+///
+/// A long-running tool (a test runner, a build watcher) is handed an
+/// `mpsc::Sender<ToolChunk>` instead of returning a single [`ToolResult`],
+/// and pushes frames as they become available. The channel is bounded, so a
+/// slow consumer naturally applies backpressure to the tool's producer side.
+#[derive(Debug, Clone)]
+pub enum ToolChunk {
+    /// A chunk of standard output, tagged with a monotonically increasing
+    /// sequence number so the consumer can detect gaps or reorder frames
+    Stdout { seq: u64, data: String },
+
+    /// A chunk of standard error, tagged the same way as `Stdout`
+    Stderr { seq: u64, data: String },
+
+    /// The tool finished successfully; no further chunks will follow
+    Done(ToolResult),
+
+    /// The tool failed; no further chunks will follow
+    Error(String),
+
+    /// Out-of-band signal telling the consumer to collapse the stream now,
+    /// without waiting for a `Done`/`Error` frame (e.g. the turn was cancelled)
+    Close,
+}
+
+/// Best-effort parse of an in-progress (possibly truncated) JSON fragment,
+/// for previewing a tool call's arguments as they stream in rather than
+/// waiting for the model to finish emitting them (see
+/// [`crate::message::OutputData::ToolArgsDelta`]).
+///
+/// Closes any open strings, objects, and arrays (innermost first, in the
+/// reverse of the order they were opened) and trims a trailing comma, then hands the result to
+/// `serde_json::from_str`. Returns `None` if the fragment still doesn't
+/// parse after repair (e.g. it was truncated mid-escape or mid-number);
+/// callers should fall back to the last successful parse in that case.
+pub fn repair_partial_json(fragment: &str) -> Option<serde_json::Value> {
+    let mut repaired = String::with_capacity(fragment.len() + 8);
+    let mut stack = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for c in fragment.chars() {
+        if in_string {
+            repaired.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                repaired.push(c);
+            }
+            '{' | '[' => {
+                stack.push(c);
+                repaired.push(c);
+            }
+            '}' | ']' => {
+                stack.pop();
+                repaired.push(c);
+            }
+            _ => repaired.push(c),
+        }
+    }
+
+    if in_string {
+        repaired.push('"');
+    }
+
+    while let Some(c) = repaired.trim_end().chars().last() {
+        if c == ',' {
+            let trimmed = repaired.trim_end();
+            repaired.truncate(trimmed.len() - 1);
+        } else {
+            break;
+        }
+    }
+
+    for open in stack.into_iter().rev() {
+        repaired.push(if open == '{' { '}' } else { ']' });
+    }
+
+    serde_json::from_str(&repaired).ok()
+}
+
+/// Default capacity for a tool's streaming response channel
+pub const DEFAULT_STREAM_CAPACITY: usize = 32;
+
+/// Create a bounded response channel for a single streaming tool call
+///
+/// Each invocation gets its own channel; the returned sender applies
+/// backpressure once `capacity` unconsumed chunks are buffered.
+pub fn tool_stream_channel(
+    capacity: usize,
+) -> (mpsc::Sender<ToolChunk>, mpsc::Receiver<ToolChunk>) {
+    mpsc::channel(capacity)
+}
+
+/// Trait for tools that stream their output incrementally instead of
+/// returning a single [`ToolResult`]
+///
+/// Implementations push [`ToolChunk`]s to `chunks` as output becomes
+/// available and terminate the stream with exactly one `Done` or `Error`
+/// frame (a `Close` frame may also arrive out-of-band if the caller wants to
+/// abandon the stream early).
+pub trait StreamingToolHandler: Send + Sync {
+    /// Execute the tool, streaming output frames through `chunks`
+    fn execute_streaming(
+        &self,
+        arguments: serde_json::Value,
+        chunks: mpsc::Sender<ToolChunk>,
+    ) -> Pin<Box<dyn Future<Output = Result<(), crate::error::AgentError>> + Send>>;
+}
+
 /// Trait for custom tool implementations
 ///
 /// This trait allows users to implement custom tools that can be used by the agent.
@@ -75,12 +197,380 @@ impl ToolResult {
 #[allow(dead_code)]
 pub trait ToolHandler: Send + Sync {
     /// Execute the tool with the given arguments
+    ///
+    /// `context` is the turn's shared [`crate::ProjectContext`] handle;
+    /// context-gathering tools should register what they touched there
+    /// (via `register_file`/`register_worktree_summary`/
+    /// `register_diagnostic`) instead of folding it into the human-visible
+    /// [`ToolResult`], so duplicate reports across tools collapse into one
+    /// system block per turn.
     fn execute(
         &self,
         arguments: serde_json::Value,
+        context: &crate::ProjectContext,
     ) -> Pin<Box<dyn Future<Output = Result<ToolResult, crate::error::AgentError>> + Send>>;
 }
 
+/// Adapts a [`crate::config::CustomToolHandler`] closure (as used by
+/// [`crate::ToolConfig::Custom`]) into a [`ToolHandler`], so a custom tool
+/// can be registered into a [`ToolRegistry`] via
+/// [`crate::ToolConfig::register_into`] and gated/cached the same way as
+/// any other [`ToolHandler`]. Ignores `context`, since a `CustomToolHandler`
+/// only ever sees its arguments.
+struct CustomToolAdapter(crate::config::CustomToolHandler);
+
+impl ToolHandler for CustomToolAdapter {
+    fn execute(
+        &self,
+        arguments: serde_json::Value,
+        _context: &crate::ProjectContext,
+    ) -> Pin<Box<dyn Future<Output = Result<ToolResult, crate::error::AgentError>> + Send>> {
+        let handler = self.0.clone();
+        Box::pin(async move {
+            Ok(match handler(arguments).await {
+                Ok(output) => ToolResult::success(output),
+                Err(error) => ToolResult::failure(error),
+            })
+        })
+    }
+}
+
+/// Build a [`ToolHandler`] from a [`crate::config::CustomToolHandler`]
+/// closure; see [`CustomToolAdapter`]
+pub(crate) fn custom_tool_handler(
+    handler: crate::config::CustomToolHandler,
+) -> std::sync::Arc<dyn ToolHandler> {
+    std::sync::Arc::new(CustomToolAdapter(handler))
+}
+
+/// Default cap on how many tool calls [`execute_tool_calls`] runs at once,
+/// independent of whatever [`num_cpus::get()`] reports -- a turn with
+/// dozens of independent tool calls shouldn't spawn dozens of subprocesses
+/// or HTTP requests simultaneously
+pub const DEFAULT_TOOL_CONCURRENCY: usize = 8;
+
+/// Default per-call timeout applied by [`execute_tool_calls`]
+pub const DEFAULT_TOOL_CALL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// Registry of this crate's own [`ToolHandler`] implementations, keyed by
+/// tool name, consulted by [`execute_tool_calls`] to find each call's
+/// handler and to decide which calls require approval
+///
+/// This only covers tools invoked through this crate's [`ToolHandler`]
+/// extension point; tool calls dispatched through `codex_core`'s MCP/bash
+/// event loop never reach it (see [`ToolCallCache`]'s doc comment).
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    handlers: std::collections::HashMap<String, std::sync::Arc<dyn ToolHandler>>,
+    requires_approval: std::collections::HashSet<String>,
+}
+
+impl ToolRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `handler` to run calls to `tool`, noting whether it
+    /// requires approval before running
+    pub fn register(&mut self, tool: &Tool, handler: std::sync::Arc<dyn ToolHandler>) {
+        if tool.requires_approval {
+            self.requires_approval.insert(tool.name.clone());
+        }
+        self.handlers.insert(tool.name.clone(), handler);
+    }
+
+    /// Whether `tool_name` was registered with `requires_approval: true`
+    pub fn requires_approval(&self, tool_name: &str) -> bool {
+        self.requires_approval.contains(tool_name)
+    }
+}
+
+/// Decides whether a tool call gated by [`ToolRegistry::requires_approval`]
+/// is allowed to run, consulted by [`execute_tool_calls`]/
+/// [`execute_tool_calls_cached`] whenever the caller's
+/// [`crate::config::ApprovalPolicy`] isn't
+/// [`crate::config::ApprovalPolicy::Never`]
+///
+/// `crate::config::ApprovalPolicy`'s other variants (`OnFailure`,
+/// `OnRequest`, `UnlessTrusted`) distinguish *when* `codex_core` asks for its
+/// own built-in bash/patch tools; a caller-registered [`ToolHandler`] has no
+/// equivalent notion of a sandboxed failure or a "trusted" command, so here
+/// they all collapse to the same rule: ask before every `requires_approval`
+/// call. Only `Never` skips asking entirely.
+pub trait ApprovalHandler: Send + Sync {
+    /// Return `true` to allow `call` to run, `false` to deny it
+    fn approve(&self, call: &ToolCall) -> Pin<Box<dyn Future<Output = bool> + Send>>;
+}
+
+/// An [`ApprovalHandler`] that denies every call -- the safe default for a
+/// caller that hasn't wired up an interactive prompt yet
+pub struct DenyAll;
+
+impl ApprovalHandler for DenyAll {
+    fn approve(&self, _call: &ToolCall) -> Pin<Box<dyn Future<Output = bool> + Send>> {
+        Box::pin(async { false })
+    }
+}
+
+/// Run a batch of tool calls on a bounded concurrent pool, collecting
+/// results back in request order so the transcript stays deterministic
+///
+/// Calls whose tool was registered with `requires_approval: true` are
+/// excluded from the concurrent batch and run sequentially afterward. Each
+/// one is first checked against `approval_policy`/`approver` -- see
+/// [`ApprovalHandler`] -- and, if denied, surfaces as a
+/// [`ToolResult::failure`] instead of running. Each call is bounded by
+/// `timeout`, and no new call is dispatched once `stop_flag` is set (e.g. by
+/// [`crate::AgentController::stop`]) -- calls skipped that way surface as
+/// a [`ToolResult::failure`] rather than panicking or hanging the batch.
+/// `context` is shared across every call in the batch, the same
+/// [`crate::ProjectContext`] handle a caller would pass to a single
+/// [`ToolHandler::execute`] call.
+pub async fn execute_tool_calls(
+    registry: &ToolRegistry,
+    calls: Vec<ToolCall>,
+    concurrency: usize,
+    timeout: std::time::Duration,
+    stop_flag: &std::sync::atomic::AtomicBool,
+    context: &crate::ProjectContext,
+    approval_policy: crate::config::ApprovalPolicy,
+    approver: &dyn ApprovalHandler,
+) -> Vec<ToolResult> {
+    use std::sync::atomic::Ordering;
+
+    let mut results: Vec<Option<ToolResult>> = vec![None; calls.len()];
+    let (parallel, sequential): (Vec<_>, Vec<_>) = calls
+        .into_iter()
+        .enumerate()
+        .partition(|(_, call)| !registry.requires_approval(&call.tool_name));
+
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(concurrency.max(1)));
+    let mut tasks = tokio::task::JoinSet::new();
+
+    for (index, call) in parallel {
+        if stop_flag.load(Ordering::SeqCst) {
+            results[index] = Some(ToolResult::failure("cancelled before execution"));
+            continue;
+        }
+        let Some(handler) = registry.handlers.get(&call.tool_name).cloned() else {
+            results[index] = Some(ToolResult::failure(format!(
+                "no handler registered for tool `{}`",
+                call.tool_name
+            )));
+            continue;
+        };
+        let semaphore = semaphore.clone();
+        let context = context.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let outcome =
+                tokio::time::timeout(timeout, handler.execute(call.arguments, &context)).await;
+            (index, flatten_outcome(outcome))
+        });
+    }
+
+    while let Some(joined) = tasks.join_next().await {
+        if let Ok((index, result)) = joined {
+            results[index] = Some(result);
+        }
+    }
+
+    for (index, call) in sequential {
+        if stop_flag.load(Ordering::SeqCst) {
+            results[index] = Some(ToolResult::failure("cancelled before execution"));
+            continue;
+        }
+        if approval_policy != crate::config::ApprovalPolicy::Never {
+            match tokio::time::timeout(timeout, approver.approve(&call)).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    results[index] = Some(ToolResult::failure(format!(
+                        "tool `{}` requires approval and was denied",
+                        call.tool_name
+                    )));
+                    continue;
+                }
+                Err(_) => {
+                    results[index] = Some(ToolResult::failure(format!(
+                        "approval for tool `{}` timed out",
+                        call.tool_name
+                    )));
+                    continue;
+                }
+            }
+        }
+        let Some(handler) = registry.handlers.get(&call.tool_name).cloned() else {
+            results[index] = Some(ToolResult::failure(format!(
+                "no handler registered for tool `{}`",
+                call.tool_name
+            )));
+            continue;
+        };
+        let outcome =
+            tokio::time::timeout(timeout, handler.execute(call.arguments, context)).await;
+        results[index] = Some(flatten_outcome(outcome));
+    }
+
+    results
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| ToolResult::failure("internal error: no result produced")))
+        .collect()
+}
+
+/// [`execute_tool_calls`], but reusing `cache`'s memoized result for any
+/// call whose tool was marked cacheable and whose `(tool_name,
+/// canonical_json(arguments))` has already been seen this session -- so a
+/// multi-step tool loop doesn't re-run an identical pure/read-only call.
+/// Only the calls that miss the cache are actually dispatched; those of
+/// their fresh results that succeeded are folded back into `cache` (a
+/// failure is not cached), and every result is returned in the original
+/// request order.
+pub async fn execute_tool_calls_cached(
+    registry: &ToolRegistry,
+    calls: Vec<ToolCall>,
+    concurrency: usize,
+    timeout: std::time::Duration,
+    stop_flag: &std::sync::atomic::AtomicBool,
+    context: &crate::ProjectContext,
+    cache: &mut ToolCallCache,
+    approval_policy: crate::config::ApprovalPolicy,
+    approver: &dyn ApprovalHandler,
+) -> Vec<ToolResult> {
+    let mut results: Vec<Option<ToolResult>> = vec![None; calls.len()];
+    let mut misses = Vec::new();
+
+    for (index, call) in calls.into_iter().enumerate() {
+        match cache.get(&call.tool_name, &call.arguments) {
+            Some(cached) => results[index] = Some(cached.clone()),
+            None => misses.push((index, call)),
+        }
+    }
+
+    let (miss_indices, miss_calls): (Vec<_>, Vec<_>) = misses.into_iter().unzip();
+    let fresh = execute_tool_calls(
+        registry,
+        miss_calls.clone(),
+        concurrency,
+        timeout,
+        stop_flag,
+        context,
+        approval_policy,
+        approver,
+    )
+    .await;
+
+    for ((index, call), result) in miss_indices.into_iter().zip(miss_calls).zip(fresh) {
+        // Only a successful execution is memoized: an approval denial or a
+        // transient failure (timeout, cancellation) isn't the tool's actual
+        // value, and caching it would permanently poison the entry -- a
+        // later call with the same arguments would replay the old denial
+        // instead of asking the approver (or retrying) again.
+        if result.success {
+            cache.insert(&call.tool_name, &call.arguments, result.clone());
+        }
+        results[index] = Some(result);
+    }
+
+    results
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| ToolResult::failure("internal error: no result produced")))
+        .collect()
+}
+
+/// Flatten a timed-out/erred/succeeded tool execution into a [`ToolResult`],
+/// shared by both the concurrent and sequential legs of [`execute_tool_calls`]
+fn flatten_outcome(
+    outcome: Result<
+        std::result::Result<ToolResult, crate::error::AgentError>,
+        tokio::time::error::Elapsed,
+    >,
+) -> ToolResult {
+    match outcome {
+        Ok(Ok(result)) => result,
+        Ok(Err(err)) => ToolResult::failure(err.to_string()),
+        Err(_) => ToolResult::failure("tool call timed out"),
+    }
+}
+
+/// Canonicalize a tool call's arguments into a stable cache key component
+///
+/// Recursively sorts object keys (through a `BTreeMap`) so two
+/// semantically identical argument payloads with differently ordered keys
+/// produce the same key.
+fn canonicalize_arguments(arguments: &serde_json::Value) -> String {
+    fn sort_keys(value: &serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let sorted: std::collections::BTreeMap<&String, serde_json::Value> =
+                    map.iter().map(|(k, v)| (k, sort_keys(v))).collect();
+                serde_json::to_value(sorted).unwrap_or(serde_json::Value::Null)
+            }
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.iter().map(sort_keys).collect())
+            }
+            other => other.clone(),
+        }
+    }
+    sort_keys(arguments).to_string()
+}
+
+/// Per-session memoization cache for pure/read-only tool calls
+///
+/// Keyed on `(tool name, canonicalized arguments)`. Opt-in per tool via
+/// [`Self::mark_cacheable`]: stateful tools (file writers, shell commands
+/// with side effects) must never be memoized, so lookups and inserts are a
+/// no-op for any tool name that hasn't been explicitly marked cacheable.
+///
+/// This applies to tools invoked through this crate's own [`ToolHandler`]
+/// trait; MCP/bash tool calls dispatched through `codex_core` are executed
+/// before this crate observes them and can't be intercepted here.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolCallCache {
+    cacheable_tools: std::collections::HashSet<String>,
+    #[serde(default)]
+    entries: std::collections::HashMap<String, ToolResult>,
+}
+
+impl ToolCallCache {
+    /// Create an empty cache with no tools marked cacheable
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opt a tool into memoization; only pure/read-only tools should be
+    /// marked cacheable
+    pub fn mark_cacheable(&mut self, tool_name: impl Into<String>) {
+        self.cacheable_tools.insert(tool_name.into());
+    }
+
+    /// Whether `tool_name` has been opted into memoization
+    pub fn is_cacheable(&self, tool_name: &str) -> bool {
+        self.cacheable_tools.contains(tool_name)
+    }
+
+    /// Look up a previously cached result for this tool call, if the tool
+    /// is cacheable and this exact call has been made before
+    pub fn get(&self, tool_name: &str, arguments: &serde_json::Value) -> Option<&ToolResult> {
+        if !self.is_cacheable(tool_name) {
+            return None;
+        }
+        self.entries.get(&Self::key(tool_name, arguments))
+    }
+
+    /// Store a tool call's result for future reuse, if the tool is cacheable
+    pub fn insert(&mut self, tool_name: &str, arguments: &serde_json::Value, result: ToolResult) {
+        if !self.is_cacheable(tool_name) {
+            return;
+        }
+        self.entries.insert(Self::key(tool_name, arguments), result);
+    }
+
+    fn key(tool_name: &str, arguments: &serde_json::Value) -> String {
+        format!("{tool_name}\u{0}{}", canonicalize_arguments(arguments))
+    }
+}
+
 /// Example: Built-in bash tool handler
 ///
 /// This is an example implementation showing how to create a tool handler.
@@ -95,6 +585,7 @@ impl ToolHandler for BashToolHandler {
     fn execute(
         &self,
         _arguments: serde_json::Value,
+        _context: &crate::ProjectContext,
     ) -> Pin<Box<dyn Future<Output = Result<ToolResult, crate::error::AgentError>> + Send>> {
         Box::pin(async move {
             // Implementation would integrate with codex_core's bash execution
@@ -115,6 +606,7 @@ impl ToolHandler for WebSearchToolHandler {
     fn execute(
         &self,
         _arguments: serde_json::Value,
+        _context: &crate::ProjectContext,
     ) -> Pin<Box<dyn Future<Output = Result<ToolResult, crate::error::AgentError>> + Send>> {
         Box::pin(async move {
             // Implementation would integrate with web search functionality
@@ -122,3 +614,645 @@ impl ToolHandler for WebSearchToolHandler {
         })
     }
 }
+
+/// An indexed chunk of a source file, along with its embedding so a
+/// restart doesn't need to re-embed chunks whose `content_hash` hasn't
+/// changed since the last index
+#[cfg(feature = "rag")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedChunk {
+    /// Path relative to the indexed root
+    path: std::path::PathBuf,
+    /// 1-indexed, inclusive start line
+    start_line: usize,
+    /// 1-indexed, inclusive end line
+    end_line: usize,
+    /// Hash of `text`, used to skip re-embedding unchanged chunks
+    content_hash: u64,
+    text: String,
+    embedding: Vec<f32>,
+}
+
+/// Built-in tool letting the agent search the workspace by meaning rather
+/// than exact string match, complementing grep-style tools for queries
+/// like "find the code that validates API keys"
+///
+/// On first use, walks `root`, splits source files into chunks at
+/// blank-line boundaries (capped at `max_chunk_lines`), embeds each chunk
+/// via `embedder`, and persists the `(path, line range, content hash,
+/// embedding)` entries to `index_path`; subsequent calls only re-embed
+/// chunks whose hash changed since the last run. `execute`'s `query`
+/// argument is embedded and compared against the index by cosine
+/// similarity, returning the top matches as path/line-range/snippet
+/// results.
+#[cfg(feature = "rag")]
+pub struct ProjectIndexToolHandler {
+    root: std::path::PathBuf,
+    index_path: std::path::PathBuf,
+    embedder: std::sync::Arc<dyn crate::rag::Embedder>,
+    max_chunk_lines: usize,
+}
+
+#[cfg(feature = "rag")]
+impl ProjectIndexToolHandler {
+    /// Number of matches returned when a call omits `limit`
+    pub const DEFAULT_LIMIT: usize = 10;
+
+    /// Create a handler that indexes `root` into `index_path`, embedding
+    /// chunks through `embedder`
+    pub fn new(
+        root: impl Into<std::path::PathBuf>,
+        index_path: impl Into<std::path::PathBuf>,
+        embedder: std::sync::Arc<dyn crate::rag::Embedder>,
+    ) -> Self {
+        Self {
+            root: root.into(),
+            index_path: index_path.into(),
+            embedder,
+            max_chunk_lines: 60,
+        }
+    }
+
+    /// JSON schema for this tool's `execute` arguments, for wiring into a
+    /// [`Tool`] definition alongside this handler
+    pub fn parameters() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Natural-language description of the code to find",
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of matches to return",
+                },
+            },
+            "required": ["query"],
+        })
+    }
+
+    async fn load_index(&self) -> Vec<IndexedChunk> {
+        let Ok(json) = tokio::fs::read_to_string(&self.index_path).await else {
+            return Vec::new();
+        };
+        serde_json::from_str(&json).unwrap_or_default()
+    }
+
+    async fn save_index(&self, chunks: &[IndexedChunk]) -> Result<(), crate::error::AgentError> {
+        let json = serde_json::to_string_pretty(chunks)
+            .map_err(|e| crate::error::AgentError::InternalError(e.to_string()))?;
+        if let Some(parent) = self.index_path.parent() {
+            let _ = tokio::fs::create_dir_all(parent).await;
+        }
+        tokio::fs::write(&self.index_path, json)
+            .await
+            .map_err(|e| crate::error::AgentError::InternalError(e.to_string()))
+    }
+
+    /// Re-walk `root`, reusing the previous embedding for any chunk whose
+    /// content hash is unchanged, embedding only what's new or modified,
+    /// then persist the refreshed index
+    async fn refresh_index(&self) -> Result<Vec<IndexedChunk>, crate::error::AgentError> {
+        let previous = self.load_index().await;
+        let mut previous_by_key: std::collections::HashMap<(std::path::PathBuf, u64), &IndexedChunk> =
+            std::collections::HashMap::new();
+        for chunk in &previous {
+            previous_by_key.insert((chunk.path.clone(), chunk.content_hash), chunk);
+        }
+
+        let mut refreshed = Vec::new();
+        for path in walk_source_files(&self.root) {
+            let Ok(text) = tokio::fs::read_to_string(&path).await else {
+                continue;
+            };
+            let relative = path.strip_prefix(&self.root).unwrap_or(&path).to_path_buf();
+            for (start_line, end_line, chunk_text) in
+                chunk_by_blank_lines(&text, self.max_chunk_lines)
+            {
+                let content_hash = hash_str(&chunk_text);
+                if let Some(existing) = previous_by_key.get(&(relative.clone(), content_hash)) {
+                    refreshed.push((*existing).clone());
+                    continue;
+                }
+                let embedding = self.embedder.embed(&chunk_text).await?;
+                refreshed.push(IndexedChunk {
+                    path: relative.clone(),
+                    start_line,
+                    end_line,
+                    content_hash,
+                    text: chunk_text,
+                    embedding,
+                });
+            }
+        }
+
+        self.save_index(&refreshed).await?;
+        Ok(refreshed)
+    }
+}
+
+#[cfg(feature = "rag")]
+impl ToolHandler for ProjectIndexToolHandler {
+    fn execute(
+        &self,
+        arguments: serde_json::Value,
+        context: &crate::ProjectContext,
+    ) -> Pin<Box<dyn Future<Output = Result<ToolResult, crate::error::AgentError>> + Send>> {
+        let root = self.root.clone();
+        let index_path = self.index_path.clone();
+        let embedder = self.embedder.clone();
+        let max_chunk_lines = self.max_chunk_lines;
+        let context = context.clone();
+
+        Box::pin(async move {
+            let Some(query) = arguments.get("query").and_then(|v| v.as_str()) else {
+                return Ok(ToolResult::failure("missing required `query` argument"));
+            };
+            let limit = arguments
+                .get("limit")
+                .and_then(|v| v.as_u64())
+                .map(|v| v as usize)
+                .unwrap_or(Self::DEFAULT_LIMIT);
+
+            let handler = ProjectIndexToolHandler {
+                root,
+                index_path,
+                embedder,
+                max_chunk_lines,
+            };
+            let chunks = handler.refresh_index().await?;
+            let query_embedding = handler.embedder.embed(query).await?;
+
+            let mut scored: Vec<(f32, &IndexedChunk)> = chunks
+                .iter()
+                .map(|chunk| {
+                    (
+                        crate::rag::cosine_similarity(&query_embedding, &chunk.embedding),
+                        chunk,
+                    )
+                })
+                .collect();
+            scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+            scored.truncate(limit);
+
+            let output = scored
+                .iter()
+                .map(|(score, chunk)| {
+                    format!(
+                        "{}:{}-{} ({score:.3})\n{}",
+                        chunk.path.display(),
+                        chunk.start_line,
+                        chunk.end_line,
+                        chunk.text
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n");
+
+            context
+                .register_worktree_summary(format!(
+                    "semantic search `{query}` ({} matches)",
+                    scored.len()
+                ))
+                .await;
+
+            Ok(ToolResult::success(output))
+        })
+    }
+}
+
+/// Walk `root` recursively, skipping `.git` and `target` directories,
+/// collecting every regular file found
+#[cfg(feature = "rag")]
+fn walk_source_files(root: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                let skip = matches!(
+                    path.file_name().and_then(|n| n.to_str()),
+                    Some(".git") | Some("target")
+                );
+                if !skip {
+                    stack.push(path);
+                }
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files
+}
+
+/// Split `text` into chunks at blank-line boundaries, capping each chunk
+/// at `max_lines` so a single huge function/paragraph doesn't become one
+/// unbounded chunk; returns `(start_line, end_line, text)` triples, both
+/// 1-indexed and inclusive
+#[cfg(feature = "rag")]
+fn chunk_by_blank_lines(text: &str, max_lines: usize) -> Vec<(usize, usize, String)> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut chunks = Vec::new();
+    let mut start = 0usize;
+    let mut current: Vec<&str> = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() && !current.is_empty() {
+            chunks.push((start + 1, i, current.join("\n")));
+            current.clear();
+            start = i + 1;
+            continue;
+        }
+        if current.len() >= max_lines.max(1) {
+            chunks.push((start + 1, i, current.join("\n")));
+            current.clear();
+            start = i;
+        }
+        current.push(line);
+    }
+    if !current.is_empty() {
+        chunks.push((start + 1, lines.len(), current.join("\n")));
+    }
+    chunks
+}
+
+/// Cheap, stable content hash used for change detection -- not
+/// cryptographic, it only needs to notice "this chunk's text changed
+/// since the last index"
+#[cfg(feature = "rag")]
+fn hash_str(text: &str) -> u64 {
+    use std::hash::Hash;
+    use std::hash::Hasher;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repair_closes_an_open_string_and_object() {
+        let value = repair_partial_json(r#"{"command":"ls -l"#).unwrap();
+        assert_eq!(value["command"], "ls -l");
+    }
+
+    #[test]
+    fn repair_closes_nested_arrays_and_objects() {
+        let value = repair_partial_json(r#"{"files":["a.rs","b.rs"#).unwrap();
+        assert_eq!(value["files"][0], "a.rs");
+        assert_eq!(value["files"][1], "b.rs");
+    }
+
+    #[test]
+    fn repair_trims_a_trailing_comma() {
+        let value = repair_partial_json(r#"{"a":1,"#).unwrap();
+        assert_eq!(value["a"], 1);
+    }
+
+    #[test]
+    fn repair_returns_none_for_unrecoverable_fragments() {
+        assert!(repair_partial_json(r#"{"a":"#).is_none());
+    }
+
+    #[test]
+    fn repair_handles_a_complete_value_unchanged() {
+        let value = repair_partial_json(r#"{"a":1}"#).unwrap();
+        assert_eq!(value["a"], 1);
+    }
+
+    struct EchoToolHandler;
+
+    impl ToolHandler for EchoToolHandler {
+        fn execute(
+            &self,
+            arguments: serde_json::Value,
+            context: &crate::ProjectContext,
+        ) -> Pin<Box<dyn Future<Output = Result<ToolResult, crate::error::AgentError>> + Send>>
+        {
+            let context = context.clone();
+            Box::pin(async move {
+                context
+                    .register_file("echo.txt", arguments.to_string())
+                    .await;
+                Ok(ToolResult::success(arguments.to_string()))
+            })
+        }
+    }
+
+    fn echo_registry(tool_name: &str, requires_approval: bool) -> ToolRegistry {
+        let mut registry = ToolRegistry::new();
+        registry.register(
+            &Tool {
+                name: tool_name.to_string(),
+                description: String::new(),
+                parameters: serde_json::Value::Null,
+                requires_approval,
+            },
+            std::sync::Arc::new(EchoToolHandler),
+        );
+        registry
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_preserves_request_order() {
+        let registry = echo_registry("echo", false);
+        let calls = vec![
+            ToolCall {
+                id: "1".to_string(),
+                tool_name: "echo".to_string(),
+                arguments: serde_json::json!(1),
+            },
+            ToolCall {
+                id: "2".to_string(),
+                tool_name: "echo".to_string(),
+                arguments: serde_json::json!(2),
+            },
+        ];
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let context = crate::ProjectContext::new();
+
+        let results = execute_tool_calls(
+            &registry,
+            calls,
+            DEFAULT_TOOL_CONCURRENCY,
+            DEFAULT_TOOL_CALL_TIMEOUT,
+            &stop_flag,
+            &context,
+            crate::config::ApprovalPolicy::Never,
+            &DenyAll,
+        )
+        .await;
+
+        assert_eq!(results[0].output, "1");
+        assert_eq!(results[1].output, "2");
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_shares_one_project_context_across_the_batch() {
+        let registry = echo_registry("echo", false);
+        let calls = vec![ToolCall {
+            id: "1".to_string(),
+            tool_name: "echo".to_string(),
+            arguments: serde_json::json!(1),
+        }];
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let context = crate::ProjectContext::new();
+
+        execute_tool_calls(
+            &registry,
+            calls,
+            DEFAULT_TOOL_CONCURRENCY,
+            DEFAULT_TOOL_CALL_TIMEOUT,
+            &stop_flag,
+            &context,
+            crate::config::ApprovalPolicy::Never,
+            &DenyAll,
+        )
+        .await;
+
+        assert!(!context.is_empty().await);
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_fails_unregistered_tools_without_panicking() {
+        let registry = ToolRegistry::new();
+        let calls = vec![ToolCall {
+            id: "1".to_string(),
+            tool_name: "missing".to_string(),
+            arguments: serde_json::Value::Null,
+        }];
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let context = crate::ProjectContext::new();
+
+        let results = execute_tool_calls(
+            &registry,
+            calls,
+            DEFAULT_TOOL_CONCURRENCY,
+            DEFAULT_TOOL_CALL_TIMEOUT,
+            &stop_flag,
+            &context,
+            crate::config::ApprovalPolicy::Never,
+            &DenyAll,
+        )
+        .await;
+
+        assert!(!results[0].success);
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_runs_approval_gated_tools_without_asking_under_never_policy() {
+        let registry = echo_registry("dangerous", true);
+        let calls = vec![ToolCall {
+            id: "1".to_string(),
+            tool_name: "dangerous".to_string(),
+            arguments: serde_json::json!("rm -rf"),
+        }];
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let context = crate::ProjectContext::new();
+
+        let results = execute_tool_calls(
+            &registry,
+            calls,
+            DEFAULT_TOOL_CONCURRENCY,
+            DEFAULT_TOOL_CALL_TIMEOUT,
+            &stop_flag,
+            &context,
+            crate::config::ApprovalPolicy::Never,
+            &DenyAll,
+        )
+        .await;
+
+        assert_eq!(results[0].output, "\"rm -rf\"");
+    }
+
+    struct AllowAll;
+
+    impl ApprovalHandler for AllowAll {
+        fn approve(&self, _call: &ToolCall) -> Pin<Box<dyn Future<Output = bool> + Send>> {
+            Box::pin(async { true })
+        }
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_denies_an_approval_gated_call_when_the_approver_refuses() {
+        let registry = echo_registry("dangerous", true);
+        let calls = vec![ToolCall {
+            id: "1".to_string(),
+            tool_name: "dangerous".to_string(),
+            arguments: serde_json::json!("rm -rf"),
+        }];
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let context = crate::ProjectContext::new();
+
+        let results = execute_tool_calls(
+            &registry,
+            calls,
+            DEFAULT_TOOL_CONCURRENCY,
+            DEFAULT_TOOL_CALL_TIMEOUT,
+            &stop_flag,
+            &context,
+            crate::config::ApprovalPolicy::OnRequest,
+            &DenyAll,
+        )
+        .await;
+
+        assert!(!results[0].success);
+        assert!(
+            results[0]
+                .error
+                .as_deref()
+                .unwrap_or_default()
+                .contains("requires approval")
+        );
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_runs_an_approval_gated_call_when_the_approver_allows() {
+        let registry = echo_registry("dangerous", true);
+        let calls = vec![ToolCall {
+            id: "1".to_string(),
+            tool_name: "dangerous".to_string(),
+            arguments: serde_json::json!("rm -rf"),
+        }];
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let context = crate::ProjectContext::new();
+
+        let results = execute_tool_calls(
+            &registry,
+            calls,
+            DEFAULT_TOOL_CONCURRENCY,
+            DEFAULT_TOOL_CALL_TIMEOUT,
+            &stop_flag,
+            &context,
+            crate::config::ApprovalPolicy::OnRequest,
+            &AllowAll,
+        )
+        .await;
+
+        assert_eq!(results[0].output, "\"rm -rf\"");
+    }
+
+    #[tokio::test]
+    async fn custom_tool_config_registers_with_its_requires_approval() {
+        let tool_config = crate::config::ToolConfig::Custom {
+            name: "echo".to_string(),
+            description: String::new(),
+            parameters: serde_json::Value::Null,
+            handler: std::sync::Arc::new(|args: serde_json::Value| {
+                Box::pin(async move { Ok::<String, String>(args.to_string()) })
+                    as Pin<Box<dyn Future<Output = Result<String, String>> + Send>>
+            }),
+            requires_approval: true,
+        };
+        let mut registry = ToolRegistry::new();
+        tool_config.register_into(&mut registry);
+
+        assert!(registry.requires_approval("echo"));
+
+        let calls = vec![ToolCall {
+            id: "1".to_string(),
+            tool_name: "echo".to_string(),
+            arguments: serde_json::json!("hi"),
+        }];
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let context = crate::ProjectContext::new();
+
+        let results = execute_tool_calls(
+            &registry,
+            calls,
+            DEFAULT_TOOL_CONCURRENCY,
+            DEFAULT_TOOL_CALL_TIMEOUT,
+            &stop_flag,
+            &context,
+            crate::config::ApprovalPolicy::Never,
+            &DenyAll,
+        )
+        .await;
+
+        assert_eq!(results[0].output, "\"hi\"");
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_cached_reuses_a_prior_result() {
+        let registry = echo_registry("echo", false);
+        let mut cache = ToolCallCache::new();
+        cache.mark_cacheable("echo");
+        let stop_flag = std::sync::atomic::AtomicBool::new(false);
+        let context = crate::ProjectContext::new();
+
+        let first = execute_tool_calls_cached(
+            &registry,
+            vec![ToolCall {
+                id: "1".to_string(),
+                tool_name: "echo".to_string(),
+                arguments: serde_json::json!(1),
+            }],
+            DEFAULT_TOOL_CONCURRENCY,
+            DEFAULT_TOOL_CALL_TIMEOUT,
+            &stop_flag,
+            &context,
+            &mut cache,
+            crate::config::ApprovalPolicy::Never,
+            &DenyAll,
+        )
+        .await;
+        assert_eq!(first[0].output, "1");
+
+        assert!(cache.get("echo", &serde_json::json!(1)).is_some());
+
+        let second = execute_tool_calls_cached(
+            &registry,
+            vec![ToolCall {
+                id: "2".to_string(),
+                tool_name: "echo".to_string(),
+                arguments: serde_json::json!(1),
+            }],
+            DEFAULT_TOOL_CONCURRENCY,
+            DEFAULT_TOOL_CALL_TIMEOUT,
+            &stop_flag,
+            &context,
+            &mut cache,
+            crate::config::ApprovalPolicy::Never,
+            &DenyAll,
+        )
+        .await;
+
+        assert_eq!(second[0].output, "1");
+    }
+
+    #[cfg(feature = "rag")]
+    #[test]
+    fn chunk_by_blank_lines_splits_on_blank_lines() {
+        let text = "fn a() {}\n\nfn b() {}\n";
+        let chunks = chunk_by_blank_lines(text, 60);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0], (1, 1, "fn a() {}".to_string()));
+        assert_eq!(chunks[1], (3, 3, "fn b() {}".to_string()));
+    }
+
+    #[cfg(feature = "rag")]
+    #[test]
+    fn chunk_by_blank_lines_caps_chunk_size() {
+        let text = "a\nb\nc\nd\n";
+        let chunks = chunk_by_blank_lines(text, 2);
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].2, "a\nb");
+        assert_eq!(chunks[1].2, "c\nd");
+    }
+
+    #[cfg(feature = "rag")]
+    #[test]
+    fn hash_str_is_stable_and_distinguishes_content() {
+        assert_eq!(hash_str("fn a() {}"), hash_str("fn a() {}"));
+        assert_ne!(hash_str("fn a() {}"), hash_str("fn b() {}"));
+    }
+}
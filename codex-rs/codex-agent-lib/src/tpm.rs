@@ -0,0 +1,196 @@
+//! TPM-sealed storage for model API keys and MCP server credentials
+//!
+//! [`SecretStore`] sits in front of wherever an [`crate::AgentConfig`]'s
+//! `api_key` or an [`crate::McpServerConfig`]'s `env` entry gets its
+//! plaintext from. When a [`TpmDevice`] is available it seals secrets as a
+//! keyedHash object under the platform's storage root key (SRK) instead of
+//! holding them in plaintext config/env: create a primary storage root key
+//! under the owner hierarchy (once), then seal the secret bound to a PCR or
+//! password policy, persisting only the resulting public/private blob pair
+//! to disk. At startup the blobs are loaded back under the SRK and unsealed
+//! via a matching policy session, so the plaintext only ever exists in
+//! memory. This crate deliberately doesn't depend on a specific TPM binding
+//! (e.g. `tss-esapi`) -- [`TpmDevice`] is the narrow seal/unseal contract a
+//! host implements against whatever stack it links. Without a configured,
+//! available device, [`SecretStore`] still resolves `Env` and `Inline`
+//! locations, but a `Sealed` location fails with an error; it does not fall
+//! back to the env/config path on its own.
+
+#[cfg(feature = "tpm")]
+use crate::error::AgentError;
+#[cfg(feature = "tpm")]
+use crate::Result;
+#[cfg(feature = "tpm")]
+use serde::Deserialize;
+#[cfg(feature = "tpm")]
+use serde::Serialize;
+#[cfg(feature = "tpm")]
+use std::future::Future;
+#[cfg(feature = "tpm")]
+use std::path::Path;
+#[cfg(feature = "tpm")]
+use std::path::PathBuf;
+#[cfg(feature = "tpm")]
+use std::pin::Pin;
+#[cfg(feature = "tpm")]
+use std::sync::Arc;
+
+/// What an unseal request must satisfy before [`TpmDevice::unseal`] releases
+/// a secret
+#[cfg(feature = "tpm")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SealPolicy {
+    /// Bound to the current value of the given PCR index (e.g. a
+    /// firmware/bootloader measurement), so unsealing fails after a
+    /// platform state change
+    Pcr(u8),
+    /// Bound to a password-authorization policy
+    Password,
+}
+
+/// A sealed secret's on-disk representation: the TPM's public/private blob
+/// pair for a single keyedHash object, plus the policy it's bound to
+#[cfg(feature = "tpm")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SealedSecret {
+    /// Public portion of the sealed keyedHash object
+    pub public_blob: Vec<u8>,
+    /// Private (encrypted) portion of the sealed keyedHash object
+    pub private_blob: Vec<u8>,
+    /// Policy that must be satisfied to unseal this object
+    pub policy: SealPolicy,
+}
+
+/// Talks to a TPM 2.0 device's storage root key to seal/unseal secrets
+///
+/// Implementations own the actual create-primary / create / load / unseal
+/// call sequence against the owner hierarchy's SRK; this crate only needs
+/// the narrow contract below, so it isn't bound to one TPM crate.
+#[cfg(feature = "tpm")]
+pub trait TpmDevice: Send + Sync {
+    /// Create the SRK under the owner hierarchy if it doesn't already exist,
+    /// then seal `secret` as a keyedHash object bound to `policy`
+    fn seal(
+        &self,
+        secret: &[u8],
+        policy: SealPolicy,
+    ) -> Pin<Box<dyn Future<Output = Result<SealedSecret>> + Send>>;
+
+    /// Load `sealed`'s blobs under the SRK and unseal via a session
+    /// satisfying `sealed.policy`, recovering the original secret bytes
+    fn unseal(
+        &self,
+        sealed: &SealedSecret,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<u8>>> + Send>>;
+
+    /// Whether a usable TPM 2.0 device was found on this platform
+    fn is_available(&self) -> bool;
+}
+
+/// Where a credential's plaintext is ultimately recovered from
+#[cfg(feature = "tpm")]
+#[derive(Debug, Clone)]
+pub enum SecretLocation {
+    /// Read from the named environment variable, same as today's behavior
+    Env(String),
+    /// Already plaintext (e.g. typed directly into config); passed through
+    Inline(String),
+    /// A [`SealedSecret`] persisted as JSON at this path, to be unsealed via
+    /// the configured [`TpmDevice`]
+    Sealed(PathBuf),
+}
+
+/// Resolves [`SecretLocation`]s to plaintext: `Env` and `Inline` directly,
+/// `Sealed` by unsealing via a [`TpmDevice`]; a `Sealed` lookup fails with
+/// an error when no device is configured or available, with no fallback to
+/// the env/config path
+#[cfg(feature = "tpm")]
+pub struct SecretStore {
+    tpm: Option<Arc<dyn TpmDevice>>,
+}
+
+#[cfg(feature = "tpm")]
+impl SecretStore {
+    /// Create a store with no TPM backing; every [`SecretLocation::Sealed`]
+    /// lookup fails and only `Env`/`Inline` resolve
+    pub fn new() -> Self {
+        Self { tpm: None }
+    }
+
+    /// Create a store backed by `device`
+    pub fn with_device(device: Arc<dyn TpmDevice>) -> Self {
+        Self { tpm: Some(device) }
+    }
+
+    /// Whether this store has a usable TPM device configured
+    pub fn has_tpm(&self) -> bool {
+        self.tpm.as_ref().is_some_and(|tpm| tpm.is_available())
+    }
+
+    /// Seal `secret` with the configured device and persist the blobs as
+    /// JSON at `path`
+    pub async fn seal_to_disk(&self, secret: &str, path: &Path, policy: SealPolicy) -> Result<()> {
+        let tpm = self.tpm.as_ref().ok_or_else(|| {
+            AgentError::ConfigError("no TPM device configured; cannot seal secret".to_string())
+        })?;
+        if !tpm.is_available() {
+            return Err(AgentError::ConfigError(
+                "configured TPM device is not available on this platform".to_string(),
+            ));
+        }
+
+        let sealed = tpm.seal(secret.as_bytes(), policy).await?;
+        let json = serde_json::to_vec_pretty(&sealed)
+            .map_err(|e| AgentError::InternalError(e.to_string()))?;
+        tokio::fs::write(path, json)
+            .await
+            .map_err(|e| AgentError::InternalError(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Resolve `location` to a plaintext secret
+    ///
+    /// `Sealed` falls back to a [`AgentError::ConfigError`] rather than
+    /// silently returning an empty credential when no TPM is available --
+    /// callers on a TPM-less host should configure `Env`/`Inline` instead.
+    pub async fn resolve(&self, location: &SecretLocation) -> Result<String> {
+        match location {
+            SecretLocation::Inline(secret) => Ok(secret.clone()),
+            SecretLocation::Env(var) => std::env::var(var).map_err(|_| {
+                AgentError::ConfigError(format!("environment variable {var} is not set"))
+            }),
+            SecretLocation::Sealed(path) => self.unseal_from_disk(path).await,
+        }
+    }
+
+    async fn unseal_from_disk(&self, path: &Path) -> Result<String> {
+        let tpm = self.tpm.as_ref().ok_or_else(|| {
+            AgentError::ConfigError(
+                "no TPM device configured; falling back to env/config is the caller's \
+                 responsibility when SecretLocation::Sealed can't be unsealed"
+                    .to_string(),
+            )
+        })?;
+        if !tpm.is_available() {
+            return Err(AgentError::ConfigError(
+                "configured TPM device is not available on this platform".to_string(),
+            ));
+        }
+
+        let json = tokio::fs::read(path)
+            .await
+            .map_err(|e| AgentError::InternalError(e.to_string()))?;
+        let sealed: SealedSecret =
+            serde_json::from_slice(&json).map_err(|e| AgentError::InternalError(e.to_string()))?;
+        let secret = tpm.unseal(&sealed).await?;
+        String::from_utf8(secret)
+            .map_err(|e| AgentError::InternalError(format!("unsealed secret wasn't valid UTF-8: {e}")))
+    }
+}
+
+#[cfg(feature = "tpm")]
+impl Default for SecretStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
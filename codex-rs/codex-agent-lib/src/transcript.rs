@@ -0,0 +1,376 @@
+//! Rolling-file session transcript persistence
+//!
+//! Modeled on `tracing-appender`'s split between a rotating file and a
+//! non-blocking writer: [`TranscriptWriter`] owns a dedicated worker thread
+//! that does the actual file I/O (opening, rotating, flushing), while
+//! callers on the async side only ever push a [`TranscriptRecord`] onto a
+//! bounded channel without blocking (a record that finds it full is dropped
+//! and logged), so a slow disk never adds latency to the agent loop.
+//! [`TranscriptReader`] is the inverse: it parses the newline-delimited JSON
+//! back out and, under the `session` feature, can replay it into a fresh
+//! [`crate::session::MessageHistory`] to audit, resume, or reproduce a past
+//! run.
+
+#[cfg(feature = "transcript")]
+use crate::error::AgentError;
+#[cfg(feature = "transcript")]
+use crate::message::InputMessage;
+#[cfg(feature = "transcript")]
+use crate::message::OutputMessage;
+#[cfg(feature = "transcript")]
+use crate::message::PlanMessage;
+#[cfg(feature = "transcript")]
+use crate::tool::ToolCall;
+#[cfg(feature = "transcript")]
+use crate::Result;
+#[cfg(feature = "transcript")]
+use serde::Deserialize;
+#[cfg(feature = "transcript")]
+use serde::Serialize;
+#[cfg(feature = "transcript")]
+use std::io::BufRead;
+#[cfg(feature = "transcript")]
+use std::io::Write;
+#[cfg(feature = "transcript")]
+use std::path::Path;
+#[cfg(feature = "transcript")]
+use std::path::PathBuf;
+#[cfg(feature = "transcript")]
+use std::sync::mpsc as std_mpsc;
+#[cfg(feature = "transcript")]
+use typed_builder::TypedBuilder;
+
+/// How often [`TranscriptWriter`] rotates onto a new backing file
+#[cfg(feature = "transcript")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationPolicy {
+    /// Start a new file every hour
+    Hourly,
+    /// Start a new file every day
+    Daily,
+    /// Start a new file once the current one exceeds this many bytes
+    SizeCapped(u64),
+    /// Never rotate; everything goes into a single file for the process's
+    /// lifetime
+    Never,
+}
+
+/// Configuration for a [`TranscriptWriter`]
+#[cfg(feature = "transcript")]
+#[derive(Debug, Clone, TypedBuilder)]
+pub struct TranscriptConfig {
+    /// Directory transcript files are written into; created if missing
+    #[builder(setter(into))]
+    pub dir: PathBuf,
+
+    /// Filename prefix; rotated files are named `{prefix}.{suffix}.jsonl`
+    #[builder(setter(into), default = "transcript".to_string())]
+    pub prefix: String,
+
+    /// When to roll onto a new file
+    #[builder(default = RotationPolicy::Daily)]
+    pub rotation: RotationPolicy,
+
+    /// Capacity of the channel between the async producer side and the
+    /// worker thread; a full channel means the worker has fallen far behind,
+    /// not that the producer should block
+    #[builder(default = 1024)]
+    pub channel_capacity: usize,
+}
+
+/// A single newline-delimited JSON record written by [`TranscriptWriter`]
+#[cfg(feature = "transcript")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum TranscriptRecord {
+    /// An [`InputMessage`] submitted to the session
+    Input {
+        session_id: String,
+        timestamp: u64,
+        message: InputMessage,
+    },
+    /// An [`OutputMessage`] produced by the session
+    Output {
+        session_id: String,
+        timestamp: u64,
+        message: OutputMessage,
+    },
+    /// A [`PlanMessage`] update
+    Plan {
+        session_id: String,
+        timestamp: u64,
+        message: PlanMessage,
+    },
+    /// A tool invocation
+    ToolCall {
+        session_id: String,
+        timestamp: u64,
+        call: ToolCall,
+    },
+}
+
+#[cfg(feature = "transcript")]
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Non-blocking, rotating writer for [`TranscriptRecord`]s
+///
+/// `append_*` methods only `try_send` onto a bounded channel of
+/// [`TranscriptConfig::channel_capacity`] records, dropping (and logging) a
+/// record when it is full; a dedicated
+/// worker thread owns the actual file and performs rotation and flushing, so
+/// disk latency never blocks the agent's async event loop.
+#[cfg(feature = "transcript")]
+pub struct TranscriptWriter {
+    tx: std_mpsc::SyncSender<TranscriptRecord>,
+    worker: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(feature = "transcript")]
+impl TranscriptWriter {
+    /// Spawn the worker thread and return a handle to it
+    pub fn new(config: TranscriptConfig) -> Result<Self> {
+        std::fs::create_dir_all(&config.dir)
+            .map_err(|e| AgentError::InternalError(e.to_string()))?;
+
+        let (tx, rx) = std_mpsc::sync_channel(config.channel_capacity.max(1));
+        let worker = std::thread::Builder::new()
+            .name("codex-transcript-writer".to_string())
+            .spawn(move || run_worker(rx, config))
+            .map_err(|e| AgentError::InternalError(e.to_string()))?;
+
+        Ok(Self {
+            tx,
+            worker: Some(worker),
+        })
+    }
+
+    /// Record an inbound [`InputMessage`]
+    pub fn append_input(&self, session_id: impl Into<String>, message: InputMessage) {
+        self.append(TranscriptRecord::Input {
+            session_id: session_id.into(),
+            timestamp: now_secs(),
+            message,
+        });
+    }
+
+    /// Record an outbound [`OutputMessage`]
+    pub fn append_output(&self, session_id: impl Into<String>, message: OutputMessage) {
+        self.append(TranscriptRecord::Output {
+            session_id: session_id.into(),
+            timestamp: now_secs(),
+            message,
+        });
+    }
+
+    /// Record a [`PlanMessage`] update
+    pub fn append_plan(&self, session_id: impl Into<String>, message: PlanMessage) {
+        self.append(TranscriptRecord::Plan {
+            session_id: session_id.into(),
+            timestamp: now_secs(),
+            message,
+        });
+    }
+
+    /// Record a tool invocation
+    pub fn append_tool_call(&self, session_id: impl Into<String>, call: ToolCall) {
+        self.append(TranscriptRecord::ToolCall {
+            session_id: session_id.into(),
+            timestamp: now_secs(),
+            call,
+        });
+    }
+
+    fn append(&self, record: TranscriptRecord) {
+        // `try_send` never blocks: if the worker has fallen behind and the
+        // bounded channel is full, the record is dropped (and logged)
+        // instead of stalling the agent's event loop.
+        if let Err(e) = self.tx.try_send(record) {
+            tracing::warn!("dropping transcript record, worker can't keep up: {e}");
+        }
+    }
+
+    /// Flush and join the worker thread
+    ///
+    /// Drops the sender first so the worker's `recv` loop observes the
+    /// channel closing and exits after flushing whatever is left.
+    pub async fn close(self) -> Result<()> {
+        let TranscriptWriter { tx, worker } = self;
+        drop(tx);
+        if let Some(worker) = worker {
+            tokio::task::spawn_blocking(move || worker.join())
+                .await
+                .map_err(|e| AgentError::InternalError(e.to_string()))?
+                .map_err(|_| {
+                    AgentError::InternalError("transcript worker thread panicked".to_string())
+                })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "transcript")]
+struct RotatingFile {
+    dir: PathBuf,
+    prefix: String,
+    rotation: RotationPolicy,
+    file: Option<std::fs::File>,
+    bytes_written: u64,
+    current_suffix: String,
+}
+
+#[cfg(feature = "transcript")]
+impl RotatingFile {
+    fn new(dir: PathBuf, prefix: String, rotation: RotationPolicy) -> Self {
+        Self {
+            dir,
+            prefix,
+            rotation,
+            file: None,
+            bytes_written: 0,
+            current_suffix: String::new(),
+        }
+    }
+
+    fn suffix_for_now(&self) -> String {
+        let now = now_secs();
+        match self.rotation {
+            RotationPolicy::Hourly => format!("{}", now / 3600),
+            RotationPolicy::Daily => format!("{}", now / 86400),
+            RotationPolicy::SizeCapped(_) | RotationPolicy::Never => self.current_suffix.clone(),
+        }
+    }
+
+    fn needs_rotation(&self, line_len: u64) -> bool {
+        if self.file.is_none() {
+            return true;
+        }
+        match self.rotation {
+            RotationPolicy::Hourly | RotationPolicy::Daily => {
+                self.suffix_for_now() != self.current_suffix
+            }
+            RotationPolicy::SizeCapped(max_bytes) => {
+                self.bytes_written + line_len > max_bytes
+            }
+            RotationPolicy::Never => false,
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let bytes = line.len() as u64 + 1;
+        if self.needs_rotation(bytes) {
+            self.rotate()?;
+        }
+        let file = self.file.as_mut().expect("rotate() always opens a file");
+        writeln!(file, "{line}")?;
+        file.flush()?;
+        self.bytes_written += bytes;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let suffix = match self.rotation {
+            RotationPolicy::Hourly | RotationPolicy::Daily => self.suffix_for_now(),
+            RotationPolicy::SizeCapped(_) | RotationPolicy::Never => {
+                format!("{}", now_secs())
+            }
+        };
+        let path = self.dir.join(format!("{}.{}.jsonl", self.prefix, suffix));
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        self.bytes_written = file.metadata().map(|m| m.len()).unwrap_or(0);
+        self.file = Some(file);
+        self.current_suffix = suffix;
+        Ok(())
+    }
+}
+
+/// Body of the dedicated writer thread: drain `rx` until the sender is
+/// dropped, rotating and flushing as configured
+#[cfg(feature = "transcript")]
+fn run_worker(rx: std_mpsc::Receiver<TranscriptRecord>, config: TranscriptConfig) {
+    let mut file = RotatingFile::new(config.dir, config.prefix, config.rotation);
+
+    while let Ok(record) = rx.recv() {
+        let line = match serde_json::to_string(&record) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!("failed to serialize transcript record: {e}");
+                continue;
+            }
+        };
+        if let Err(e) = file.write_line(&line) {
+            tracing::warn!("failed to write transcript record: {e}");
+        }
+    }
+}
+
+/// Reads transcript files written by [`TranscriptWriter`]
+#[cfg(feature = "transcript")]
+pub struct TranscriptReader;
+
+#[cfg(feature = "transcript")]
+impl TranscriptReader {
+    /// Parse every record in `path`, in the order they were written
+    pub async fn read_records(path: &Path) -> Result<Vec<TranscriptRecord>> {
+        let path = path.to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let file = std::fs::File::open(&path)
+                .map_err(|e| AgentError::InternalError(e.to_string()))?;
+            let reader = std::io::BufReader::new(file);
+            let mut records = Vec::new();
+            for line in reader.lines() {
+                let line = line.map_err(|e| AgentError::InternalError(e.to_string()))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record: TranscriptRecord = serde_json::from_str(&line)
+                    .map_err(|e| AgentError::InternalError(e.to_string()))?;
+                records.push(record);
+            }
+            Ok(records)
+        })
+        .await
+        .map_err(|e| AgentError::InternalError(e.to_string()))?
+    }
+
+    /// Replay a transcript back into a fresh [`crate::session::MessageHistory`]
+    ///
+    /// `Input` records are replayed as `role: "user"`, `Output::Primary`
+    /// records as `role: "assistant"`; every other record (deltas, tool
+    /// events, plan updates) is skipped since [`crate::session::MessageHistory`]
+    /// only models the conversational turns.
+    #[cfg(feature = "session")]
+    pub async fn replay_into_history(
+        path: &Path,
+        history: &mut crate::session::MessageHistory,
+    ) -> Result<()> {
+        use crate::message::OutputData;
+
+        for record in Self::read_records(path).await? {
+            match record {
+                TranscriptRecord::Input { message, .. } => {
+                    history.add("user".to_string(), message.message);
+                }
+                TranscriptRecord::Output {
+                    message: OutputMessage {
+                        data: OutputData::Primary(text),
+                        ..
+                    },
+                    ..
+                } => {
+                    history.add("assistant".to_string(), text);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
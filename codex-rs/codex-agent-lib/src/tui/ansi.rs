@@ -0,0 +1,211 @@
+//! ANSI SGR (Select Graphic Rendition) parsing into ratatui [`Line`]s
+//!
+//! [`crate::utils::output::clean_ansi`] throws the escapes away entirely,
+//! which is right for plain-text consumers but loses real color when
+//! `render_output` re-displays a colored `rustc`/`pytest`/`cargo` run. This
+//! module parses just enough of ECMA-48 SGR to carry that color through as
+//! ratatui [`Style`]s instead.
+
+#[cfg(feature = "tui")]
+use ratatui::style::Color;
+#[cfg(feature = "tui")]
+use ratatui::style::Modifier;
+#[cfg(feature = "tui")]
+use ratatui::style::Style;
+#[cfg(feature = "tui")]
+use ratatui::text::Line;
+#[cfg(feature = "tui")]
+use ratatui::text::Span;
+
+/// Parse `text` into styled [`Line`]s, applying ANSI SGR escapes (16-color,
+/// 256-color, and truecolor foreground/background, plus bold, italic,
+/// underline, and reverse) as ratatui [`Style`]s. Unrecognized escapes are
+/// dropped silently; `\x1b[0m` (or a bare `\x1b[m`) resets to the default
+/// style. Each input line (split on `\n`) becomes one output `Line`, and
+/// style state carries across line breaks the way a real terminal would.
+#[cfg(feature = "tui")]
+pub fn ansi_to_lines(text: &str) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut style = Style::default();
+
+    for raw_line in text.split('\n') {
+        let mut spans = Vec::new();
+        let mut chars = raw_line.chars().peekable();
+        let mut current = String::new();
+
+        while let Some(c) = chars.next() {
+            if c == '\x1b' && chars.peek() == Some(&'[') {
+                chars.next(); // consume '['
+                let mut params = String::new();
+                let mut final_byte = None;
+                for p in chars.by_ref() {
+                    if ('@'..='~').contains(&p) {
+                        final_byte = Some(p);
+                        break;
+                    }
+                    params.push(p);
+                }
+                if !current.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut current), style));
+                }
+                if final_byte == Some('m') {
+                    apply_sgr(&mut style, &params);
+                }
+            } else {
+                current.push(c);
+            }
+        }
+        if !current.is_empty() {
+            spans.push(Span::styled(current, style));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines
+}
+
+/// Apply a single SGR parameter sequence (the digits between `\x1b[` and
+/// `m`, e.g. `"1;31"` or `"38;5;208"`) to `style`, mutating it in place
+#[cfg(feature = "tui")]
+fn apply_sgr(style: &mut Style, params: &str) {
+    if params.is_empty() {
+        *style = Style::default();
+        return;
+    }
+
+    let codes: Vec<&str> = params.split(';').collect();
+    let mut i = 0;
+    while i < codes.len() {
+        let code: i32 = match codes[i].parse() {
+            Ok(n) => n,
+            Err(_) => {
+                i += 1;
+                continue;
+            }
+        };
+
+        match code {
+            0 => *style = Style::default(),
+            1 => *style = style.add_modifier(Modifier::BOLD),
+            3 => *style = style.add_modifier(Modifier::ITALIC),
+            4 => *style = style.add_modifier(Modifier::UNDERLINED),
+            7 => *style = style.add_modifier(Modifier::REVERSED),
+            22 => *style = style.remove_modifier(Modifier::BOLD),
+            23 => *style = style.remove_modifier(Modifier::ITALIC),
+            24 => *style = style.remove_modifier(Modifier::UNDERLINED),
+            27 => *style = style.remove_modifier(Modifier::REVERSED),
+            30..=37 => *style = style.fg(ansi_16_color(code - 30, false)),
+            38 => {
+                if let Some((color, consumed)) = extended_color(&codes[i + 1..]) {
+                    *style = style.fg(color);
+                    i += consumed;
+                }
+            }
+            39 => *style = style.fg(Color::Reset),
+            40..=47 => *style = style.bg(ansi_16_color(code - 40, false)),
+            48 => {
+                if let Some((color, consumed)) = extended_color(&codes[i + 1..]) {
+                    *style = style.bg(color);
+                    i += consumed;
+                }
+            }
+            49 => *style = style.bg(Color::Reset),
+            90..=97 => *style = style.fg(ansi_16_color(code - 90, true)),
+            100..=107 => *style = style.bg(ansi_16_color(code - 100, true)),
+            _ => {}
+        }
+        i += 1;
+    }
+}
+
+/// Map a 0-7 SGR color index to the matching [`Color`] variant
+#[cfg(feature = "tui")]
+fn ansi_16_color(index: i32, bright: bool) -> Color {
+    match (index, bright) {
+        (0, false) => Color::Black,
+        (1, false) => Color::Red,
+        (2, false) => Color::Green,
+        (3, false) => Color::Yellow,
+        (4, false) => Color::Blue,
+        (5, false) => Color::Magenta,
+        (6, false) => Color::Cyan,
+        (7, false) => Color::Gray,
+        (0, true) => Color::DarkGray,
+        (1, true) => Color::LightRed,
+        (2, true) => Color::LightGreen,
+        (3, true) => Color::LightYellow,
+        (4, true) => Color::LightBlue,
+        (5, true) => Color::LightMagenta,
+        (6, true) => Color::LightCyan,
+        (7, true) => Color::White,
+        _ => Color::Reset,
+    }
+}
+
+/// Parse a `5;<n>` (256-color) or `2;<r>;<g>;<b>` (truecolor) sequence that
+/// follows a `38`/`48` code, returning the resolved color and how many of
+/// `rest`'s entries it consumed
+#[cfg(feature = "tui")]
+fn extended_color(rest: &[&str]) -> Option<(Color, usize)> {
+    match rest.first().copied() {
+        Some("5") => {
+            let n: u8 = rest.get(1)?.parse().ok()?;
+            Some((Color::Indexed(n), 2))
+        }
+        Some("2") => {
+            let r: u8 = rest.get(1)?.parse().ok()?;
+            let g: u8 = rest.get(2)?.parse().ok()?;
+            let b: u8 = rest.get(3)?.parse().ok()?;
+            Some((Color::Rgb(r, g, b), 4))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_is_one_unstyled_span() {
+        let lines = ansi_to_lines("hello world");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].spans.len(), 1);
+        assert_eq!(lines[0].spans[0].content, "hello world");
+    }
+
+    #[test]
+    fn sgr_16_color_sets_foreground() {
+        let lines = ansi_to_lines("\x1b[31merror\x1b[0m: oops");
+        assert_eq!(lines[0].spans[0].content, "error");
+        assert_eq!(lines[0].spans[0].style.fg, Some(Color::Red));
+        assert_eq!(lines[0].spans[1].content, ": oops");
+        assert_eq!(lines[0].spans[1].style.fg, None);
+    }
+
+    #[test]
+    fn sgr_256_color_is_indexed() {
+        let lines = ansi_to_lines("\x1b[38;5;208morange\x1b[0m");
+        assert_eq!(lines[0].spans[0].style.fg, Some(Color::Indexed(208)));
+    }
+
+    #[test]
+    fn sgr_truecolor_is_rgb() {
+        let lines = ansi_to_lines("\x1b[38;2;10;20;30mtc\x1b[0m");
+        assert_eq!(lines[0].spans[0].style.fg, Some(Color::Rgb(10, 20, 30)));
+    }
+
+    #[test]
+    fn bold_modifier_survives_reset_of_unrelated_attribute() {
+        let lines = ansi_to_lines("\x1b[1;32mbold green\x1b[39m still bold");
+        assert!(lines[0].spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert_eq!(lines[0].spans[1].style.fg, Some(Color::Reset));
+        assert!(lines[0].spans[1].style.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn style_state_carries_across_lines() {
+        let lines = ansi_to_lines("\x1b[31mred\nstill red\x1b[0m");
+        assert_eq!(lines[1].spans[0].style.fg, Some(Color::Red));
+    }
+}
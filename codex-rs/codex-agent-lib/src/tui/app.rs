@@ -3,10 +3,20 @@
 #[cfg(feature = "tui")]
 use crate::Agent;
 #[cfg(feature = "tui")]
+use crate::message::InputMessage;
+#[cfg(feature = "tui")]
 use crate::message::OutputData;
 #[cfg(feature = "tui")]
 use crate::message::TodoItem;
 #[cfg(feature = "tui")]
+use crate::tui::log::LogBuffer;
+#[cfg(feature = "tui")]
+use crate::tui::log::TuiLogLayer;
+#[cfg(feature = "tui")]
+use crate::tui::slash::SlashCommand;
+#[cfg(feature = "tui")]
+use crate::tui::slash::SlashCommandRegistry;
+#[cfg(feature = "tui")]
 use crate::Result;
 #[cfg(feature = "tui")]
 use crossterm::event::DisableMouseCapture;
@@ -31,10 +41,14 @@ use crossterm::terminal::disable_raw_mode;
 #[cfg(feature = "tui")]
 use crossterm::terminal::enable_raw_mode;
 #[cfg(feature = "tui")]
+use crossterm::event::MouseEventKind;
+#[cfg(feature = "tui")]
 use ratatui::Terminal;
 #[cfg(feature = "tui")]
 use ratatui::backend::CrosstermBackend;
 #[cfg(feature = "tui")]
+use ratatui::layout::Rect;
+#[cfg(feature = "tui")]
 use std::io;
 #[cfg(feature = "tui")]
 use std::sync::Arc;
@@ -44,28 +58,210 @@ use std::sync::Mutex;
 use std::time::Duration;
 #[cfg(feature = "tui")]
 use tokio::sync::mpsc;
+#[cfg(feature = "tui")]
+use tracing::Level;
 
 /// Message in the chat
 #[cfg(feature = "tui")]
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct Message {
     pub role: MessageRole,
     pub content: String,
 }
 
 #[cfg(feature = "tui")]
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum MessageRole {
     User,
     Assistant,
     System,
 }
 
+/// The most recent [`OutputData::Image`] the agent sent, waiting to be
+/// drawn in the output pane
+#[cfg(feature = "tui")]
+#[derive(Clone)]
+pub struct PendingImage {
+    pub mime: String,
+    pub data: Vec<u8>,
+    pub alt: Option<String>,
+}
+
+/// Number of `tracing` events [`AppState::log_buffer`] keeps before dropping
+/// the oldest
+#[cfg(feature = "tui")]
+const DEFAULT_LOG_CAPACITY: usize = 500;
+
+/// Scrollback state for one scrollable pane (the chat history or the tool
+/// output); the offset is in wrapped display lines rather than messages,
+/// so long entries still scroll smoothly
+#[cfg(feature = "tui")]
+#[derive(Clone, Copy)]
+pub struct ScrollState {
+    /// Number of lines hidden above the top of the viewport
+    pub offset: usize,
+    /// Whether the viewport stays pinned to the latest line as new
+    /// content arrives; disabled by any manual scroll, re-enabled once
+    /// the user scrolls back down to the bottom
+    pub follow_tail: bool,
+}
+
+#[cfg(feature = "tui")]
+impl Default for ScrollState {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            follow_tail: true,
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+impl ScrollState {
+    /// Scroll up (towards older content) by `lines`
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.offset = self.offset.saturating_sub(lines);
+        self.follow_tail = false;
+    }
+
+    /// Scroll down (towards newer content) by `lines`
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.offset = self.offset.saturating_add(lines);
+        self.follow_tail = false;
+    }
+
+    /// Reconcile the offset against the pane's current content height,
+    /// pinning to the tail if `follow_tail` is set or the offset has
+    /// drifted past the last possible position (e.g. content shrank);
+    /// returns the offset to actually render from
+    pub fn reconcile(&mut self, total_lines: usize, visible_height: usize) -> usize {
+        let max_offset = total_lines.saturating_sub(visible_height);
+        if self.follow_tail || self.offset >= max_offset {
+            self.offset = max_offset;
+            self.follow_tail = true;
+        }
+        self.offset
+    }
+}
+
+/// Previously submitted inputs, with up/down recall while composing the
+/// next one; optionally persisted to disk across runs via
+/// [`AgentTui::with_history_file`]
+#[cfg(feature = "tui")]
+#[derive(Clone, Default)]
+pub struct InputHistory {
+    entries: Vec<String>,
+    /// Position in `entries` while browsing with Up/Down; `None` means the
+    /// input box holds a fresh, unbrowsed draft
+    index: Option<usize>,
+    /// The draft that was in progress when browsing started, so Down past
+    /// the most recent entry restores it instead of leaving the input blank
+    draft: String,
+}
+
+#[cfg(feature = "tui")]
+impl InputHistory {
+    /// Load previously submitted inputs, oldest first; empty if `path`
+    /// doesn't exist yet (e.g. first run)
+    pub fn load(path: &std::path::Path) -> Self {
+        let entries = std::fs::read_to_string(path)
+            .map(|contents| contents.lines().map(str::to_string).collect())
+            .unwrap_or_default();
+        Self {
+            entries,
+            index: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Persist the recorded entries to `path`, one per line, creating its
+    /// parent directory if missing
+    pub fn save(&self, path: &std::path::Path) {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(path, self.entries.join("\n"));
+    }
+
+    /// Record a submitted message, skipping a no-op repeat of the last entry
+    pub fn record(&mut self, message: &str) {
+        if self.entries.last().map(String::as_str) != Some(message) {
+            self.entries.push(message.to_string());
+        }
+        self.index = None;
+        self.draft.clear();
+    }
+
+    /// Walk backwards into older entries, saving `current_input` as the
+    /// draft the first time so [`Self::next`] can restore it later
+    pub fn prev(&mut self, current_input: &str) -> Option<&str> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let next_index = match self.index {
+            None => {
+                self.draft = current_input.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => 0,
+            Some(i) => i - 1,
+        };
+        self.index = Some(next_index);
+        Some(&self.entries[next_index])
+    }
+
+    /// Walk forward towards more recent entries; past the most recent
+    /// entry, returns the draft that was in progress before browsing
+    /// started
+    pub fn next(&mut self) -> Option<String> {
+        match self.index {
+            None => None,
+            Some(i) if i + 1 < self.entries.len() => {
+                self.index = Some(i + 1);
+                Some(self.entries[i + 1].clone())
+            }
+            Some(_) => {
+                self.index = None;
+                Some(std::mem::take(&mut self.draft))
+            }
+        }
+    }
+
+    /// Editing a recalled entry turns it into a fresh draft: further
+    /// Up/Down presses start browsing anew from the most recent entry
+    pub fn reset_browse(&mut self) {
+        self.index = None;
+    }
+}
+
+/// How the Enter handler treats a submitted prompt while a turn is already
+/// in flight (`AppState::is_processing`)
+#[cfg(feature = "tui")]
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum BusyBehavior {
+    /// Drop the keystrokes that produced the prompt; the input field is
+    /// simply cleared with nothing sent. This was the TUI's only behavior
+    /// before [`BusyBehavior`] existed.
+    #[default]
+    DoNothing,
+    /// Buffer the prompt in [`AppState::queued_prompts`] and dispatch it
+    /// once the in-flight turn reports [`OutputData::Completed`] or
+    /// [`OutputData::Error`], in the order prompts were queued
+    Queue,
+    /// Stop the in-flight turn and send the new prompt immediately
+    Interrupt,
+}
+
 /// Application state
 #[cfg(feature = "tui")]
 pub struct AppState {
     /// Input field content
     pub input: String,
+    /// Previously submitted inputs, with up/down recall via `Up`/`Down`
+    pub input_history: InputHistory,
+    /// Where `input_history` is persisted on exit and loaded from on
+    /// startup, set up via [`AgentTui::with_history_file`]
+    pub history_path: Option<std::path::PathBuf>,
     /// Chat history
     pub messages: Vec<Message>,
     /// Todo list
@@ -74,10 +270,50 @@ pub struct AppState {
     pub status: String,
     /// Tool output buffer
     pub tool_output: String,
+    /// Raw argument text accumulated so far per in-flight tool call id, fed
+    /// by [`OutputData::ToolArgsDelta`] and previewed via
+    /// [`Self::preview_tool_args`]
+    pub tool_arg_fragments: std::collections::HashMap<String, String>,
+    /// An image the agent sent, shown in the output pane in place of
+    /// `tool_output` until the next tool run or assistant message clears it
+    pub image: Option<PendingImage>,
+    /// Scrollback state for the chat pane
+    pub chat_scroll: ScrollState,
+    /// Scrollback state for the tool output pane
+    pub output_scroll: ScrollState,
+    /// Screen area the chat pane occupied as of the last draw, used to
+    /// route mouse wheel events to the right pane's scroll state
+    pub chat_area: Rect,
+    /// Screen area the tool output pane occupied as of the last draw
+    pub output_area: Rect,
     /// Whether the agent is processing
     pub is_processing: bool,
+    /// How Enter behaves while `is_processing` is set, configured via
+    /// [`AgentTui::with_busy_behavior`]
+    pub busy_behavior: BusyBehavior,
+    /// Prompts submitted while `is_processing` was set and `busy_behavior`
+    /// is [`BusyBehavior::Queue`], waiting to be dispatched in order once
+    /// the in-flight turn completes
+    pub queued_prompts: std::collections::VecDeque<InputMessage>,
+    /// Slash commands available for expansion in the input field, set up
+    /// via [`AgentTui::with_slash_command`]
+    pub slash_commands: SlashCommandRegistry,
+    /// Ring buffer `tracing` events are fed into by the layer returned from
+    /// [`AgentTui::log_layer`]
+    pub log_buffer: LogBuffer,
+    /// Whether the log pane overlay is shown, toggled by `F2`
+    pub show_logs: bool,
+    /// Only records at least this severe are shown in the log pane, cycled
+    /// by `F3`
+    pub log_level_filter: Level,
+    /// If set, only log records whose target contains this substring are
+    /// shown in the log pane
+    pub log_target_filter: Option<String>,
     /// Custom data
     pub custom_data: Option<Box<dyn std::any::Any + Send + Sync>>,
+    /// Where `messages`/`todos` are checkpointed after every mutation, set
+    /// up via [`AgentTui::with_session_store`]
+    pub session_store: Option<(String, std::sync::Arc<dyn crate::tui::store::SessionStore>)>,
 }
 
 #[cfg(feature = "tui")]
@@ -85,6 +321,8 @@ impl AppState {
     pub fn new() -> Self {
         Self {
             input: String::new(),
+            input_history: InputHistory::default(),
+            history_path: None,
             messages: vec![Message {
                 role: MessageRole::System,
                 content: "Welcome! I'll help you with Python development. Let me set up the environment...".to_string(),
@@ -92,23 +330,88 @@ impl AppState {
             todos: Vec::new(),
             status: "Ready".to_string(),
             tool_output: String::new(),
+            tool_arg_fragments: std::collections::HashMap::new(),
+            image: None,
+            chat_scroll: ScrollState::default(),
+            output_scroll: ScrollState::default(),
+            chat_area: Rect::default(),
+            output_area: Rect::default(),
             is_processing: false,
+            busy_behavior: BusyBehavior::default(),
+            queued_prompts: std::collections::VecDeque::new(),
+            slash_commands: SlashCommandRegistry::new(),
+            log_buffer: LogBuffer::new(DEFAULT_LOG_CAPACITY),
+            show_logs: false,
+            log_level_filter: Level::INFO,
+            log_target_filter: None,
             custom_data: None,
+            session_store: None,
         }
     }
-    
+
+    /// If `input` is a `/name args` slash command registered in
+    /// `slash_commands`, expand it; otherwise wrap `input` as a plain
+    /// message unchanged
+    fn expand_input(&self, input: &str) -> InputMessage {
+        let Some(rest) = input.strip_prefix('/') else {
+            return input.into();
+        };
+        let (name, args) = rest.split_once(' ').unwrap_or((rest, ""));
+        match self.slash_commands.find(name) {
+            Some(command) => command.expand(args),
+            None => input.into(),
+        }
+    }
+
+    /// Slash-command name completions for the popup `render_input` shows
+    /// while typing -- `None` once `input` no longer looks like an
+    /// in-progress command (doesn't start with `/`, or the name has
+    /// already been completed with a trailing space)
+    pub fn slash_completions(&self) -> Option<Vec<&str>> {
+        let rest = self.input.strip_prefix('/')?;
+        if rest.contains(' ') {
+            return None;
+        }
+        Some(self.slash_commands.matching(rest))
+    }
+
     pub fn add_message(&mut self, role: MessageRole, content: String) {
         self.messages.push(Message { role, content });
+        self.checkpoint();
     }
-    
+
     pub fn update_todos(&mut self, todos: Vec<TodoItem>) {
         self.todos = todos;
+        self.checkpoint();
     }
-    
+
+    /// If a [`Self::session_store`] is configured, spawn a task that
+    /// persists the current `messages`/`todos` under its session id; a
+    /// no-op otherwise. Fire-and-forget, same as [`Self::preview_tool_args`]
+    /// makes no attempt to surface a save failure to the UI.
+    fn checkpoint(&self) {
+        let Some((session_id, store)) = self.session_store.clone() else {
+            return;
+        };
+        let snapshot = crate::tui::store::SessionSnapshot {
+            messages: self.messages.clone(),
+            todos: self.todos.clone(),
+        };
+        tokio::spawn(async move {
+            let _ = store.save(&session_id, &snapshot).await;
+        });
+    }
+
     pub fn set_status(&mut self, status: String) {
         self.status = status;
     }
-    
+
+    /// Pop the next queued prompt, if any, for dispatch once the in-flight
+    /// turn has completed
+    pub fn dequeue_prompt(&mut self) -> Option<InputMessage> {
+        self.queued_prompts.pop_front()
+    }
+
     pub fn append_tool_output(&mut self, output: String) {
         // Limit total output size to prevent memory issues
         if self.tool_output.len() > 10000 {
@@ -117,10 +420,211 @@ impl AppState {
             self.tool_output.insert_str(0, "... (output truncated) ...\n");
         }
         self.tool_output.push_str(&output);
+        self.image = None;
     }
-    
+
     pub fn clear_tool_output(&mut self) {
         self.tool_output.clear();
+        self.tool_arg_fragments.clear();
+        self.image = None;
+        self.output_scroll = ScrollState::default();
+    }
+
+    /// Accumulate one raw fragment of `id`'s still-streaming tool call
+    /// arguments and, if the accumulated text repairs into valid JSON (see
+    /// [`crate::tool::repair_partial_json`]), preview it in the output
+    /// pane; an unrepairable fragment leaves the previous preview in place
+    /// rather than clearing it
+    pub fn preview_tool_args(&mut self, id: String, delta: String) {
+        let accumulated = self.tool_arg_fragments.entry(id).or_default();
+        accumulated.push_str(&delta);
+
+        if let Some(value) = crate::tool::repair_partial_json(accumulated) {
+            self.tool_output = format!("$ {value}\n");
+            self.image = None;
+        }
+    }
+
+    /// Replace the output pane's contents with an image, clearing any
+    /// buffered text output
+    pub fn set_image(&mut self, mime: String, data: Vec<u8>, alt: Option<String>) {
+        self.tool_output.clear();
+        self.image = Some(PendingImage { mime, data, alt });
+    }
+
+    /// Show or hide the log pane overlay
+    pub fn toggle_logs(&mut self) {
+        self.show_logs = !self.show_logs;
+    }
+
+    /// Cycle `log_level_filter` through `ERROR -> WARN -> INFO -> DEBUG ->
+    /// TRACE -> ERROR`
+    pub fn cycle_log_level(&mut self) {
+        self.log_level_filter = match self.log_level_filter {
+            Level::ERROR => Level::WARN,
+            Level::WARN => Level::INFO,
+            Level::INFO => Level::DEBUG,
+            Level::DEBUG => Level::TRACE,
+            Level::TRACE => Level::ERROR,
+        };
+    }
+}
+
+/// Outcome of feeding a single key press to [`AppState`]
+#[cfg(feature = "tui")]
+pub enum KeyOutcome {
+    /// Enter was pressed with a non-empty input and the agent wasn't
+    /// already processing a turn; the caller should forward this message.
+    /// If the input was a registered slash command, this is already its
+    /// expansion rather than the literal `/name args` text.
+    Submit(InputMessage),
+    /// Enter was pressed while a turn was already in flight and
+    /// `busy_behavior` was [`BusyBehavior::Interrupt`]; the caller should
+    /// stop the in-flight turn and then forward this message
+    Interrupt(InputMessage),
+    /// Ctrl+C was pressed; the caller should exit the UI loop
+    Quit,
+    /// The key only mutated `state` (or was a no-op); nothing further to do
+    None,
+}
+
+/// Number of lines a mouse wheel tick scrolls; a bare `PageUp`/`PageDown`
+/// scrolls by the chat pane's visible height instead
+#[cfg(feature = "tui")]
+const SCROLL_LINES: usize = 3;
+
+/// Multiplier applied to the scroll distance ([`SCROLL_LINES`] for the
+/// mouse wheel, one page for `PageUp`/`PageDown`) when Shift is held, for a
+/// faster multi-line jump
+#[cfg(feature = "tui")]
+const SHIFT_SCROLL_MULTIPLIER: usize = 4;
+
+/// Apply a single key press to `state`
+///
+/// Shared between [`AgentTui::run`]'s raw-mode loop and
+/// [`crate::tui::test_backend::TestHarness`] so headless snapshot tests
+/// exercise exactly the same input handling as the real TUI. `ctrl`/`shift`
+/// reflect the key event's modifiers; Shift accelerates `PageUp`/`PageDown`
+/// scrolling (see [`SHIFT_SCROLL_MULTIPLIER`]); `Up`/`Down` recall entries
+/// from [`AppState::input_history`] instead of scrolling.
+#[cfg(feature = "tui")]
+pub fn handle_key(state: &mut AppState, code: KeyCode, ctrl: bool) -> KeyOutcome {
+    handle_key_with_shift(state, code, ctrl, false)
+}
+
+/// Like [`handle_key`], but also takes whether Shift was held, for
+/// accelerated scrolling
+#[cfg(feature = "tui")]
+pub fn handle_key_with_shift(state: &mut AppState, code: KeyCode, ctrl: bool, shift: bool) -> KeyOutcome {
+    let page_lines = state.chat_area.height.saturating_sub(2).max(1) as usize;
+    let lines = if shift {
+        page_lines * SHIFT_SCROLL_MULTIPLIER
+    } else {
+        page_lines
+    };
+
+    match code {
+        KeyCode::Char('c') if ctrl => KeyOutcome::Quit,
+        KeyCode::Enter => {
+            if state.input.is_empty() {
+                return KeyOutcome::None;
+            }
+            let typed = state.input.clone();
+            state.input.clear();
+            state.input_history.record(&typed);
+            let expanded = state.expand_input(&typed);
+
+            if !state.is_processing {
+                state.add_message(MessageRole::User, expanded.message.clone());
+                state.is_processing = true;
+                state.set_status("Processing...".to_string());
+                return KeyOutcome::Submit(expanded);
+            }
+
+            match state.busy_behavior {
+                BusyBehavior::DoNothing => KeyOutcome::None,
+                BusyBehavior::Queue => {
+                    state.add_message(MessageRole::User, expanded.message.clone());
+                    state.queued_prompts.push_back(expanded);
+                    KeyOutcome::None
+                }
+                BusyBehavior::Interrupt => {
+                    state.add_message(MessageRole::User, expanded.message.clone());
+                    KeyOutcome::Interrupt(expanded)
+                }
+            }
+        }
+        KeyCode::F(2) => {
+            state.toggle_logs();
+            KeyOutcome::None
+        }
+        KeyCode::F(3) => {
+            state.cycle_log_level();
+            KeyOutcome::None
+        }
+        KeyCode::PageUp => {
+            state.chat_scroll.scroll_up(lines);
+            KeyOutcome::None
+        }
+        KeyCode::PageDown => {
+            state.chat_scroll.scroll_down(lines);
+            KeyOutcome::None
+        }
+        KeyCode::Up => {
+            if let Some(recalled) = state.input_history.prev(&state.input) {
+                state.input = recalled.to_string();
+            }
+            KeyOutcome::None
+        }
+        KeyCode::Down => {
+            if let Some(recalled) = state.input_history.next() {
+                state.input = recalled;
+            }
+            KeyOutcome::None
+        }
+        KeyCode::Char(c) => {
+            state.input.push(c);
+            state.input_history.reset_browse();
+            KeyOutcome::None
+        }
+        KeyCode::Backspace => {
+            state.input.pop();
+            state.input_history.reset_browse();
+            KeyOutcome::None
+        }
+        _ => KeyOutcome::None,
+    }
+}
+
+/// Apply a mouse wheel tick to whichever scrollable pane `column`/`row`
+/// (the event's screen position) falls within; a no-op outside both panes.
+/// `shift` accelerates the scroll by [`SHIFT_SCROLL_MULTIPLIER`].
+#[cfg(feature = "tui")]
+pub fn handle_mouse_scroll(state: &mut AppState, kind: MouseEventKind, column: u16, row: u16, shift: bool) {
+    let lines = if shift {
+        SCROLL_LINES * SHIFT_SCROLL_MULTIPLIER
+    } else {
+        SCROLL_LINES
+    };
+
+    let hit = |area: Rect| {
+        column >= area.x && column < area.x + area.width && row >= area.y && row < area.y + area.height
+    };
+
+    let scroll = if hit(state.chat_area) {
+        Some(&mut state.chat_scroll)
+    } else if hit(state.output_area) {
+        Some(&mut state.output_scroll)
+    } else {
+        None
+    };
+
+    if let Some(scroll) = scroll {
+        match kind {
+            MouseEventKind::ScrollUp => scroll.scroll_up(lines),
+            MouseEventKind::ScrollDown => scroll.scroll_down(lines),
+            _ => {}
+        }
     }
 }
 
@@ -152,6 +656,53 @@ impl AgentTui {
         self.state.lock().unwrap().messages = messages;
         self
     }
+
+    /// Register a slash command, available for expansion in the input
+    /// field (see [`crate::tui::slash`])
+    pub fn with_slash_command(self, command: impl SlashCommand + 'static) -> Self {
+        self.state.lock().unwrap().slash_commands.register(command);
+        self
+    }
+
+    /// Configure how Enter behaves while a turn is already in flight
+    /// (default [`BusyBehavior::DoNothing`])
+    pub fn with_busy_behavior(self, behavior: BusyBehavior) -> Self {
+        self.state.lock().unwrap().busy_behavior = behavior;
+        self
+    }
+
+    /// Load persisted input history from `path` (a no-op if it doesn't
+    /// exist yet) and save back to it, one entry per line, when
+    /// [`Self::run`] returns -- so `Up`/`Down` recall survives across runs
+    pub fn with_history_file(self, path: impl Into<std::path::PathBuf>) -> Self {
+        let path = path.into();
+        {
+            let mut state = self.state.lock().unwrap();
+            state.input_history = InputHistory::load(&path);
+            state.history_path = Some(path);
+        }
+        self
+    }
+
+    /// Checkpoint `messages`/`todos` to `store` under `session_id` after
+    /// every mutation; [`Self::run`] also loads a prior snapshot for this
+    /// id on startup, resuming mid-conversation
+    pub fn with_session_store(
+        self,
+        session_id: impl Into<String>,
+        store: Arc<dyn crate::tui::store::SessionStore>,
+    ) -> Self {
+        self.state.lock().unwrap().session_store = Some((session_id.into(), store));
+        self
+    }
+
+    /// A `tracing_subscriber::Layer` feeding this TUI's log pane; register
+    /// it (e.g. via `tracing_subscriber::registry().with(tui.log_layer())`)
+    /// alongside whatever other layers write logs elsewhere (see
+    /// [`crate::tui::log`])
+    pub fn log_layer(&self) -> TuiLogLayer {
+        TuiLogLayer::new(self.state.lock().unwrap().log_buffer.clone())
+    }
     
     /// Run the TUI application with the given agent
     pub async fn run(
@@ -168,6 +719,17 @@ impl AgentTui {
         let mut terminal = Terminal::new(backend)
             .map_err(|e| crate::error::AgentError::InternalError(e.to_string()))?;
         
+        // Resume a prior conversation, if a session store is configured and
+        // has a snapshot saved under its session id
+        let resume = self.state.lock().unwrap().session_store.clone();
+        if let Some((session_id, store)) = resume {
+            if let Some(snapshot) = store.load(&session_id).await? {
+                let mut state = self.state.lock().unwrap();
+                state.messages = snapshot.messages;
+                state.todos = snapshot.todos;
+            }
+        }
+
         // Create channels
         let (input_tx, input_rx) = mpsc::channel(100);
         let (plan_tx, mut plan_rx) = mpsc::channel(100);
@@ -190,6 +752,7 @@ impl AgentTui {
         
         // Spawn output handler
         let state_output = self.state.clone();
+        let input_tx_output = input_tx.clone();
         tokio::spawn(async move {
             while let Some(output) = output_rx.recv().await {
                 let mut state = state_output.lock().unwrap();
@@ -211,6 +774,9 @@ impl AgentTui {
                             state.add_message(MessageRole::Assistant, delta);
                         }
                     }
+                    OutputData::ToolArgsDelta { id, delta } => {
+                        state.preview_tool_args(id, delta);
+                    }
                     OutputData::ToolStart { tool_name, arguments } => {
                         state.set_status(format!("Running: {}", tool_name));
                         state.clear_tool_output();
@@ -247,17 +813,54 @@ impl AgentTui {
                             }
                         }
                     }
+                    OutputData::ToolOutputDelta { chunk, .. } => {
+                        #[cfg(feature = "utils")]
+                        let cleaned = crate::utils::output::clean_ansi(&chunk);
+                        #[cfg(not(feature = "utils"))]
+                        let cleaned = chunk;
+
+                        // Unlike `ToolOutput`'s one-shot dump, a delta is
+                        // appended live as it arrives, so the output pane
+                        // (which already auto-scrolls) never truncates a
+                        // long-running command's output.
+                        state.append_tool_output(cleaned);
+                    }
                     OutputData::ToolComplete { tool_name, .. } => {
                         state.append_tool_output(format!("âœ“ {} completed\n\n", tool_name));
                     }
+                    OutputData::Image { mime, data, alt } => {
+                        state.set_image(mime, data, alt);
+                    }
                     OutputData::Completed => {
-                        state.set_status("Ready".to_string());
-                        state.is_processing = false;
+                        match state.dequeue_prompt() {
+                            Some(next) => {
+                                state.set_status("Processing...".to_string());
+                                let input_tx_output = input_tx_output.clone();
+                                tokio::spawn(async move {
+                                    let _ = input_tx_output.send(next).await;
+                                });
+                            }
+                            None => {
+                                state.set_status("Ready".to_string());
+                                state.is_processing = false;
+                            }
+                        }
                     }
                     OutputData::Error(err) => {
                         state.add_message(MessageRole::System, format!("Error: {:?}", err));
-                        state.set_status("Error occurred".to_string());
-                        state.is_processing = false;
+                        match state.dequeue_prompt() {
+                            Some(next) => {
+                                state.set_status("Processing...".to_string());
+                                let input_tx_output = input_tx_output.clone();
+                                tokio::spawn(async move {
+                                    let _ = input_tx_output.send(next).await;
+                                });
+                            }
+                            None => {
+                                state.set_status("Error occurred".to_string());
+                                state.is_processing = false;
+                            }
+                        }
                     }
                     _ => {}
                 }
@@ -282,57 +885,84 @@ impl AgentTui {
         
         loop {
             // Draw UI
+            let mut pending_escape = None;
             terminal.draw(|f| {
-                let state = state_ui.lock().unwrap();
-                crate::tui::components::render_default_layout(f, f.area(), &state, &title);
+                let mut state = state_ui.lock().unwrap();
+                pending_escape =
+                    crate::tui::components::render_default_layout(f, f.area(), &mut state, &title);
             }).map_err(|e| crate::error::AgentError::InternalError(e.to_string()))?;
-            
+
+            // A graphics-protocol image doesn't fit in ratatui's cell
+            // grid, so render_default_layout hands back raw escape bytes
+            // here instead; write them straight to the terminal, inside
+            // the blank pane the frame already reserved for them.
+            if let Some((area, bytes)) = pending_escape {
+                use std::io::Write;
+                let backend = terminal.backend_mut();
+                execute!(backend, crossterm::cursor::MoveTo(area.x, area.y))
+                    .map_err(|e| crate::error::AgentError::InternalError(e.to_string()))?;
+                backend
+                    .write_all(&bytes)
+                    .map_err(|e| crate::error::AgentError::InternalError(e.to_string()))?;
+                backend
+                    .flush()
+                    .map_err(|e| crate::error::AgentError::InternalError(e.to_string()))?;
+            }
+
             // Handle input
             if event::poll(Duration::from_millis(50))
                 .map_err(|e| crate::error::AgentError::InternalError(e.to_string()))?
             {
-                if let Event::Key(key) = event::read()
+                match event::read()
                     .map_err(|e| crate::error::AgentError::InternalError(e.to_string()))?
                 {
-                    if key.kind == KeyEventKind::Press {
-                        match key.code {
-                            KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
-                                break;
-                            }
-                            KeyCode::Enter => {
-                                let mut state = state_ui.lock().unwrap();
-                                if !state.input.is_empty() && !state.is_processing {
-                                    let msg = state.input.clone();
-                                    state.input.clear();
-                                    state.add_message(MessageRole::User, msg.clone());
-                                    state.is_processing = true;
-                                    state.set_status("Processing...".to_string());
-                                    drop(state);
-                                    
-                                    let input_tx = input_tx.clone();
-                                    tokio::spawn(async move {
-                                        let _ = input_tx.send(msg.into()).await;
-                                    });
-                                }
-                            }
-                            KeyCode::Char(c) => {
-                                let mut state = state_ui.lock().unwrap();
-                                state.input.push(c);
+                    Event::Key(key) if key.kind == KeyEventKind::Press => {
+                        let ctrl = key.modifiers.contains(event::KeyModifiers::CONTROL);
+                        let shift = key.modifiers.contains(event::KeyModifiers::SHIFT);
+                        let mut state = state_ui.lock().unwrap();
+                        let outcome = handle_key_with_shift(&mut state, key.code, ctrl, shift);
+                        drop(state);
+
+                        match outcome {
+                            KeyOutcome::Quit => break,
+                            KeyOutcome::Submit(msg) => {
+                                let input_tx = input_tx.clone();
+                                tokio::spawn(async move {
+                                    let _ = input_tx.send(msg.into()).await;
+                                });
                             }
-                            KeyCode::Backspace => {
-                                let mut state = state_ui.lock().unwrap();
-                                state.input.pop();
+                            KeyOutcome::Interrupt(msg) => {
+                                let controller = controller.clone();
+                                let input_tx = input_tx.clone();
+                                tokio::spawn(async move {
+                                    controller.interrupt().await;
+                                    let _ = input_tx.send(msg.into()).await;
+                                });
                             }
-                            _ => {}
+                            KeyOutcome::None => {}
                         }
                     }
+                    Event::Mouse(mouse) => {
+                        let shift = mouse.modifiers.contains(event::KeyModifiers::SHIFT);
+                        let mut state = state_ui.lock().unwrap();
+                        handle_mouse_scroll(&mut state, mouse.kind, mouse.column, mouse.row, shift);
+                    }
+                    _ => {}
                 }
             }
         }
         
         // Stop the agent
         controller.stop().await;
-        
+
+        // Persist input history, if a history file was configured
+        {
+            let state = self.state.lock().unwrap();
+            if let Some(path) = &state.history_path {
+                state.input_history.save(path);
+            }
+        }
+
         // Restore terminal
         disable_raw_mode().map_err(|e| crate::error::AgentError::InternalError(e.to_string()))?;
         execute!(
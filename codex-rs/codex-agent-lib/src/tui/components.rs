@@ -39,9 +39,18 @@ use ratatui::widgets::Paragraph;
 #[cfg(feature = "tui")]
 use ratatui::widgets::Wrap;
 
-/// Render the default layout with all components
+/// Render the default layout with all components. Returns the
+/// graphics-protocol escape bytes for an inline image, if `state.image` is
+/// set and the terminal supports one (see [`render_output`]) -- the
+/// caller must write them directly to the terminal after this frame is
+/// drawn, since they don't fit in ratatui's cell grid
 #[cfg(feature = "tui")]
-pub fn render_default_layout(frame: &mut Frame, area: Rect, state: &AppState, _title: &str) {
+pub fn render_default_layout(
+    frame: &mut Frame,
+    area: Rect,
+    state: &mut AppState,
+    _title: &str,
+) -> Option<(Rect, Vec<u8>)> {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
@@ -51,10 +60,16 @@ pub fn render_default_layout(frame: &mut Frame, area: Rect, state: &AppState, _t
             Constraint::Length(3),  // Input field
         ])
         .split(area);
-    
+
     // Render status bar
-    render_status(frame, chunks[0], &state.status, state.is_processing);
-    
+    render_status(
+        frame,
+        chunks[0],
+        &state.status,
+        state.is_processing,
+        state.queued_prompts.len(),
+    );
+
     // Split main content area
     let main_chunks = Layout::default()
         .direction(Direction::Horizontal)
@@ -63,10 +78,11 @@ pub fn render_default_layout(frame: &mut Frame, area: Rect, state: &AppState, _t
             Constraint::Percentage(40), // Todos and output
         ])
         .split(chunks[1]);
-    
+
     // Render messages
-    render_chat(frame, main_chunks[0], &state.messages);
-    
+    state.chat_area = main_chunks[0];
+    render_chat(frame, main_chunks[0], &state.messages, &mut state.chat_scroll);
+
     // Split right side
     let right_chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -75,21 +91,36 @@ pub fn render_default_layout(frame: &mut Frame, area: Rect, state: &AppState, _t
             Constraint::Percentage(60), // Tool output
         ])
         .split(main_chunks[1]);
-    
+
     // Render todos
     render_todos(frame, right_chunks[0], &state.todos);
-    
+
     // Render tool output
-    render_output(frame, right_chunks[1], &state.tool_output);
-    
+    state.output_area = right_chunks[1];
+    let pending_escape = render_output(frame, right_chunks[1], state);
+
+    // Render the log pane on top of the main content, if toggled on
+    if state.show_logs {
+        render_log_pane(frame, chunks[1], state);
+    }
+
     // Render input
-    render_input(frame, chunks[2], &state.input);
+    render_input(frame, chunks[2], &state.input, state.slash_completions());
+
+    pending_escape
 }
 
-/// Render the status bar
+/// Render the status bar. `queued` is the number of prompts buffered by
+/// [`BusyBehavior::Queue`](crate::tui::app::BusyBehavior::Queue), shown as a
+/// small "N queued" indicator alongside the status text when non-zero.
 #[cfg(feature = "tui")]
-pub fn render_status(frame: &mut Frame, area: Rect, status: &str, is_processing: bool) {
-    let status = Paragraph::new(status)
+pub fn render_status(frame: &mut Frame, area: Rect, status: &str, is_processing: bool, queued: usize) {
+    let text = if queued > 0 {
+        format!("{status}  ({queued} queued)")
+    } else {
+        status.to_string()
+    };
+    let status = Paragraph::new(text)
         .style(Style::default().fg(if is_processing {
             Color::Yellow
         } else {
@@ -99,9 +130,15 @@ pub fn render_status(frame: &mut Frame, area: Rect, status: &str, is_processing:
     frame.render_widget(status, area);
 }
 
-/// Render the chat messages
+/// Render the chat messages, honoring `scroll`'s offset instead of always
+/// tailing so earlier conversation stays reachable via `PageUp`/mouse wheel
 #[cfg(feature = "tui")]
-pub fn render_chat(frame: &mut Frame, area: Rect, messages: &[Message]) {
+pub fn render_chat(
+    frame: &mut Frame,
+    area: Rect,
+    messages: &[Message],
+    scroll: &mut crate::tui::app::ScrollState,
+) {
     let all_messages: Vec<ListItem> = messages
         .iter()
         .flat_map(|msg| {
@@ -110,17 +147,17 @@ pub fn render_chat(frame: &mut Frame, area: Rect, messages: &[Message]) {
                 MessageRole::Assistant => Style::default().fg(Color::White),
                 MessageRole::System => Style::default().fg(Color::Yellow),
             };
-            
+
             let prefix = match msg.role {
                 MessageRole::User => "You: ",
                 MessageRole::Assistant => "Assistant: ",
                 MessageRole::System => "System: ",
             };
-            
-            // Simple line wrapping
+
+            // Width-aware line wrapping (display columns, not bytes)
             let width = area.width.saturating_sub(4) as usize;
-            let wrapped = wrap_text(&msg.content, width);
-            
+            let wrapped = crate::wrap::wrap(&msg.content, width, crate::wrap::WrapMode::Optimal);
+
             wrapped
                 .into_iter()
                 .enumerate()
@@ -135,18 +172,18 @@ pub fn render_chat(frame: &mut Frame, area: Rect, messages: &[Message]) {
                 .collect::<Vec<_>>()
         })
         .collect();
-    
-    // Show only the most recent messages that fit
+
     let visible_height = area.height.saturating_sub(2) as usize;
-    let messages_to_show: Vec<ListItem> = if all_messages.len() > visible_height {
-        let skip_count = all_messages.len() - visible_height;
-        all_messages.into_iter().skip(skip_count).collect()
-    } else {
-        all_messages
-    };
-    
-    let messages_list = List::new(messages_to_show)
-        .block(Block::default().borders(Borders::ALL).title("Chat"));
+    let offset = scroll.reconcile(all_messages.len(), visible_height);
+    let messages_to_show: Vec<ListItem> = all_messages
+        .into_iter()
+        .skip(offset)
+        .take(visible_height)
+        .collect();
+
+    let title = if scroll.follow_tail { "Chat" } else { "Chat (scrolled)" };
+    let messages_list =
+        List::new(messages_to_show).block(Block::default().borders(Borders::ALL).title(title));
     frame.render_widget(messages_list, area);
 }
 
@@ -178,83 +215,132 @@ pub fn render_todos(frame: &mut Frame, area: Rect, todos: &[TodoItem]) {
     frame.render_widget(todos_list, area);
 }
 
-/// Render the tool output
+/// Render the tool output, preserving any ANSI color the command itself
+/// produced instead of re-colorizing lines with `starts_with`/`contains`
+/// heuristics, and honoring `state.output_scroll`'s offset instead of
+/// always tailing. If `state.image` is set, renders that image instead (via
+/// a terminal graphics protocol if one is detected, or a half-block
+/// downsample otherwise) and returns the escape-sequence bytes the caller
+/// must write directly to the terminal after this frame is drawn, since a
+/// graphics-protocol image doesn't fit in ratatui's cell grid
 #[cfg(feature = "tui")]
-pub fn render_output(frame: &mut Frame, area: Rect, output: &str) {
-    let output_lines: Vec<Line> = output
-        .lines()
-        .map(|line| {
-            if line.starts_with('$') {
-                Line::from(Span::styled(line, Style::default().fg(Color::Cyan)))
-            } else if line.starts_with('✓') {
-                Line::from(Span::styled(line, Style::default().fg(Color::Green)))
-            } else if line.starts_with("🔧") {
-                Line::from(Span::styled(line, Style::default().fg(Color::Yellow)))
-            } else if line.contains("error") || line.contains("Error") {
-                Line::from(Span::styled(line, Style::default().fg(Color::Red)))
-            } else {
-                Line::from(Span::styled(line, Style::default().fg(Color::Gray)))
+pub fn render_output(frame: &mut Frame, area: Rect, state: &mut AppState) -> Option<(Rect, Vec<u8>)> {
+    let block = Block::default().borders(Borders::ALL);
+
+    if let Some(image) = &state.image {
+        let block = block.title("Output");
+        let inner_area = block.inner(area);
+        frame.render_widget(block, area);
+
+        return match crate::tui::image::render_image(
+            &image.mime,
+            &image.data,
+            inner_area.width,
+            inner_area.height,
+            image.alt.as_deref(),
+        ) {
+            crate::tui::image::ImageRender::Escape(bytes) => Some((inner_area, bytes)),
+            crate::tui::image::ImageRender::Lines(lines) => {
+                frame.render_widget(Paragraph::new(lines), inner_area);
+                None
             }
-        })
-        .collect();
-    
-    // Show only the most recent output
+        };
+    }
+
+    let output_lines: Vec<Line> = crate::tui::ansi::ansi_to_lines(&state.tool_output);
+
     let visible_height = area.height.saturating_sub(2) as usize;
-    let output_to_show: Vec<Line> = if output_lines.len() > visible_height {
-        let skip_count = output_lines.len() - visible_height;
-        output_lines.into_iter().skip(skip_count).collect()
+    let offset = state.output_scroll.reconcile(output_lines.len(), visible_height);
+    let output_to_show: Vec<Line> = output_lines.into_iter().skip(offset).take(visible_height).collect();
+
+    let title = if state.output_scroll.follow_tail {
+        "Output"
     } else {
-        output_lines
+        "Output (scrolled)"
     };
-    
     let tool_output = Paragraph::new(output_to_show)
-        .block(Block::default().borders(Borders::ALL).title("Output"))
+        .block(block.title(title))
         .wrap(Wrap { trim: false });
     frame.render_widget(tool_output, area);
+    None
 }
 
-/// Render the input field
+/// Render the input field, overlaying a completion popup above it when
+/// `completions` is `Some` -- i.e. the input looks like an in-progress
+/// `/name` slash command (see [`AppState::slash_completions`])
 #[cfg(feature = "tui")]
-pub fn render_input(frame: &mut Frame, area: Rect, input: &str) {
-    let input = Paragraph::new(input)
+pub fn render_input(frame: &mut Frame, area: Rect, input: &str, completions: Option<Vec<&str>>) {
+    let input_widget = Paragraph::new(input)
         .style(Style::default().fg(Color::White))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Input (Enter to send, Ctrl+C to quit)"),
+                .title("Input (Enter to send, \u{2191}\u{2193} history, Ctrl+C to quit)"),
         );
-    frame.render_widget(input, area);
+    frame.render_widget(input_widget, area);
+
+    let Some(names) = completions else {
+        return;
+    };
+    if names.is_empty() {
+        return;
+    }
+
+    let height = (names.len() as u16 + 2).min(area.y);
+    let popup_area = Rect {
+        x: area.x,
+        y: area.y.saturating_sub(height),
+        width: area.width,
+        height,
+    };
+
+    let items: Vec<ListItem> = names
+        .into_iter()
+        .map(|name| ListItem::new(format!("/{name}")))
+        .collect();
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Commands")
+            .style(Style::default().fg(Color::Cyan)),
+    );
+    frame.render_widget(ratatui::widgets::Clear, popup_area);
+    frame.render_widget(list, popup_area);
 }
 
-// Helper function for text wrapping
+/// Render the log pane overlay: `state.log_buffer`'s records at or above
+/// `state.log_level_filter` (and matching `state.log_target_filter`, if
+/// set), each line color-coded by level via [`crate::tui::log::LogRecord::color`].
+/// Only called when `state.show_logs` is set (toggled by `F2`; `F3` cycles
+/// the level filter -- see [`AppState::toggle_logs`] and
+/// [`AppState::cycle_log_level`]).
 #[cfg(feature = "tui")]
-fn wrap_text(text: &str, width: usize) -> Vec<String> {
-    if text.is_empty() {
-        return vec![String::new()];
-    }
-    
-    let mut result = Vec::new();
-    let mut current_line = String::new();
-    
-    for word in text.split_whitespace() {
-        if current_line.is_empty() {
-            current_line = word.to_string();
-        } else if current_line.len() + word.len() + 1 < width {
-            current_line.push(' ');
-            current_line.push_str(word);
-        } else {
-            result.push(current_line);
-            current_line = word.to_string();
-        }
-    }
-    
-    if !current_line.is_empty() {
-        result.push(current_line);
-    }
-    
-    if result.is_empty() {
-        vec![String::new()]
-    } else {
-        result
-    }
-}
\ No newline at end of file
+pub fn render_log_pane(frame: &mut Frame, area: Rect, state: &AppState) {
+    let records = state
+        .log_buffer
+        .filtered(state.log_level_filter, state.log_target_filter.as_deref());
+
+    let visible = area.height.saturating_sub(2) as usize;
+    let start = records.len().saturating_sub(visible);
+
+    let items: Vec<ListItem> = records[start..]
+        .iter()
+        .map(|r| {
+            ListItem::new(Line::from(Span::styled(
+                format!("[{}] {}: {}", r.level, r.target, r.message),
+                Style::default().fg(r.color()),
+            )))
+        })
+        .collect();
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(
+                "Logs (F2 to close, F3 filter: {})",
+                state.log_level_filter
+            )),
+    );
+    frame.render_widget(ratatui::widgets::Clear, area);
+    frame.render_widget(list, area);
+}
@@ -0,0 +1,180 @@
+//! Inline image rendering for terminals that support a graphics protocol,
+//! with a Unicode half-block fallback for the ones that don't
+//!
+//! ratatui's cell grid has no notion of a pixel, so unlike
+//! [`crate::tui::ansi`] this can't just hand back styled [`Span`]s: Kitty
+//! and iTerm2 images are raw escape sequences written straight to the
+//! terminal, bypassing the grid entirely. [`render_image`] returns either
+//! those bytes (for the caller to write after the frame is drawn) or, for
+//! a plain terminal, ratatui [`Line`]s made of colored half-block
+//! characters -- each character cell covers two source pixels stacked
+//! vertically, using the foreground color for the top pixel and the
+//! background color for the bottom one.
+
+#[cfg(feature = "tui")]
+use ratatui::style::Color;
+#[cfg(feature = "tui")]
+use ratatui::style::Style;
+#[cfg(feature = "tui")]
+use ratatui::text::Line;
+#[cfg(feature = "tui")]
+use ratatui::text::Span;
+
+/// Which inline-image escape sequence the attached terminal understands
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphicsProtocol {
+    /// kitty's graphics protocol (APC `_G...` sequences)
+    Kitty,
+    /// iTerm2's inline image protocol (`OSC 1337 File=...`)
+    ITerm2,
+    /// Neither is supported; fall back to half-block rendering
+    None,
+}
+
+/// Detect which protocol the current terminal advertises via environment
+/// variables, the same signals `kitty`/iTerm2-aware tools check: a
+/// non-empty `KITTY_WINDOW_ID` means kitty (or a kitty-protocol-compatible
+/// terminal), and `TERM_PROGRAM=iTerm.app` means iTerm2
+#[cfg(feature = "tui")]
+pub fn detect_graphics_protocol() -> GraphicsProtocol {
+    if std::env::var("KITTY_WINDOW_ID").is_ok_and(|v| !v.is_empty()) {
+        GraphicsProtocol::Kitty
+    } else if std::env::var("TERM_PROGRAM").is_ok_and(|v| v == "iTerm.app") {
+        GraphicsProtocol::ITerm2
+    } else {
+        GraphicsProtocol::None
+    }
+}
+
+/// How to get an image onto the screen: either raw escape-sequence bytes
+/// the caller writes directly to the terminal after the ratatui frame is
+/// drawn, or plain styled lines for a terminal with no graphics protocol
+#[cfg(feature = "tui")]
+pub enum ImageRender {
+    /// Write these bytes straight to stdout; they don't occupy ratatui's
+    /// cell grid, so the caller must still reserve `rows` blank lines in
+    /// the layout for the terminal to draw into
+    Escape(Vec<u8>),
+    /// Render as ordinary text, e.g. via a `Paragraph`
+    Lines(Vec<Line<'static>>),
+}
+
+/// Render `data` (raw bytes of a `mime`-typed image) to fit within `cols`
+/// columns and `rows` rows, using whichever protocol
+/// [`detect_graphics_protocol`] reports -- or the half-block fallback if
+/// detection finds neither. Only the half-block path decodes `data`, and
+/// shows `alt` text instead when it fails to decode; the Kitty and iTerm2
+/// paths pass `data` to the terminal undecoded
+#[cfg(feature = "tui")]
+pub fn render_image(mime: &str, data: &[u8], cols: u16, rows: u16, alt: Option<&str>) -> ImageRender {
+    match detect_graphics_protocol() {
+        GraphicsProtocol::Kitty => ImageRender::Escape(kitty_escape(data, cols, rows)),
+        GraphicsProtocol::ITerm2 => ImageRender::Escape(iterm2_escape(mime, data, cols, rows)),
+        GraphicsProtocol::None => ImageRender::Lines(half_block_fallback(data, cols, rows, alt)),
+    }
+}
+
+/// Base64-encode `data` and chunk it per the kitty graphics protocol,
+/// which caps each `_G...;<payload>` escape at 4096 payload bytes and
+/// chains them with `m=1`/`m=0`
+#[cfg(feature = "tui")]
+fn kitty_escape(data: &[u8], cols: u16, rows: u16) -> Vec<u8> {
+    use base64::Engine;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(data);
+    let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(4096).collect();
+
+    let mut out = Vec::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        if i == 0 {
+            out.extend_from_slice(
+                format!("\x1b_Ga=T,f=100,c={cols},r={rows},m={more};").as_bytes(),
+            );
+        } else {
+            out.extend_from_slice(format!("\x1b_Gm={more};").as_bytes());
+        }
+        out.extend_from_slice(chunk);
+        out.extend_from_slice(b"\x1b\\");
+    }
+    out
+}
+
+/// Build an iTerm2 inline-image `OSC 1337 File=` sequence, sized in
+/// terminal cells via `width`/`height`
+#[cfg(feature = "tui")]
+fn iterm2_escape(mime: &str, data: &[u8], cols: u16, rows: u16) -> Vec<u8> {
+    use base64::Engine;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(data);
+    format!(
+        "\x1b]1337;File=inline=1;width={cols};height={rows};size={size};type={mime}:{encoded}\x07",
+        size = data.len(),
+    )
+    .into_bytes()
+}
+
+/// Decode `data` and downsample it to `cols`x`rows` half-block characters;
+/// each cell's foreground/background pair represents one vertical pixel
+/// pair, so the effective vertical resolution is `2 * rows`. On decode
+/// failure, falls back to a single line of `alt` text (or a placeholder)
+#[cfg(feature = "tui")]
+fn half_block_fallback(data: &[u8], cols: u16, rows: u16, alt: Option<&str>) -> Vec<Line<'static>> {
+    let cols = cols.max(1) as u32;
+    let rows = rows.max(1) as u32;
+
+    let img = match image::load_from_memory(data) {
+        Ok(img) => img,
+        Err(_) => {
+            let text = alt.map(str::to_string).unwrap_or_else(|| "[image]".to_string());
+            return vec![Line::from(text)];
+        }
+    };
+
+    let resized = img.resize_exact(cols, rows * 2, image::imageops::FilterType::Triangle).to_rgb8();
+
+    let mut lines = Vec::with_capacity(rows as usize);
+    for row in 0..rows {
+        let mut spans = Vec::with_capacity(cols as usize);
+        for col in 0..cols {
+            let top = resized.get_pixel(col, row * 2);
+            let bottom = resized.get_pixel(col, row * 2 + 1);
+            let style = Style::default()
+                .fg(Color::Rgb(top[0], top[1], top[2]))
+                .bg(Color::Rgb(bottom[0], bottom[1], bottom[2]));
+            spans.push(Span::styled("\u{2580}", style)); // ▀
+        }
+        lines.push(Line::from(spans));
+    }
+    lines
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kitty_escape_chunks_payload_and_terminates_chain() {
+        let data = vec![0u8; 10_000];
+        let escape = kitty_escape(&data, 40, 20);
+        let text = String::from_utf8_lossy(&escape);
+        assert!(text.starts_with("\x1b_Ga=T,f=100,c=40,r=20,m=1;"));
+        assert!(text.ends_with("\x1b\\"));
+        assert!(text.contains("m=0;"));
+    }
+
+    #[test]
+    fn iterm2_escape_carries_dimensions_and_mime() {
+        let escape = iterm2_escape("image/png", b"fakepng", 10, 5);
+        let text = String::from_utf8_lossy(&escape);
+        assert!(text.starts_with("\x1b]1337;File="));
+        assert!(text.contains("width=10;height=5"));
+        assert!(text.contains("type=image/png"));
+    }
+
+    #[test]
+    fn invalid_image_bytes_fall_back_to_alt_text() {
+        let lines = half_block_fallback(b"not an image", 10, 5, Some("a plot"));
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].spans[0].content, "a plot");
+    }
+}
@@ -0,0 +1,352 @@
+//! In-memory log ring buffer, `tracing` layer, and rolling file sink for
+//! the TUI's log pane
+//!
+//! The examples wire `tracing_subscriber::fmt` straight to a plain file,
+//! which means the only way to see internal/tool logs while the TUI is
+//! running is to `tail -f` that file in another terminal. [`TuiLogLayer`]
+//! is a `tracing_subscriber::Layer` that instead feeds every event into a
+//! bounded [`LogBuffer`] shared with [`super::app::AppState`], so
+//! [`super::components::render_log_pane`] can show a live, level-filterable
+//! tail in-app. [`RollingFileSink`] is the on-disk counterpart: a
+//! size-rotated writer that can still be handed to `tracing_subscriber::fmt`
+//! as its `with_writer`, so the file keeps a capped history instead of
+//! growing unbounded.
+//!
+//! ```ignore
+//! let tui = AgentTui::new();
+//! let sink = RollingFileSink::new("./logs", "codex-agent-lib", 1_000_000, 5)?;
+//! tracing_subscriber::registry()
+//!     .with(tui.log_layer())
+//!     .with(tracing_subscriber::fmt::layer().with_writer(sink).with_ansi(false))
+//!     .init();
+//! ```
+
+#[cfg(feature = "tui")]
+use ratatui::style::Color;
+#[cfg(feature = "tui")]
+use std::collections::VecDeque;
+#[cfg(feature = "tui")]
+use std::path::PathBuf;
+#[cfg(feature = "tui")]
+use std::sync::Arc;
+#[cfg(feature = "tui")]
+use std::sync::Mutex;
+#[cfg(feature = "tui")]
+use tracing::field::Field;
+#[cfg(feature = "tui")]
+use tracing::field::Visit;
+#[cfg(feature = "tui")]
+use tracing::Level;
+#[cfg(feature = "tui")]
+use tracing_subscriber::layer::Context;
+#[cfg(feature = "tui")]
+use tracing_subscriber::Layer;
+
+/// One event captured by [`TuiLogLayer`]
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+#[cfg(feature = "tui")]
+impl LogRecord {
+    /// The color [`super::components::render_log_pane`] renders this
+    /// record's level in
+    pub fn color(&self) -> Color {
+        match self.level {
+            Level::ERROR => Color::Red,
+            Level::WARN => Color::Yellow,
+            Level::INFO => Color::Green,
+            Level::DEBUG => Color::Cyan,
+            Level::TRACE => Color::DarkGray,
+        }
+    }
+}
+
+/// How verbose a [`Level`] is, lowest first, for threshold comparisons that
+/// don't depend on `Level`'s own (reversed) `Ord` impl
+#[cfg(feature = "tui")]
+fn verbosity(level: Level) -> u8 {
+    match level {
+        Level::ERROR => 0,
+        Level::WARN => 1,
+        Level::INFO => 2,
+        Level::DEBUG => 3,
+        Level::TRACE => 4,
+    }
+}
+
+/// Bounded ring buffer of [`LogRecord`]s, shared between [`TuiLogLayer`]
+/// (the producer, on whichever thread `tracing` dispatches an event on) and
+/// [`super::app::AppState`] (the consumer rendering the log pane)
+#[cfg(feature = "tui")]
+#[derive(Clone)]
+pub struct LogBuffer {
+    records: Arc<Mutex<VecDeque<LogRecord>>>,
+    capacity: usize,
+}
+
+#[cfg(feature = "tui")]
+impl LogBuffer {
+    /// Create a buffer holding at most `capacity` records, dropping the
+    /// oldest once full
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity: capacity.max(1),
+        }
+    }
+
+    fn push(&self, record: LogRecord) {
+        let mut records = self.records.lock().unwrap();
+        if records.len() >= self.capacity {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    /// Records at least as severe as `min_level` and, if `target_filter` is
+    /// set, whose target contains it, oldest first
+    pub fn filtered(&self, min_level: Level, target_filter: Option<&str>) -> Vec<LogRecord> {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| verbosity(r.level) <= verbosity(min_level))
+            .filter(|r| target_filter.map_or(true, |f| r.target.contains(f)))
+            .cloned()
+            .collect()
+    }
+}
+
+/// A `tracing_subscriber::Layer` that feeds every event into a [`LogBuffer`],
+/// alongside whatever other layers (e.g. `tracing_subscriber::fmt`) are also
+/// registered
+#[cfg(feature = "tui")]
+pub struct TuiLogLayer {
+    buffer: LogBuffer,
+}
+
+#[cfg(feature = "tui")]
+impl TuiLogLayer {
+    pub fn new(buffer: LogBuffer) -> Self {
+        Self { buffer }
+    }
+}
+
+#[cfg(feature = "tui")]
+impl<S: tracing::Subscriber> Layer<S> for TuiLogLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = MessageVisitor::default();
+        event.record(&mut message);
+        self.buffer.push(LogRecord {
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_string(),
+            message: message.0,
+        });
+    }
+}
+
+/// Extracts an event's `message` field (falling back to the first field
+/// recorded, for events that don't use the `message` shorthand)
+#[cfg(feature = "tui")]
+#[derive(Default)]
+struct MessageVisitor(String);
+
+#[cfg(feature = "tui")]
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0 = format!("{value:?}");
+        } else if self.0.is_empty() {
+            self.0 = format!("{}={value:?}", field.name());
+        }
+    }
+}
+
+/// Plain-text rolling file sink usable as a `tracing_subscriber::fmt`
+/// writer, independent of [`TuiLogLayer`]'s in-memory buffer so logs
+/// survive past the ring buffer's capacity. Rotates the active file once it
+/// exceeds `max_bytes`, shifting older rotations up by one numbered suffix
+/// and dropping whichever falls past `max_files`.
+#[cfg(feature = "tui")]
+#[derive(Clone)]
+pub struct RollingFileSink {
+    inner: Arc<Mutex<RollingFileSinkInner>>,
+}
+
+#[cfg(feature = "tui")]
+struct RollingFileSinkInner {
+    dir: PathBuf,
+    prefix: String,
+    max_bytes: u64,
+    max_files: usize,
+    file: std::fs::File,
+    bytes_written: u64,
+}
+
+#[cfg(feature = "tui")]
+impl RollingFileSink {
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        prefix: impl Into<String>,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        let prefix = prefix.into();
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(format!("{prefix}.log")))?;
+        let bytes_written = file.metadata()?.len();
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(RollingFileSinkInner {
+                dir,
+                prefix,
+                max_bytes: max_bytes.max(1),
+                max_files: max_files.max(1),
+                file,
+                bytes_written,
+            })),
+        })
+    }
+}
+
+#[cfg(feature = "tui")]
+impl RollingFileSinkInner {
+    fn active_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.log", self.prefix))
+    }
+
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        self.dir.join(format!("{}.{n}.log", self.prefix))
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let _ = std::fs::remove_file(self.rotated_path(self.max_files));
+        for n in (1..self.max_files).rev() {
+            if self.rotated_path(n).exists() {
+                std::fs::rename(self.rotated_path(n), self.rotated_path(n + 1))?;
+            }
+        }
+        std::fs::rename(self.active_path(), self.rotated_path(1))?;
+
+        self.file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.active_path())?;
+        self.bytes_written = 0;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "tui")]
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for RollingFileSink {
+    type Writer = RollingFileSink;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[cfg(feature = "tui")]
+impl std::io::Write for RollingFileSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.bytes_written + buf.len() as u64 > inner.max_bytes {
+            inner.rotate()?;
+        }
+        let written = inner.file.write(buf)?;
+        inner.bytes_written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.lock().unwrap().file.flush()
+    }
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+
+    fn record(level: Level, target: &str, message: &str) -> LogRecord {
+        LogRecord {
+            level,
+            target: target.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn buffer_drops_oldest_once_full() {
+        let buffer = LogBuffer::new(2);
+        buffer.push(record(Level::INFO, "a", "one"));
+        buffer.push(record(Level::INFO, "a", "two"));
+        buffer.push(record(Level::INFO, "a", "three"));
+
+        let messages: Vec<_> = buffer
+            .filtered(Level::TRACE, None)
+            .into_iter()
+            .map(|r| r.message)
+            .collect();
+        assert_eq!(messages, vec!["two", "three"]);
+    }
+
+    #[test]
+    fn filtered_excludes_records_more_verbose_than_min_level() {
+        let buffer = LogBuffer::new(10);
+        buffer.push(record(Level::ERROR, "a", "err"));
+        buffer.push(record(Level::DEBUG, "a", "dbg"));
+
+        let messages: Vec<_> = buffer
+            .filtered(Level::INFO, None)
+            .into_iter()
+            .map(|r| r.message)
+            .collect();
+        assert_eq!(messages, vec!["err"]);
+    }
+
+    #[test]
+    fn filtered_matches_target_substring() {
+        let buffer = LogBuffer::new(10);
+        buffer.push(record(Level::INFO, "codex_agent_lib::tool", "ran"));
+        buffer.push(record(Level::INFO, "codex_agent_lib::tui", "drew"));
+
+        let messages: Vec<_> = buffer
+            .filtered(Level::TRACE, Some("tool"))
+            .into_iter()
+            .map(|r| r.message)
+            .collect();
+        assert_eq!(messages, vec!["ran"]);
+    }
+
+    #[test]
+    fn rolling_sink_rotates_past_max_bytes() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::atomic::Ordering;
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let dir = std::env::temp_dir().join(format!(
+            "codex-log-sink-test-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let mut sink = RollingFileSink::new(&dir, "test", 8, 2).unwrap();
+
+        use std::io::Write;
+        sink.write_all(b"12345678").unwrap();
+        sink.write_all(b"more").unwrap();
+
+        assert!(dir.join("test.1.log").exists());
+        assert!(dir.join("test.log").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
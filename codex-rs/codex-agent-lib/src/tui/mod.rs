@@ -1,5 +1,8 @@
 //! TUI components for building interactive agent applications
 
+#[cfg(feature = "tui")]
+pub mod ansi;
+
 #[cfg(feature = "tui")]
 pub mod app;
 
@@ -9,14 +12,80 @@ pub mod components;
 #[cfg(feature = "tui")]
 pub mod event;
 
+#[cfg(feature = "tui")]
+pub mod image;
+
+#[cfg(feature = "tui")]
+pub mod log;
+
+#[cfg(feature = "tui")]
+pub mod slash;
+
+#[cfg(feature = "tui")]
+pub mod store;
+
+#[cfg(feature = "tui")]
+pub mod test_backend;
+
+#[cfg(feature = "tui")]
+pub use ansi::ansi_to_lines;
+
 #[cfg(feature = "tui")]
 pub use app::AgentTui;
 
 #[cfg(feature = "tui")]
 pub use app::AppState;
 
+#[cfg(feature = "tui")]
+pub use app::BusyBehavior;
+
 #[cfg(feature = "tui")]
 pub use app::Message;
 
 #[cfg(feature = "tui")]
-pub use app::MessageRole;
\ No newline at end of file
+pub use app::MessageRole;
+
+#[cfg(feature = "tui")]
+pub use app::ScrollState;
+
+#[cfg(feature = "tui")]
+pub use app::InputHistory;
+
+#[cfg(feature = "tui")]
+pub use log::LogBuffer;
+
+#[cfg(feature = "tui")]
+pub use log::LogRecord;
+
+#[cfg(feature = "tui")]
+pub use log::RollingFileSink;
+
+#[cfg(feature = "tui")]
+pub use log::TuiLogLayer;
+
+#[cfg(feature = "tui")]
+pub use slash::FileCommand;
+
+#[cfg(feature = "tui")]
+pub use slash::PromptCommand;
+
+#[cfg(feature = "tui")]
+pub use slash::ShellCommand;
+
+#[cfg(feature = "tui")]
+pub use slash::SlashCommand;
+
+#[cfg(feature = "tui")]
+pub use slash::SlashCommandRegistry;
+
+#[cfg(feature = "tui")]
+pub use store::JsonFileSessionStore;
+
+#[cfg(feature = "tui")]
+pub use store::SessionSnapshot;
+
+#[cfg(feature = "tui")]
+pub use store::SessionStore;
+
+#[cfg(feature = "tui")]
+pub use test_backend::TestHarness;
@@ -0,0 +1,204 @@
+//! Slash-command subsystem for [`super::AgentTui`]'s input field
+//!
+//! Typing `/` in the input field offers autocompletion over a registered
+//! set of [`SlashCommand`]s; submitting one expands it into the
+//! [`InputMessage`] actually sent to the agent instead of the literal
+//! `/name args` text, so a command can pull project context (a file, a
+//! saved prompt, a shell command's output) into the turn.
+
+#[cfg(feature = "tui")]
+use crate::message::InputMessage;
+
+/// A command triggered by typing `/name args` in the input field
+#[cfg(feature = "tui")]
+pub trait SlashCommand: Send + Sync {
+    /// The command's name, without the leading `/` (e.g. `"file"`)
+    fn name(&self) -> &str;
+
+    /// Completions for `partial_arg`, the text typed after the command
+    /// name. The input field's popup lists command names only and does not
+    /// call this. The default offers none, for
+    /// commands with free-form arguments (e.g. `/shell`).
+    fn complete(&self, partial_arg: &str) -> Vec<String> {
+        let _ = partial_arg;
+        Vec::new()
+    }
+
+    /// Expand `args` (the text typed after the command name) into the
+    /// message that should actually be sent to the agent
+    fn expand(&self, args: &str) -> InputMessage;
+}
+
+/// Registry of [`SlashCommand`]s available to an [`super::AgentTui`],
+/// consulted both for the `render_input` completion popup and to expand a
+/// command when the input is submitted
+#[cfg(feature = "tui")]
+#[derive(Default)]
+pub struct SlashCommandRegistry {
+    commands: Vec<Box<dyn SlashCommand>>,
+}
+
+#[cfg(feature = "tui")]
+impl SlashCommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a command, overriding any previously registered command of
+    /// the same name
+    pub fn register(&mut self, command: impl SlashCommand + 'static) {
+        self.commands.retain(|c| c.name() != command.name());
+        self.commands.push(Box::new(command));
+    }
+
+    /// Names of registered commands starting with `prefix`, for the
+    /// completion popup while the command name itself is being typed
+    pub fn matching(&self, prefix: &str) -> Vec<&str> {
+        self.commands
+            .iter()
+            .map(|c| c.name())
+            .filter(|name| name.starts_with(prefix))
+            .collect()
+    }
+
+    /// Look up a command by its exact name
+    pub fn find(&self, name: &str) -> Option<&dyn SlashCommand> {
+        self.commands.iter().find(|c| c.name() == name).map(|c| c.as_ref())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+/// Built-in `/file <path>` command: inlines a file's contents into the
+/// message, fenced as a code block
+#[cfg(feature = "tui")]
+pub struct FileCommand;
+
+#[cfg(feature = "tui")]
+impl SlashCommand for FileCommand {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn expand(&self, args: &str) -> InputMessage {
+        let path = args.trim();
+        match std::fs::read_to_string(path) {
+            Ok(contents) => format!("Contents of `{path}`:\n```\n{contents}\n```").into(),
+            Err(err) => format!("Could not read `{path}`: {err}").into(),
+        }
+    }
+}
+
+/// Built-in `/prompt <name>` command: expands one of a set of saved prompt
+/// templates registered ahead of time via [`PromptCommand::new`]
+#[cfg(feature = "tui")]
+pub struct PromptCommand {
+    templates: std::collections::HashMap<String, String>,
+}
+
+#[cfg(feature = "tui")]
+impl PromptCommand {
+    pub fn new(templates: std::collections::HashMap<String, String>) -> Self {
+        Self { templates }
+    }
+}
+
+#[cfg(feature = "tui")]
+impl SlashCommand for PromptCommand {
+    fn name(&self) -> &str {
+        "prompt"
+    }
+
+    fn complete(&self, partial_arg: &str) -> Vec<String> {
+        self.templates
+            .keys()
+            .filter(|name| name.starts_with(partial_arg))
+            .cloned()
+            .collect()
+    }
+
+    fn expand(&self, args: &str) -> InputMessage {
+        let name = args.trim();
+        match self.templates.get(name) {
+            Some(template) => template.clone().into(),
+            None => format!("No saved prompt named `{name}`").into(),
+        }
+    }
+}
+
+/// Built-in `/shell <cmd>` command: runs `cmd` through the system shell and
+/// inlines its captured output
+#[cfg(feature = "tui")]
+pub struct ShellCommand;
+
+#[cfg(feature = "tui")]
+impl SlashCommand for ShellCommand {
+    fn name(&self) -> &str {
+        "shell"
+    }
+
+    fn expand(&self, args: &str) -> InputMessage {
+        let cmd = args.trim();
+        let output = if cfg!(windows) {
+            std::process::Command::new("cmd").arg("/C").arg(cmd).output()
+        } else {
+            std::process::Command::new("sh").arg("-c").arg(cmd).output()
+        };
+
+        match output {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                if output.status.success() {
+                    format!("Output of `{cmd}`:\n```\n{stdout}\n```").into()
+                } else {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    format!("`{cmd}` failed:\n```\n{stdout}{stderr}\n```").into()
+                }
+            }
+            Err(err) => format!("Could not run `{cmd}`: {err}").into(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_filters_by_prefix() {
+        let mut registry = SlashCommandRegistry::new();
+        registry.register(FileCommand);
+        registry.register(ShellCommand);
+
+        let mut names = registry.matching("f");
+        names.sort_unstable();
+        assert_eq!(names, vec!["file"]);
+    }
+
+    #[test]
+    fn registering_same_name_twice_replaces_the_command() {
+        let mut registry = SlashCommandRegistry::new();
+        registry.register(FileCommand);
+        registry.register(FileCommand);
+        assert_eq!(registry.matching("file").len(), 1);
+    }
+
+    #[test]
+    fn prompt_command_expands_a_registered_template() {
+        let mut templates = std::collections::HashMap::new();
+        templates.insert("greeting".to_string(), "Say hello".to_string());
+        let command = PromptCommand::new(templates);
+
+        assert_eq!(command.expand("greeting").message, "Say hello");
+        assert!(command.expand("missing").message.contains("No saved prompt"));
+    }
+
+    #[test]
+    fn file_command_reports_an_unreadable_path_instead_of_panicking() {
+        let command = FileCommand;
+        let message = command.expand("/no/such/path-should-not-exist");
+        assert!(message.message.contains("Could not read"));
+    }
+}
@@ -0,0 +1,156 @@
+//! Session persistence for [`crate::tui::AgentTui`]: saving and restoring
+//! chat history and todos across restarts
+//!
+//! [`AppState`](crate::tui::AppState) otherwise keeps everything in RAM, so
+//! closing the TUI loses the whole conversation. [`SessionStore`] is the
+//! narrow save/load contract [`AgentTui`](crate::tui::AgentTui) checkpoints
+//! against after every chat/todo mutation; [`JsonFileSessionStore`] is the
+//! default implementation, and a caller that wants a database instead only
+//! needs to implement the trait.
+
+#[cfg(feature = "tui")]
+use crate::error::AgentError;
+#[cfg(feature = "tui")]
+use crate::message::TodoItem;
+#[cfg(feature = "tui")]
+use crate::tui::app::Message;
+#[cfg(feature = "tui")]
+use crate::Result;
+#[cfg(feature = "tui")]
+use serde::Deserialize;
+#[cfg(feature = "tui")]
+use serde::Serialize;
+#[cfg(feature = "tui")]
+use std::future::Future;
+#[cfg(feature = "tui")]
+use std::path::PathBuf;
+#[cfg(feature = "tui")]
+use std::pin::Pin;
+
+/// A point-in-time snapshot of the conversation, as persisted by a
+/// [`SessionStore`]
+#[cfg(feature = "tui")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    /// Chat history, in display order
+    pub messages: Vec<Message>,
+    /// Current todo list
+    pub todos: Vec<TodoItem>,
+}
+
+/// Saves and restores a [`SessionSnapshot`] by session id
+///
+/// Implementations are expected to be cheap to call after every message --
+/// [`AgentTui`](crate::tui::AgentTui) checkpoints on every
+/// [`AppState::add_message`](crate::tui::AppState::add_message) and
+/// [`AppState::update_todos`](crate::tui::AppState::update_todos) -- so a
+/// database-backed implementation should favor an upsert over a
+/// read-modify-write.
+#[cfg(feature = "tui")]
+pub trait SessionStore: Send + Sync {
+    /// Persist `snapshot` under `session_id`, replacing any previous save
+    fn save(
+        &self,
+        session_id: &str,
+        snapshot: &SessionSnapshot,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+
+    /// Load the most recent snapshot saved under `session_id`, or `None` if
+    /// nothing has been saved yet
+    fn load(&self, session_id: &str) -> Pin<Box<dyn Future<Output = Result<Option<SessionSnapshot>>> + Send>>;
+}
+
+/// Default [`SessionStore`]: one JSON file per session id, under a
+/// configured directory
+#[cfg(feature = "tui")]
+pub struct JsonFileSessionStore {
+    dir: PathBuf,
+}
+
+#[cfg(feature = "tui")]
+impl JsonFileSessionStore {
+    /// Snapshots are written to `dir/{session_id}.json`; `dir` is created
+    /// (including parents) on the first save if it doesn't already exist
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path_for(&self, session_id: &str) -> PathBuf {
+        self.dir.join(format!("{session_id}.json"))
+    }
+}
+
+#[cfg(feature = "tui")]
+impl SessionStore for JsonFileSessionStore {
+    fn save(
+        &self,
+        session_id: &str,
+        snapshot: &SessionSnapshot,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
+        let path = self.path_for(session_id);
+        let dir = self.dir.clone();
+        let json = serde_json::to_string_pretty(snapshot).map_err(|e| AgentError::InternalError(e.to_string()));
+        Box::pin(async move {
+            let json = json?;
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .map_err(|e| AgentError::InternalError(e.to_string()))?;
+            tokio::fs::write(&path, json)
+                .await
+                .map_err(|e| AgentError::InternalError(e.to_string()))?;
+            Ok(())
+        })
+    }
+
+    fn load(&self, session_id: &str) -> Pin<Box<dyn Future<Output = Result<Option<SessionSnapshot>>> + Send>> {
+        let path = self.path_for(session_id);
+        Box::pin(async move {
+            match tokio::fs::read_to_string(&path).await {
+                Ok(json) => {
+                    let snapshot = serde_json::from_str(&json)
+                        .map_err(|e| AgentError::InternalError(e.to_string()))?;
+                    Ok(Some(snapshot))
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(AgentError::InternalError(e.to_string())),
+            }
+        })
+    }
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+    use crate::tui::app::MessageRole;
+
+    #[tokio::test]
+    async fn load_returns_none_when_nothing_was_saved() {
+        let dir = std::env::temp_dir().join("codex-agent-lib-session-store-test-missing");
+        let store = JsonFileSessionStore::new(dir);
+
+        let loaded = store.load("no-such-session").await.unwrap();
+        assert!(loaded.is_none());
+    }
+
+    #[tokio::test]
+    async fn save_then_load_round_trips_the_snapshot() {
+        let dir = std::env::temp_dir().join(format!(
+            "codex-agent-lib-session-store-test-{}",
+            std::process::id()
+        ));
+        let store = JsonFileSessionStore::new(dir);
+        let snapshot = SessionSnapshot {
+            messages: vec![Message {
+                role: MessageRole::User,
+                content: "hello".to_string(),
+            }],
+            todos: Vec::new(),
+        };
+
+        store.save("session-1", &snapshot).await.unwrap();
+        let loaded = store.load("session-1").await.unwrap().expect("snapshot was saved");
+
+        assert_eq!(loaded.messages.len(), 1);
+        assert_eq!(loaded.messages[0].content, "hello");
+    }
+}
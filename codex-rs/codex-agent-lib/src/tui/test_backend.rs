@@ -0,0 +1,271 @@
+//! Headless `TestBackend` harness for `AgentTui`/`AppState`
+//!
+//! Mirrors the most valuable testing approach in the ratatui ecosystem:
+//! render into an in-memory cell buffer instead of a real terminal, feed
+//! synthetic key/resize events, and assert on the resulting grid (or a
+//! serialized text snapshot). This lets a test assert that a given
+//! [`crate::message::TodoItem`]/[`crate::tui::Message`] stream produces the
+//! expected layout, including scrollback and streaming-update behavior,
+//! without a pty.
+
+#[cfg(feature = "tui")]
+use crate::tui::app::AppState;
+#[cfg(feature = "tui")]
+use crate::tui::app::KeyOutcome;
+#[cfg(feature = "tui")]
+use crate::tui::app::handle_key;
+#[cfg(feature = "tui")]
+use crate::tui::app::handle_key_with_shift;
+#[cfg(feature = "tui")]
+use crate::tui::app::handle_mouse_scroll;
+#[cfg(feature = "tui")]
+use crossterm::event::KeyCode;
+#[cfg(feature = "tui")]
+use crossterm::event::MouseEventKind;
+#[cfg(feature = "tui")]
+use ratatui::Terminal;
+#[cfg(feature = "tui")]
+use ratatui::backend::TestBackend;
+
+/// Drives [`AppState`] plus the default layout through an in-memory buffer
+#[cfg(feature = "tui")]
+pub struct TestHarness {
+    terminal: Terminal<TestBackend>,
+    state: AppState,
+    title: String,
+}
+
+#[cfg(feature = "tui")]
+impl TestHarness {
+    /// Create a harness with a `width`x`height` in-memory buffer and a
+    /// freshly initialized [`AppState`]
+    pub fn new(width: u16, height: u16) -> Self {
+        let backend = TestBackend::new(width, height);
+        let terminal =
+            Terminal::new(backend).expect("constructing a Terminal over a TestBackend cannot fail");
+        Self {
+            terminal,
+            state: AppState::new(),
+            title: "Agent TUI".to_string(),
+        }
+    }
+
+    /// Replace the harness's starting state
+    pub fn with_state(mut self, state: AppState) -> Self {
+        self.state = state;
+        self
+    }
+
+    /// Set the title passed to `render_default_layout`
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// The current application state
+    pub fn state(&self) -> &AppState {
+        &self.state
+    }
+
+    /// Mutable access to the application state, for tests that want to seed
+    /// messages/todos directly rather than driving them through key presses
+    pub fn state_mut(&mut self) -> &mut AppState {
+        &mut self.state
+    }
+
+    /// Feed a single key press, applying the same handling
+    /// [`crate::tui::AgentTui::run`]'s raw-mode loop applies; returns the
+    /// outcome so a test can assert a message was (or wasn't) submitted
+    pub fn feed_key(&mut self, code: KeyCode) -> KeyOutcome {
+        handle_key(&mut self.state, code, false)
+    }
+
+    /// Feed a Ctrl+<char> key press
+    pub fn feed_ctrl_key(&mut self, code: KeyCode) -> KeyOutcome {
+        handle_key(&mut self.state, code, true)
+    }
+
+    /// Feed a Shift+<key> key press, for asserting accelerated scroll
+    pub fn feed_shift_key(&mut self, code: KeyCode) -> KeyOutcome {
+        handle_key_with_shift(&mut self.state, code, false, true)
+    }
+
+    /// Feed a mouse wheel tick at `(column, row)`
+    pub fn feed_mouse_scroll(&mut self, kind: MouseEventKind, column: u16, row: u16) {
+        handle_mouse_scroll(&mut self.state, kind, column, row, false);
+    }
+
+    /// Resize the in-memory buffer; the next `render` reflows into the new
+    /// dimensions
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.terminal.backend_mut().resize(width, height);
+    }
+
+    /// Render the current state into the in-memory buffer
+    pub fn render(&mut self) {
+        let state = &mut self.state;
+        let title = &self.title;
+        self.terminal
+            .draw(|f| {
+                crate::tui::components::render_default_layout(f, f.area(), state, title);
+            })
+            .expect("drawing to a TestBackend cannot fail");
+    }
+
+    /// Serialize the last rendered cell grid as plain text, one line per
+    /// row with trailing whitespace trimmed -- good enough for a snapshot
+    /// assertion via `assert_eq!` or golden-file comparison
+    pub fn snapshot(&self) -> String {
+        let buffer = self.terminal.backend().buffer();
+        let area = buffer.area();
+        (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buffer[(x, y)].symbol().to_string())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(all(test, feature = "tui"))]
+mod tests {
+    use super::*;
+    use crate::tui::app::MessageRole;
+
+    #[test]
+    fn typing_and_enter_submits_and_clears_input() {
+        let mut harness = TestHarness::new(40, 10);
+
+        for c in "hello".chars() {
+            harness.feed_key(KeyCode::Char(c));
+        }
+        assert_eq!(harness.state().input, "hello");
+
+        let outcome = harness.feed_key(KeyCode::Enter);
+        assert!(matches!(outcome, KeyOutcome::Submit(msg) if msg.message == "hello"));
+        assert!(harness.state().input.is_empty());
+        assert_eq!(harness.state().messages.last().unwrap().role, MessageRole::User);
+    }
+
+    #[test]
+    fn busy_do_nothing_drops_the_prompt() {
+        let mut state = AppState::new();
+        state.is_processing = true;
+        let mut harness = TestHarness::new(40, 10).with_state(state);
+
+        for c in "hello".chars() {
+            harness.feed_key(KeyCode::Char(c));
+        }
+        let outcome = harness.feed_key(KeyCode::Enter);
+
+        assert!(matches!(outcome, KeyOutcome::None));
+        assert!(harness.state().input.is_empty());
+        assert!(harness.state().queued_prompts.is_empty());
+    }
+
+    #[test]
+    fn busy_queue_buffers_the_prompt_instead_of_dropping_it() {
+        let mut state = AppState::new();
+        state.is_processing = true;
+        state.busy_behavior = crate::tui::app::BusyBehavior::Queue;
+        let mut harness = TestHarness::new(40, 10).with_state(state);
+
+        for c in "hello".chars() {
+            harness.feed_key(KeyCode::Char(c));
+        }
+        let outcome = harness.feed_key(KeyCode::Enter);
+
+        assert!(matches!(outcome, KeyOutcome::None));
+        assert_eq!(harness.state().queued_prompts.len(), 1);
+        assert_eq!(harness.state().messages.last().unwrap().role, MessageRole::User);
+    }
+
+    #[test]
+    fn busy_interrupt_returns_the_prompt_for_the_caller_to_dispatch() {
+        let mut state = AppState::new();
+        state.is_processing = true;
+        state.busy_behavior = crate::tui::app::BusyBehavior::Interrupt;
+        let mut harness = TestHarness::new(40, 10).with_state(state);
+
+        for c in "hello".chars() {
+            harness.feed_key(KeyCode::Char(c));
+        }
+        let outcome = harness.feed_key(KeyCode::Enter);
+
+        assert!(matches!(outcome, KeyOutcome::Interrupt(msg) if msg.message == "hello"));
+        assert!(harness.state().queued_prompts.is_empty());
+    }
+
+    #[test]
+    fn render_produces_a_non_empty_snapshot() {
+        let mut harness = TestHarness::new(40, 10).with_title("Test");
+        harness.state_mut().add_message(MessageRole::Assistant, "hi there".to_string());
+        harness.render();
+
+        let snapshot = harness.snapshot();
+        assert!(snapshot.contains("hi there"));
+    }
+
+    #[test]
+    fn ctrl_c_requests_quit() {
+        let mut harness = TestHarness::new(40, 10);
+        let outcome = harness.feed_ctrl_key(KeyCode::Char('c'));
+        assert!(matches!(outcome, KeyOutcome::Quit));
+    }
+
+    #[test]
+    fn page_up_disables_follow_tail_and_page_down_back_to_bottom_reenables_it() {
+        let mut harness = TestHarness::new(40, 10);
+        for i in 0..30 {
+            harness.state_mut().add_message(MessageRole::System, format!("line {i}"));
+        }
+        harness.render();
+        assert!(harness.state().chat_scroll.follow_tail);
+
+        harness.feed_key(KeyCode::PageUp);
+        assert!(!harness.state().chat_scroll.follow_tail);
+
+        // Page all the way back down; `reconcile` on the next render pins
+        // us to the tail once the offset reaches (or exceeds) the max.
+        for _ in 0..10 {
+            harness.feed_key(KeyCode::PageDown);
+        }
+        harness.render();
+        assert!(harness.state().chat_scroll.follow_tail);
+    }
+
+    #[test]
+    fn shift_page_up_scrolls_further_than_a_bare_page_up() {
+        let mut harness = TestHarness::new(40, 10);
+        for i in 0..30 {
+            harness.state_mut().add_message(MessageRole::System, format!("line {i}"));
+        }
+        harness.render();
+
+        harness.feed_key(KeyCode::PageUp);
+        let bare_offset = harness.state().chat_scroll.offset;
+
+        harness.state_mut().chat_scroll.follow_tail = true;
+        harness.render();
+        harness.feed_shift_key(KeyCode::PageUp);
+        let shift_offset = harness.state().chat_scroll.offset;
+
+        assert!(shift_offset < bare_offset);
+    }
+
+    #[test]
+    fn mouse_wheel_inside_output_pane_scrolls_output_not_chat() {
+        let mut harness = TestHarness::new(40, 10);
+        harness.render();
+        let output_area = harness.state().output_area;
+
+        harness.feed_mouse_scroll(MouseEventKind::ScrollUp, output_area.x + 1, output_area.y + 1);
+
+        assert!(!harness.state().output_scroll.follow_tail);
+        assert!(harness.state().chat_scroll.follow_tail);
+    }
+}
@@ -2,42 +2,51 @@
 
 #[cfg(feature = "utils")]
 pub mod output {
+    use crate::config::CommandInput;
+    use crate::config::Shell;
     use crate::message::OutputData;
     use crate::message::OutputMessage;
-    
+
     /// Strip ANSI escape codes from text
     #[cfg(feature = "utils")]
     pub fn clean_ansi(text: &str) -> String {
         let bytes = strip_ansi_escapes::strip(text);
         String::from_utf8_lossy(&bytes).to_string()
     }
-    
-    /// Extract shell commands from tool calls
+
+    /// Extract shell commands from tool calls, accepting the `command`
+    /// argument as either a shell string or a pre-split argv (formalized as
+    /// [`CommandInput`] so every caller parses both shapes the same way)
     pub fn extract_commands(msg: &OutputMessage) -> Vec<String> {
         match &msg.data {
             OutputData::ToolStart {
                 tool_name,
                 arguments,
-            } if tool_name == "shell" || tool_name == "bash" => {
-                // Try to extract command from arguments
-                if let Some(cmd) = arguments.get("command") {
-                    if let Some(cmd_str) = cmd.as_str() {
-                        return vec![cmd_str.to_string()];
-                    } else if let Some(cmd_array) = cmd.as_array() {
-                        let cmd_str = cmd_array
-                            .iter()
-                            .filter_map(|v| v.as_str())
-                            .collect::<Vec<_>>()
-                            .join(" ");
-                        return vec![cmd_str];
-                    }
-                }
-                Vec::new()
-            }
+            } if tool_name == "shell" || tool_name == "bash" => arguments
+                .get("command")
+                .and_then(CommandInput::from_value)
+                .map(|cmd| vec![cmd.display()])
+                .unwrap_or_default(),
             _ => Vec::new(),
         }
     }
-    
+
+    /// Wrap a `ToolStart`'s `command` argument into the argv that should
+    /// actually be spawned for the configured [`Shell`], if the tool is a
+    /// shell/bash invocation
+    pub fn command_argv(msg: &OutputMessage, shell: &Shell) -> Option<Vec<String>> {
+        match &msg.data {
+            OutputData::ToolStart {
+                tool_name,
+                arguments,
+            } if tool_name == "shell" || tool_name == "bash" => arguments
+                .get("command")
+                .and_then(CommandInput::from_value)
+                .map(|cmd| shell.wrap(cmd)),
+            _ => None,
+        }
+    }
+
     /// Format tool output for display with line limiting
     pub fn format_tool_output(output: &str, max_lines: usize) -> String {
         let lines: Vec<&str> = output.lines().collect();
@@ -71,77 +80,21 @@ pub mod output {
         }
     }
     
-    /// Smart text wrapping that preserves word boundaries
+    /// Smart text wrapping that preserves word boundaries, measuring each
+    /// word's display width (not its byte length) so CJK/emoji content
+    /// wraps at the right column. Defaults to the optimal-fit breaker; use
+    /// [`wrap_text_with_mode`] to opt into cheaper greedy wrapping.
     pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
-        if text.is_empty() || width == 0 {
-            return vec![String::new()];
-        }
-        
-        let mut result = Vec::new();
-        
-        for line in text.lines() {
-            if line.len() <= width {
-                result.push(line.to_string());
-            } else {
-                let mut current_line = String::new();
-                let mut current_width = 0;
-                
-                for word in line.split_whitespace() {
-                    let word_len = word.len();
-                    
-                    if current_width == 0 {
-                        // First word on the line
-                        if word_len > width {
-                            // Word is longer than width, break it
-                            let mut chars = word.chars();
-                            while current_width < width {
-                                if let Some(ch) = chars.next() {
-                                    current_line.push(ch);
-                                    current_width += 1;
-                                } else {
-                                    break;
-                                }
-                            }
-                            result.push(current_line.clone());
-                            current_line.clear();
-                            current_width = 0;
-                            
-                            // Handle remaining characters
-                            let remaining: String = chars.collect();
-                            if !remaining.is_empty() {
-                                for chunk in remaining.as_bytes().chunks(width) {
-                                    result.push(String::from_utf8_lossy(chunk).to_string());
-                                }
-                            }
-                        } else {
-                            current_line.push_str(word);
-                            current_width = word_len;
-                        }
-                    } else if current_width + 1 + word_len <= width {
-                        // Word fits on current line with space
-                        current_line.push(' ');
-                        current_line.push_str(word);
-                        current_width += 1 + word_len;
-                    } else {
-                        // Word doesn't fit, start new line
-                        result.push(current_line.clone());
-                        current_line.clear();
-                        current_line.push_str(word);
-                        current_width = word_len;
-                    }
-                }
-                
-                if !current_line.is_empty() {
-                    result.push(current_line);
-                }
-            }
-        }
-        
-        if result.is_empty() {
-            vec![String::new()]
-        } else {
-            result
-        }
+        wrap_text_with_mode(text, width, crate::wrap::WrapMode::Optimal)
+    }
+
+    /// Like [`wrap_text`], but lets the caller pick the wrap strategy
+    pub fn wrap_text_with_mode(
+        text: &str,
+        width: usize,
+        mode: crate::wrap::WrapMode,
+    ) -> Vec<String> {
+        crate::wrap::wrap(text, width, mode)
     }
     
     /// Check if a message contains tool execution
@@ -150,20 +103,22 @@ pub mod output {
             msg.data,
             OutputData::ToolStart { .. }
                 | OutputData::ToolOutput { .. }
+                | OutputData::ToolOutputDelta { .. }
                 | OutputData::ToolComplete { .. }
         )
     }
-    
+
     /// Extract tool name from a tool message
     pub fn get_tool_name(msg: &OutputMessage) -> Option<String> {
         match &msg.data {
             OutputData::ToolStart { tool_name, .. }
             | OutputData::ToolOutput { tool_name, .. }
+            | OutputData::ToolOutputDelta { tool_name, .. }
             | OutputData::ToolComplete { tool_name, .. } => Some(tool_name.clone()),
             _ => None,
         }
     }
-    
+
     /// Format a message for display
     pub fn format_message(msg: &OutputMessage) -> String {
         match &msg.data {
@@ -173,6 +128,9 @@ pub mod output {
             OutputData::ToolOutput { tool_name, output } => {
                 format!("📤 {}: {}", tool_name, output)
             }
+            OutputData::ToolOutputDelta { tool_name, chunk } => {
+                format!("📤 {}: {}", tool_name, chunk)
+            }
             OutputData::ToolComplete { tool_name, .. } => format!("✅ {} completed", tool_name),
             OutputData::Error(err) => format!("❌ Error: {:?}", err),
             OutputData::Completed => "✅ Completed".to_string(),
@@ -0,0 +1,332 @@
+//! Typed `uv` environment manager, registered as tools on [`AgentConfig`]
+//!
+//! The entire `uv` workflow (`uv --version`, `uv init`, `uv venv`,
+//! `uv pip install`, `uv run python`) used to live as fragile
+//! natural-language instructions in a system prompt, relying on the model
+//! remembering to prefix everything with `bash -c`. [`UvEnvironment`]
+//! shells out to `uv` itself with correct argument vectors, and
+//! [`UvEnvironment::tool_configs`] turns each operation into a
+//! [`ToolConfig::Custom`] entry so the model calls a structured
+//! `ensure_uv`/`init_project`/`create_venv`/`install`/`run_script`/
+//! `list_scripts` operation instead of emitting raw shell -- removing a
+//! whole class of "model forgot bash -c" failures.
+//!
+//! [`AgentConfig`]: crate::config::AgentConfig
+
+#[cfg(feature = "uv")]
+use crate::config::CustomToolHandler;
+#[cfg(feature = "uv")]
+use crate::config::ToolConfig;
+#[cfg(feature = "uv")]
+use std::path::PathBuf;
+#[cfg(feature = "uv")]
+use std::sync::Arc;
+#[cfg(feature = "uv")]
+use tokio::process::Command;
+#[cfg(feature = "uv")]
+use typed_builder::TypedBuilder;
+
+/// Knobs for the `uv`-backed tools a [`UvEnvironment`] registers
+#[cfg(feature = "uv")]
+#[derive(Debug, Clone, TypedBuilder)]
+pub struct UvConfig {
+    /// Project directory `uv` commands run in
+    #[builder(setter(into))]
+    pub working_directory: PathBuf,
+
+    /// Whether `uv`'s resolver/installer chatter (package resolution
+    /// progress, download logs) is surfaced in the tool's output; off by
+    /// default so routine installs don't spam the model with noise it has
+    /// no use for, can be flipped on for a verbose/debugging mode
+    #[builder(default = false)]
+    pub show_resolution: bool,
+}
+
+/// Shells out to `uv` on behalf of the agent, so the model calls structured
+/// operations (see module docs) rather than emitting raw shell
+#[cfg(feature = "uv")]
+pub struct UvEnvironment {
+    config: UvConfig,
+}
+
+#[cfg(feature = "uv")]
+impl UvEnvironment {
+    /// Create an environment rooted at `config.working_directory`
+    pub fn new(config: UvConfig) -> Arc<Self> {
+        Arc::new(Self { config })
+    }
+
+    /// `uv --version`, confirming `uv` is installed and on `PATH`
+    pub async fn ensure_uv(&self) -> Result<String, String> {
+        self.run(&["--version"]).await
+    }
+
+    /// `uv init`, scaffolding a new `pyproject.toml` in the working directory
+    pub async fn init_project(&self) -> Result<String, String> {
+        self.run(&["init"]).await
+    }
+
+    /// `uv venv`, creating the project's virtual environment
+    pub async fn create_venv(&self) -> Result<String, String> {
+        self.run(&["venv"]).await
+    }
+
+    /// `uv pip install <packages...>`
+    pub async fn install(&self, packages: Vec<String>) -> Result<String, String> {
+        let mut args = vec!["pip".to_string(), "install".to_string()];
+        args.extend(packages);
+        self.run_owned(args).await
+    }
+
+    /// Run `path` with `args` via `uv run python`; if `path` is `None`,
+    /// return the list of runnable scripts/entry points discovered in
+    /// `pyproject.toml` instead of failing, so the agent can offer choices
+    pub async fn run_script(
+        &self,
+        path: Option<String>,
+        args: Vec<String>,
+    ) -> Result<String, String> {
+        let Some(path) = path else {
+            let scripts = self.list_scripts().await?;
+            return Ok(if scripts.is_empty() {
+                "no runnable scripts found in pyproject.toml".to_string()
+            } else {
+                format!("available scripts: {}", scripts.join(", "))
+            });
+        };
+        let mut argv = vec!["run".to_string(), "python".to_string(), path];
+        argv.extend(args);
+        self.run_owned(argv).await
+    }
+
+    /// Entry points declared under `pyproject.toml`'s `[project.scripts]`
+    /// table, or an empty list if there's no `pyproject.toml` yet
+    pub async fn list_scripts(&self) -> Result<Vec<String>, String> {
+        let pyproject = self.config.working_directory.join("pyproject.toml");
+        let contents = match tokio::fs::read_to_string(&pyproject).await {
+            Ok(contents) => contents,
+            Err(_) => return Ok(Vec::new()),
+        };
+        let parsed: toml::Value = contents.parse().map_err(|e: toml::de::Error| e.to_string())?;
+        let scripts = parsed
+            .get("project")
+            .and_then(|project| project.get("scripts"))
+            .and_then(|scripts| scripts.as_table())
+            .map(|table| table.keys().cloned().collect())
+            .unwrap_or_default();
+        Ok(scripts)
+    }
+
+    async fn run_owned(&self, args: Vec<String>) -> Result<String, String> {
+        self.run(&args.iter().map(String::as_str).collect::<Vec<_>>())
+            .await
+    }
+
+    async fn run(&self, args: &[&str]) -> Result<String, String> {
+        let output = Command::new("uv")
+            .args(args)
+            .current_dir(&self.config.working_directory)
+            .output()
+            .await
+            .map_err(|e| format!("failed to spawn uv: {e}"))?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+        if !output.status.success() {
+            return Err(if stderr.trim().is_empty() {
+                stdout
+            } else {
+                stderr
+            });
+        }
+
+        if self.config.show_resolution {
+            Ok(format!("{stdout}{stderr}"))
+        } else {
+            Ok(strip_resolution_noise(&stdout))
+        }
+    }
+
+    /// Build the [`ToolConfig::Custom`] entries for every `uv` operation,
+    /// ready to hand to [`crate::config::AgentConfig`]'s `tools` builder
+    /// field; each handler closes over this [`Arc<UvEnvironment>`] so every
+    /// call runs against the same working directory
+    pub fn tool_configs(self: &Arc<Self>) -> Vec<ToolConfig> {
+        vec![
+            self.ensure_uv_tool(),
+            self.init_project_tool(),
+            self.create_venv_tool(),
+            self.install_tool(),
+            self.run_script_tool(),
+            self.list_scripts_tool(),
+        ]
+    }
+
+    fn ensure_uv_tool(self: &Arc<Self>) -> ToolConfig {
+        let env = self.clone();
+        ToolConfig::Custom {
+            name: "ensure_uv".to_string(),
+            description: "Confirm uv is installed and report its version".to_string(),
+            parameters: serde_json::json!({ "type": "object", "properties": {} }),
+            handler: handler(move |_args| {
+                let env = env.clone();
+                async move { env.ensure_uv().await }
+            }),
+            requires_approval: false,
+        }
+    }
+
+    fn init_project_tool(self: &Arc<Self>) -> ToolConfig {
+        let env = self.clone();
+        ToolConfig::Custom {
+            name: "init_project".to_string(),
+            description: "Scaffold a new pyproject.toml in the working directory".to_string(),
+            parameters: serde_json::json!({ "type": "object", "properties": {} }),
+            handler: handler(move |_args| {
+                let env = env.clone();
+                async move { env.init_project().await }
+            }),
+            requires_approval: true,
+        }
+    }
+
+    fn create_venv_tool(self: &Arc<Self>) -> ToolConfig {
+        let env = self.clone();
+        ToolConfig::Custom {
+            name: "create_venv".to_string(),
+            description: "Create the project's virtual environment".to_string(),
+            parameters: serde_json::json!({ "type": "object", "properties": {} }),
+            handler: handler(move |_args| {
+                let env = env.clone();
+                async move { env.create_venv().await }
+            }),
+            requires_approval: true,
+        }
+    }
+
+    fn install_tool(self: &Arc<Self>) -> ToolConfig {
+        let env = self.clone();
+        ToolConfig::Custom {
+            name: "install".to_string(),
+            description: "Install one or more packages into the project's environment"
+                .to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "packages": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Package names (and optional version specifiers) to install"
+                    }
+                },
+                "required": ["packages"]
+            }),
+            handler: handler(move |args| {
+                let env = env.clone();
+                async move {
+                    let packages = args
+                        .get("packages")
+                        .and_then(|v| v.as_array())
+                        .map(|items| {
+                            items
+                                .iter()
+                                .filter_map(|v| v.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    env.install(packages).await
+                }
+            }),
+            requires_approval: true,
+        }
+    }
+
+    fn run_script_tool(self: &Arc<Self>) -> ToolConfig {
+        let env = self.clone();
+        ToolConfig::Custom {
+            name: "run_script".to_string(),
+            description: "Run a Python script in the project's environment via `uv run`; \
+                           omit `path` to list runnable scripts/entry points instead"
+                .to_string(),
+            parameters: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Path to the script to run, relative to the working directory"
+                    },
+                    "args": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Arguments passed to the script"
+                    }
+                }
+            }),
+            handler: handler(move |args| {
+                let env = env.clone();
+                async move {
+                    let path = args
+                        .get("path")
+                        .and_then(|v| v.as_str())
+                        .map(str::to_string);
+                    let script_args = args
+                        .get("args")
+                        .and_then(|v| v.as_array())
+                        .map(|items| {
+                            items
+                                .iter()
+                                .filter_map(|v| v.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+                    env.run_script(path, script_args).await
+                }
+            }),
+            requires_approval: true,
+        }
+    }
+
+    fn list_scripts_tool(self: &Arc<Self>) -> ToolConfig {
+        let env = self.clone();
+        ToolConfig::Custom {
+            name: "list_scripts".to_string(),
+            description: "List runnable scripts/entry points declared in pyproject.toml"
+                .to_string(),
+            parameters: serde_json::json!({ "type": "object", "properties": {} }),
+            handler: handler(move |_args| {
+                let env = env.clone();
+                async move { env.list_scripts().await.map(|scripts| scripts.join(", ")) }
+            }),
+            requires_approval: false,
+        }
+    }
+}
+
+/// Adapt an `async fn(Value) -> Result<String, String>`-shaped closure into
+/// a [`CustomToolHandler`], so each tool builder above only has to write the
+/// operation's logic rather than the boxing/pinning boilerplate
+#[cfg(feature = "uv")]
+fn handler<F, Fut>(f: F) -> CustomToolHandler
+where
+    F: Fn(serde_json::Value) -> Fut + Send + Sync + 'static,
+    Fut: std::future::Future<Output = Result<String, String>> + Send + 'static,
+{
+    Arc::new(move |args| Box::pin(f(args)))
+}
+
+/// `uv`'s resolver/installer progress lines are prefixed distinctly enough
+/// (`Resolved`, `Downloaded`, `Installed`, `Prepared`, `Built`) to filter
+/// out without a real parser; this only runs when `show_resolution` is off.
+#[cfg(feature = "uv")]
+fn strip_resolution_noise(stdout: &str) -> String {
+    const NOISE_PREFIXES: &[&str] = &["Resolved ", "Downloaded ", "Installed ", "Prepared ", "Built "];
+    stdout
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim_start();
+            !NOISE_PREFIXES.iter().any(|prefix| trimmed.starts_with(prefix))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,250 @@
+//! Browser-based transport for running an [`Agent`] session, alongside the
+//! crossterm [`crate::tui::AgentTui`]
+//!
+//! Modeled on the Discord/Matrix connectors: the actual HTTP/WebSocket (or
+//! SSE) server is left to a [`WebTransport`] implementation (e.g. backed by
+//! `axum` or `warp`), so this crate stays free of a networking dependency.
+//! [`AgentWebUi`] owns only the dispatch loop and the bridge from
+//! `Agent::execute`'s output channel to every connected browser: a
+//! [`BrowserSender`] the agent pushes [`OutputData`] into, and
+//! [`WebTransport::serve`] fans it out to subscribers -- the same
+//! sender/subscriber split [`crate::connectors::discord::GatewayTransport`]
+//! uses for Discord. [`WebEvent`] is this transport's counterpart to
+//! [`crate::tui::event::TuiEvent`]: what a connected browser can send back.
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::Agent;
+use crate::agent::AgentController;
+use crate::error::AgentError;
+use crate::error::Result;
+use crate::message::OutputData;
+use crate::message::OutputMessage;
+use crate::message::TodoItem;
+
+/// Web counterpart to [`crate::tui::event::TuiEvent`]: what a connected
+/// browser client can send back to the running agent
+#[derive(Debug, Clone)]
+pub enum WebEvent {
+    /// A prompt submitted from the browser's input box
+    Prompt(String),
+    /// Interrupt the turn currently in flight
+    Interrupt,
+}
+
+/// One rendered chat message, as returned by the query endpoint
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct WebUiMessage {
+    pub role: &'static str,
+    pub content: String,
+}
+
+/// Snapshot of the current turn/tool state, returned by a transport's query
+/// endpoint as JSON so a freshly loaded page can render immediately instead
+/// of waiting for the next streamed update
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct WebUiState {
+    pub status: String,
+    pub messages: Vec<WebUiMessage>,
+    pub todos: Vec<TodoItem>,
+}
+
+/// Fans a running agent's [`OutputMessage`]s out to every connected browser
+/// client; the "sender" half of the pattern, handed to a [`WebTransport`]
+/// so it can subscribe once per connected client
+#[derive(Clone)]
+pub struct BrowserSender {
+    tx: broadcast::Sender<OutputMessage>,
+}
+
+impl BrowserSender {
+    /// Subscribe to the stream of [`OutputMessage`]s, e.g. to forward over
+    /// an SSE or WebSocket connection to one browser client
+    pub fn subscribe(&self) -> broadcast::Receiver<OutputMessage> {
+        self.tx.subscribe()
+    }
+}
+
+/// Capacity of a [`BrowserSender`]'s broadcast channel; a client that lags
+/// this far behind just misses the oldest messages, the same tradeoff
+/// [`crate::agent::AgentController::subscribe`] makes
+const BROWSER_BROADCAST_CAPACITY: usize = 256;
+
+/// Embedded HTTP server that serves a static page, streams
+/// [`OutputMessage`]s to connected browsers, answers the current
+/// [`WebUiState`] as JSON, and forwards browser-submitted [`WebEvent`]s back
+/// to the agent
+///
+/// Left as a trait (rather than a concrete `axum`/`warp` server) so this
+/// crate stays free of a networking dependency; see
+/// [`crate::connectors::discord::GatewayTransport`] for the same pattern
+/// applied to Discord's REST API.
+pub trait WebTransport: Send + Sync {
+    /// Start serving `bind_addr`, streaming `sender`'s broadcast to every
+    /// connected client, answering the query endpoint with `state`'s
+    /// current JSON encoding, and forwarding submitted [`WebEvent`]s to
+    /// `events_tx`
+    fn serve(
+        &self,
+        bind_addr: SocketAddr,
+        sender: BrowserSender,
+        state: Arc<RwLock<WebUiState>>,
+        events_tx: mpsc::Sender<WebEvent>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + '_>>;
+}
+
+/// Handle to a running [`AgentWebUi`] session
+pub struct AgentWebUiHandle {
+    controller: AgentController,
+    server_task: JoinHandle<Result<()>>,
+}
+
+impl AgentWebUiHandle {
+    /// The running agent's controller, e.g. to call
+    /// [`AgentController::stop`] from outside the session
+    pub fn controller(&self) -> &AgentController {
+        &self.controller
+    }
+
+    /// Wait for the embedded server to stop (normally only once
+    /// [`AgentController::stop`] is called and the transport shuts down)
+    pub async fn join(self) -> Result<()> {
+        self.server_task
+            .await
+            .map_err(|e| AgentError::InternalError(e.to_string()))?
+    }
+}
+
+/// Browser-based counterpart to [`crate::tui::AgentTui`]: runs the same
+/// [`Agent`] session, rendering it through a [`WebTransport`] instead of a
+/// crossterm terminal
+pub struct AgentWebUi {
+    bind_addr: SocketAddr,
+    transport: Arc<dyn WebTransport>,
+}
+
+impl AgentWebUi {
+    /// Create a web UI served by `transport`, bound to `127.0.0.1:0` (an
+    /// OS-assigned port) until [`Self::with_bind_addr`] sets one
+    pub fn new(transport: Arc<dyn WebTransport>) -> Self {
+        Self {
+            bind_addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+            transport,
+        }
+    }
+
+    /// Set the address the embedded HTTP server binds to
+    pub fn with_bind_addr(mut self, addr: SocketAddr) -> Self {
+        self.bind_addr = addr;
+        self
+    }
+
+    /// Run the web UI with the given agent, submitting `initial_prompt` (if
+    /// any) as the first turn
+    pub async fn run(
+        self,
+        agent: Agent,
+        initial_prompt: Option<String>,
+    ) -> Result<AgentWebUiHandle> {
+        let (input_tx, input_rx) = mpsc::channel(100);
+        let (plan_tx, mut plan_rx) = mpsc::channel(100);
+        let (output_tx, mut output_rx) = mpsc::channel(100);
+
+        let handle = agent.execute(input_rx, plan_tx, output_tx).await?;
+        let controller = handle.controller().clone();
+
+        let state = Arc::new(RwLock::new(WebUiState::default()));
+        let sender = BrowserSender {
+            tx: broadcast::channel(BROWSER_BROADCAST_CAPACITY).0,
+        };
+
+        let plan_state = state.clone();
+        tokio::spawn(async move {
+            while let Some(plan_msg) = plan_rx.recv().await {
+                plan_state.write().await.todos = plan_msg.todos;
+            }
+        });
+
+        let output_state = state.clone();
+        let output_sender = sender.clone();
+        tokio::spawn(async move {
+            // `handle` keeps the agent's conversation alive for as long as
+            // this task is draining its output, mirroring how
+            // `AgentTui::run`'s own output-handler task holds no separate
+            // reference but the loop it's in doesn't return until the UI
+            // quits.
+            let _handle = handle;
+            while let Some(message) = output_rx.recv().await {
+                apply_to_state(&output_state, &message.data).await;
+                let _ = output_sender.tx.send(message);
+            }
+        });
+
+        if let Some(prompt) = initial_prompt {
+            let _ = input_tx.send(prompt.into()).await;
+        }
+
+        let (events_tx, mut events_rx) = mpsc::channel(32);
+        let event_input_tx = input_tx.clone();
+        let event_controller = controller.clone();
+        tokio::spawn(async move {
+            while let Some(event) = events_rx.recv().await {
+                match event {
+                    WebEvent::Prompt(text) => {
+                        let _ = event_input_tx.send(text.into()).await;
+                    }
+                    WebEvent::Interrupt => event_controller.interrupt().await,
+                }
+            }
+        });
+
+        let server_task = tokio::spawn({
+            let transport = self.transport.clone();
+            let bind_addr = self.bind_addr;
+            let sender = sender.clone();
+            let state = state.clone();
+            async move { transport.serve(bind_addr, sender, state, events_tx).await }
+        });
+
+        Ok(AgentWebUiHandle {
+            controller,
+            server_task,
+        })
+    }
+}
+
+/// Fold one [`OutputData`] event into the shared [`WebUiState`], a reduced
+/// form of the translation [`crate::tui::AgentTui::run`]'s output-handler
+/// task applies to its own [`crate::tui::AppState`]: here an error only
+/// updates `status` and adds no chat message
+async fn apply_to_state(state: &Arc<RwLock<WebUiState>>, data: &OutputData) {
+    let mut state = state.write().await;
+    match data {
+        OutputData::Start => state.status = "Agent started".to_string(),
+        OutputData::Primary(msg) => state.messages.push(WebUiMessage {
+            role: "assistant",
+            content: msg.clone(),
+        }),
+        OutputData::PrimaryDelta(delta) => match state.messages.last_mut() {
+            Some(last) if last.role == "assistant" => last.content.push_str(delta),
+            _ => state.messages.push(WebUiMessage {
+                role: "assistant",
+                content: delta.clone(),
+            }),
+        },
+        OutputData::ToolStart { tool_name, .. } => {
+            state.status = format!("Running: {tool_name}");
+        }
+        OutputData::Completed => state.status = "Ready".to_string(),
+        OutputData::Error(err) => state.status = format!("Error: {err:?}"),
+        _ => {}
+    }
+}
@@ -0,0 +1,203 @@
+//! Unicode-width-aware line wrapping shared by [`crate::utils::output`] and
+//! [`crate::tui::components`], which both used to wrap by byte length and
+//! paid for it on any CJK or emoji content.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// How `wrap` lays words out into lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    /// Pack words onto a line until the next one doesn't fit. Cheap, but
+    /// can leave a line much shorter than `width` right before a long word
+    /// gets pushed to the next one.
+    Greedy,
+    /// Knuth-Plass-style optimal fit: minimizes the total squared slack
+    /// across all lines. Costs an O(words^2) pass but produces more even
+    /// right edges.
+    Optimal,
+}
+
+/// Wrap `text` to `width` display columns, measuring each word's width via
+/// `unicode-width` rather than its byte length. Existing newlines are hard
+/// breaks; a single word wider than `width` is split on grapheme-cluster
+/// boundaries so no line ever overflows.
+pub fn wrap(text: &str, width: usize, mode: WrapMode) -> Vec<String> {
+    if text.is_empty() || width == 0 {
+        return vec![String::new()];
+    }
+
+    let mut result = Vec::new();
+    for line in text.lines() {
+        let words = split_long_words(line, width);
+        if words.is_empty() {
+            result.push(String::new());
+            continue;
+        }
+        match mode {
+            WrapMode::Greedy => result.extend(wrap_greedy(&words, width)),
+            WrapMode::Optimal => result.extend(wrap_optimal(&words, width)),
+        }
+    }
+
+    if result.is_empty() {
+        vec![String::new()]
+    } else {
+        result
+    }
+}
+
+/// Split any word wider than `width` into grapheme-boundary chunks that each
+/// fit, so the line breakers below never have to special-case an oversized
+/// word
+fn split_long_words(line: &str, width: usize) -> Vec<String> {
+    let mut words = Vec::new();
+    for word in line.split_whitespace() {
+        if word.width() <= width {
+            words.push(word.to_string());
+            continue;
+        }
+
+        let mut current = String::new();
+        let mut current_width = 0;
+        for grapheme in word.graphemes(true) {
+            let grapheme_width = grapheme.width();
+            if current_width + grapheme_width > width && !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+                current_width = 0;
+            }
+            current.push_str(grapheme);
+            current_width += grapheme_width;
+        }
+        if !current.is_empty() {
+            words.push(current);
+        }
+    }
+    words
+}
+
+fn wrap_greedy(words: &[String], width: usize) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut current_width = 0;
+
+    for word in words {
+        let word_width = word.width();
+        if current.is_empty() {
+            current.push_str(word);
+            current_width = word_width;
+        } else if current_width + 1 + word_width <= width {
+            current.push(' ');
+            current.push_str(word);
+            current_width += 1 + word_width;
+        } else {
+            result.push(std::mem::take(&mut current));
+            current.push_str(word);
+            current_width = word_width;
+        }
+    }
+    if !current.is_empty() {
+        result.push(current);
+    }
+    result
+}
+
+/// DP over word indices where `cost[i]` is the minimum total penalty to lay
+/// out `words[i..]`. A candidate line holding `words[i..j]` with total
+/// width (plus inter-word spaces) `w` costs `(width - w)^2`, except the
+/// final line, which is free since there's no next line to balance against.
+/// Breakpoints are reconstructed from the `j` that minimized `cost[i]`.
+fn wrap_optimal(words: &[String], width: usize) -> Vec<String> {
+    let n = words.len();
+    let widths: Vec<usize> = words.iter().map(|w| w.width()).collect();
+
+    let mut cost = vec![0u64; n + 1];
+    let mut break_at = vec![n; n + 1];
+
+    for i in (0..n).rev() {
+        let mut best_cost = u64::MAX;
+        let mut best_j = i + 1;
+        let mut line_width = widths[i];
+        let mut j = i + 1;
+
+        loop {
+            let fits = line_width <= width;
+            // Only a lone word (a single grapheme wider than `width`) may
+            // overflow; a multi-word line that doesn't fit is no candidate.
+            if !fits && j > i + 1 {
+                break;
+            }
+            let is_last_line = j == n;
+            let penalty = if !fits || is_last_line {
+                0
+            } else {
+                let slack = (width - line_width) as u64;
+                slack * slack
+            };
+
+            let total = penalty.saturating_add(cost[j]);
+            if total < best_cost {
+                best_cost = total;
+                best_j = j;
+            }
+
+            if !fits || j >= n {
+                break;
+            }
+            line_width += 1 + widths[j];
+            j += 1;
+        }
+
+        cost[i] = best_cost;
+        break_at[i] = best_j;
+    }
+
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < n {
+        let j = break_at[i];
+        result.push(words[i..j].join(" "));
+        i = j;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn greedy_respects_width_in_display_columns() {
+        let text = "These are some words that need to wrap at a narrow width";
+        let wrapped = wrap(text, 20, WrapMode::Greedy);
+        assert!(wrapped.iter().all(|line| line.width() <= 20));
+    }
+
+    #[test]
+    fn optimal_respects_width_in_display_columns() {
+        let text = "These are some words that need to wrap at a narrow width";
+        let wrapped = wrap(text, 20, WrapMode::Optimal);
+        assert!(wrapped.iter().all(|line| line.width() <= 20));
+    }
+
+    #[test]
+    fn wide_characters_count_by_display_width_not_bytes() {
+        // Each CJK character is 3 bytes in UTF-8 but 2 display columns wide
+        let text = "你好世界你好世界你好世界";
+        let wrapped = wrap(text, 6, WrapMode::Optimal);
+        assert!(wrapped.iter().all(|line| line.width() <= 6));
+        assert!(wrapped.len() > 1);
+    }
+
+    #[test]
+    fn oversized_word_is_hard_split_on_grapheme_boundaries() {
+        let text = "supercalifragilisticexpialidocious";
+        let wrapped = wrap(text, 10, WrapMode::Optimal);
+        assert!(wrapped.iter().all(|line| line.width() <= 10));
+    }
+
+    #[test]
+    fn short_text_is_unwrapped() {
+        assert_eq!(wrap("Short", 20, WrapMode::Optimal), vec!["Short"]);
+    }
+}